@@ -0,0 +1,95 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use noise::{NoiseFn, Perlin};
+use wgpu_voxels::objects::block::Block;
+use wgpu_voxels::world::chunk::Chunk;
+use wgpu_voxels::world::chunk_section::ChunkSection;
+use wgpu_voxels::world::noise_terrain_generator::NoiseTerrainGenerator;
+use wgpu_voxels::world::terrain_generator::TerrainGenerator;
+use wgpu_voxels::WorldConfig;
+
+// Meshes whole chunks the way a mesh job does, for terrain that's cheap, typical and expensive to
+// mesh. Each chunk's neighbors come from the same terrain so its borders are culled like in game.
+// Vertex counts are printed once up front, since a faster mesher that emits more vertices isn't
+// necessarily a win.
+//
+// Run with `cargo bench --bench meshing`.
+
+// Grass over a few layers of dirt over stone; every section is full or empty.
+fn flat(_position: (i32, i32)) -> Vec<u8> {
+    blocks(|_, y, _| match y {
+        0 => Block::Bedrock.id,
+        1..=59 => Block::Stone.id,
+        60..=63 => Block::Dirt.id,
+        64 => Block::Grass.id,
+        _ => Block::Air.id,
+    })
+}
+
+// Stone up to y = 128 riddled with tunnels, far more exposed faces per block than the surface.
+fn caves(position: (i32, i32)) -> Vec<u8> {
+    let noise = Perlin::new(7);
+    let (wx, wz) = Chunk::local_to_world_position(position);
+
+    blocks(|x, y, z| {
+        let p = [(wx + x) as f64 * 0.08, y as f64 * 0.08, (wz + z) as f64 * 0.08];
+
+        match y {
+            0 => Block::Bedrock.id,
+            1..=127 if noise.get(p) < 0.2 => Block::Stone.id,
+            _ => Block::Air.id,
+        }
+    })
+}
+
+fn blocks(block: impl Fn(i32, i32, i32) -> u8) -> Vec<u8> {
+    let mut blocks = vec![Block::Air.id; Chunk::SIZE as usize];
+
+    for (i, b) in blocks.iter_mut().enumerate() {
+        let (x, y, z) = Chunk::index_to_xyz(i);
+        *b = block(x, y, z);
+    }
+
+    blocks
+}
+
+// The chunk at the origin with all four neighbors set.
+fn chunk(terrain: &dyn Fn((i32, i32)) -> Vec<u8>) -> Chunk {
+    let mut chunk = Chunk::from_blocks((0, 0), &terrain((0, 0)));
+
+    chunk.set_left(&ChunkSection::split(&terrain((-1, 0))));
+    chunk.set_right(&ChunkSection::split(&terrain((1, 0))));
+    chunk.set_front(&ChunkSection::split(&terrain((0, -1))));
+    chunk.set_back(&ChunkSection::split(&terrain((0, 1))));
+
+    chunk
+}
+
+fn meshing(c: &mut Criterion) {
+    let generator = NoiseTerrainGenerator::new(&WorldConfig::default());
+    let noisy = |position| generator.generate(position);
+
+    let chunks = [
+        ("flat", chunk(&flat)),
+        ("noisy", chunk(&noisy)),
+        ("caves", chunk(&caves)),
+    ];
+
+    let mut group = c.benchmark_group("build_meshes");
+    group.throughput(Throughput::Elements(Chunk::SIZE as u64));
+
+    for (name, chunk) in chunks.iter() {
+        let meshes = chunk.build_meshes().unwrap();
+        let vertices: usize = meshes.iter().map(|m| m.vertices.len() + m.alpha_vertices.len()).sum();
+
+        println!("{}: {} vertices", name, vertices);
+
+        group.bench_with_input(BenchmarkId::from_parameter(name), chunk, |b, chunk| {
+            b.iter(|| chunk.build_meshes().unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, meshing);
+criterion_main!(benches);
\ No newline at end of file
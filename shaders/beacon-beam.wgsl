@@ -0,0 +1,73 @@
+struct Camera {
+    @location(0) position: vec4<f32>,
+    @location(1) projection: mat4x4<f32>,
+}
+
+struct Beacon {
+    @location(0) time: f32,
+}
+
+struct InstanceInput {
+    @location(0) base: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) across: f32,
+    @location(1) along: f32,
+    @location(2) height: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+@group(1) @binding(0)
+var<uniform> beacon: Beacon;
+
+let BEAM_HEIGHT: f32 = 320.0;
+let BEAM_WIDTH: f32 = 0.35;
+
+// One quad per beacon, turned around the vertical axis to face the camera. It widens with distance
+// so the beam stays a few pixels wide when seen from far away.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+
+    let corner = corners[index];
+    let to_camera = camera.position.xz - instance.base.xz;
+    let distance = max(length(to_camera), 0.001);
+    let facing = to_camera / distance;
+    let right = vec3<f32>(-facing.y, 0.0, facing.x);
+    let width = max(BEAM_WIDTH, distance * 0.004);
+
+    let position = instance.base + right * corner.x * width + vec3<f32>(0.0, corner.y * BEAM_HEIGHT, 0.0);
+
+    out.across = corner.x;
+    out.along = corner.y;
+    out.height = position.y;
+    out.clip_position = camera.projection * vec4<f32>(position, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let core = pow(1.0 - abs(in.across), 2.0);
+    let bands = 0.75 + 0.25 * sin(in.height * 0.35 - beacon.time * 4.0);
+    let pulse = 0.85 + 0.15 * sin(beacon.time * 1.7);
+    let fade = pow(1.0 - in.along, 0.6);
+
+    let strength = core * bands * pulse * fade;
+    let color = mix(vec3<f32>(0.3, 0.85, 1.0), vec3<f32>(1.0, 1.0, 1.0), core * core);
+
+    return vec4<f32>(color * strength, strength);
+}
@@ -49,3 +49,18 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         return vec4<f32>(1.0, 1.0, 1.0, 0.0);
     }
 }
+
+// Used where line polygons aren't supported. The box is drawn filled and only fragments within
+// about a pixel of two of its faces, which is along an edge, are kept.
+@fragment
+fn fs_outline(in: VertexOutput) -> @location(0) vec4<f32> {
+    let p = in.block_position;
+    let edge = min(p, 1.0 - p) / max(fwidth(p), vec3<f32>(0.0001));
+    let near = u32(edge.x < 1.0) + u32(edge.y < 1.0) + u32(edge.z < 1.0);
+
+    if (in.target_position.y < 0.0 || near < 2u) {
+        discard;
+    }
+
+    return vec4<f32>(1.0, 1.0, 0.0, 1.0);
+}
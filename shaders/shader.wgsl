@@ -3,16 +3,20 @@ struct Camera {
     @location(1) projection: mat4x4<f32>,
 }
 
+// See `mesh::vertex::Vertex` for the packing.
 struct VertexInput {
-    @location(0) position: vec3<f32>,
-    @location(1) uv: vec2<f32>,
-    @location(2) opacity: f32,
+    @location(0) position: u32,
+    @location(1) uv: u32,
+    @location(2) origin: vec3<f32>,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) uv: vec2<f32>,
     @location(1) opacity: f32,
+    @location(2) world_position: vec3<f32>,
+    @location(3) overlay_uv: vec2<f32>,
+    @location(4) overlay: f32,
 };
 
 @group(0) @binding(0)
@@ -22,9 +26,19 @@ var<uniform> camera: Camera;
 fn vs_main(model: VertexInput) -> VertexOutput {
     var out: VertexOutput;
 
-    out.uv = model.uv;
-    out.opacity = model.opacity;
-    out.clip_position = camera.projection * vec4<f32>(model.position, 1.0);
+    let local = vec3<f32>(
+        f32(model.position & 255u),
+        f32((model.position >> 8u) & 255u),
+        f32((model.position >> 16u) & 255u),
+    ) / 10.0;
+    let position = model.origin + local;
+
+    out.uv = vec2<f32>(f32(model.uv & 31u), f32((model.uv >> 5u) & 31u)) / 16.0;
+    out.overlay_uv = vec2<f32>(f32((model.uv >> 10u) & 31u), f32((model.uv >> 15u) & 31u)) / 16.0;
+    out.overlay = f32((model.uv >> 20u) & 1u);
+    out.opacity = f32(model.position >> 24u) / 255.0;
+    out.world_position = position;
+    out.clip_position = camera.projection * vec4<f32>(position, 1.0);
 
     return out;
 }
@@ -38,6 +52,25 @@ var s_diffuse: sampler;
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let color = textureSample(t_diffuse, s_diffuse, in.uv);
+    // Sampled for every face since samples have to stay in uniform control flow.
+    let overlay = textureSample(t_diffuse, s_diffuse, in.overlay_uv);
+
+    return vec4<f32>(mix(color.xyz, overlay.xyz, overlay.a * in.overlay), in.opacity);
+}
 
-    return vec4<f32>(color.xyz, in.opacity);
+@fragment
+fn fs_normals(in: VertexOutput) -> @location(0) vec4<f32> {
+    let normal = normalize(cross(dpdx(in.world_position), dpdy(in.world_position)));
+
+    return vec4<f32>(abs(normal), 1.0);
+}
+
+@fragment
+fn fs_uvs(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(fract(in.uv * 16.0), 0.0, 1.0);
+}
+
+@fragment
+fn fs_overdraw(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(0.1, 0.04, 0.01, 1.0);
 }
@@ -0,0 +1,53 @@
+struct SpeedLinesUniform {
+    intensity: f32,
+    time: f32,
+    aspect: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> speed_lines: SpeedLinesUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    out.position = uv * 2.0 - 1.0;
+    out.clip_position = vec4<f32>(out.position, 0.0, 1.0);
+
+    return out;
+}
+
+fn hash(n: f32) -> f32 {
+    return fract(sin(n) * 43758.5453);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let p = vec2<f32>(in.position.x * speed_lines.aspect, in.position.y);
+    let radius = length(p);
+
+    // The screen is split into thin wedges around its center; each wedge may hold one streak,
+    // re-rolled a few times a second so the lines flicker instead of sitting still.
+    let wedge = (atan2(p.y, p.x) / 6.2831853 + 0.5) * 96.0;
+    let cell = floor(wedge);
+    let cycle = floor(speed_lines.time * 3.0 + hash(cell) * 7.0);
+    let seed = hash(cell * 13.7 + cycle);
+
+    let width = 0.08 + 0.12 * hash(seed * 91.3);
+    let streak = 1.0 - smoothstep(0.0, width, abs(fract(wedge) - 0.5));
+
+    // Streaks only reach in from the edges so the center of the view stays clear.
+    let start = 0.75 + 0.35 * hash(seed * 17.1);
+    let edge = smoothstep(start, start + 0.4, radius);
+
+    let alpha = streak * edge * step(0.55, seed) * speed_lines.intensity * 0.35;
+
+    return vec4<f32>(1.0, 1.0, 1.0, alpha);
+}
@@ -0,0 +1,115 @@
+struct Camera {
+    @location(0) position: vec4<f32>,
+    @location(1) projection: mat4x4<f32>,
+}
+
+struct Water {
+    @location(0) time: f32,
+    @location(1) znear: f32,
+    @location(2) zfar: f32,
+}
+
+// See `mesh::vertex::Vertex` for the packing.
+struct VertexInput {
+    @location(0) position: u32,
+    @location(1) uv: u32,
+    @location(2) origin: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) opacity: f32,
+    @location(2) world_position: vec3<f32>,
+    @location(3) shore: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+@group(1) @binding(0)
+var<uniform> water: Water;
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    let local = vec3<f32>(
+        f32(model.position & 255u),
+        f32((model.position >> 8u) & 255u),
+        f32((model.position >> 16u) & 255u),
+    ) / 10.0;
+    let position = model.origin + local;
+
+    out.uv = vec2<f32>(f32(model.uv & 31u), f32((model.uv >> 5u) & 31u)) / 16.0;
+    out.opacity = f32(model.position >> 24u) / 255.0;
+    out.shore = f32((model.uv >> 21u) & 1u);
+    out.world_position = position;
+    out.clip_position = camera.projection * vec4<f32>(position, 1.0);
+
+    return out;
+}
+
+@group(2) @binding(0)
+var t_diffuse: texture_2d<f32>;
+
+@group(2) @binding(1)
+var s_diffuse: sampler;
+
+@group(3) @binding(0)
+var t_scene: texture_2d<f32>;
+
+@group(3) @binding(1)
+var s_scene: sampler;
+
+@group(3) @binding(2)
+var t_depth: texture_depth_2d;
+
+// The block projection maps depth like OpenGL, so invert that mapping here.
+fn linear_depth(depth: f32) -> f32 {
+    let n = water.znear;
+    let f = water.zfar;
+
+    return 2.0 * f * n / ((f + n) - depth * (f - n));
+}
+
+fn scene_depth(uv: vec2<f32>, dims: vec2<f32>) -> f32 {
+    let texel = clamp(vec2<i32>(uv * dims), vec2<i32>(0, 0), vec2<i32>(dims) - vec2<i32>(1, 1));
+
+    return linear_depth(textureLoad(t_depth, texel, 0));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let dims = vec2<f32>(textureDimensions(t_scene));
+    let screen_uv = in.clip_position.xy / dims;
+    let water_depth = linear_depth(in.clip_position.z);
+
+    let wave = vec2<f32>(
+        sin(in.world_position.x * 1.7 + in.world_position.z * 0.6 + water.time * 1.5),
+        cos(in.world_position.z * 1.9 - in.world_position.x * 0.4 + water.time * 1.3),
+    ) * 0.01;
+
+    var uv = screen_uv + wave;
+    var floor_depth = scene_depth(uv, dims);
+
+    // Don't pull in geometry that sits in front of the water surface.
+    if (floor_depth < water_depth) {
+        uv = screen_uv;
+        floor_depth = scene_depth(uv, dims);
+    }
+
+    let refracted = textureSample(t_scene, s_scene, uv).rgb;
+    let tint = textureSample(t_diffuse, s_diffuse, in.uv).rgb;
+
+    let thickness = max(floor_depth - water_depth, 0.0);
+    let absorption = clamp(1.0 - exp(-thickness * 0.2), 1.0 - in.opacity, 1.0);
+
+    // `shore` is 1 at corners along a solid block and fades across the face, so a band of foam
+    // hugs the shore and slowly washes in and out.
+    let ripple = sin(in.world_position.x * 2.3 + in.world_position.z * 1.7 + water.time * 2.0) * 0.5 + 0.5;
+    let edge = 1.0 - 0.35 * (0.6 + 0.4 * sin(water.time * 0.8));
+    let foam = smoothstep(edge - 0.25, edge, in.shore) * (0.6 + 0.4 * ripple);
+
+    return vec4<f32>(mix(mix(refracted, tint, absorption), vec3<f32>(0.9, 0.95, 1.0), foam * 0.8), 1.0);
+}
@@ -1,28 +1,46 @@
 use std::time::Duration;
-use cgmath::{Deg, Rad, vec3};
+use cgmath::Vector3;
 use crossbeam::channel::Sender;
 use winit::event::{DeviceEvent, ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
 use winit::window::Window;
-use crate::{EventLoopRequest, State};
+use crate::window::event_loop_request::EventLoopRequest;
+use crate::window::state::State;
 use crate::scene::camera::Camera;
+use crate::objects::block::Block;
 use crate::scene::camera_uniform::CameraUniform;
 use crate::scene::projection::Projection;
 use crate::engine::block_pipeline::BlockPipeline;
 use crate::engine::block_target_pipeline::BlockTargetPipeline;
 use crate::engine::hotbar_pipeline::HotbarPipeline;
+use crate::engine::water_pipeline::WaterPipeline;
+use crate::engine::speed_lines_pipeline::SpeedLinesPipeline;
+use crate::engine::beacon_pipeline::BeaconPipeline;
+use crate::engine::frame_capture::FrameCapture;
+use crate::scene::photo_mode::PhotoMode;
+use crate::scene::golden_scene::GoldenScene;
+use anyhow::Context;
+use crate::scene::sequence::Sequence;
+use crate::window::graphics_preset::{GraphicsBenchmark, GraphicsPreset};
+use crate::world::world_stats::WorldStats;
+use crate::world::worldgen_config::WorldgenConfig;
 
 pub struct App {
     state: State,
     block_pipeline: BlockPipeline,
     block_target_pipeline: BlockTargetPipeline,
     hotbar_pipeline: HotbarPipeline,
+    water_pipeline: WaterPipeline,
+    speed_lines_pipeline: SpeedLinesPipeline,
+    beacon_pipeline: BeaconPipeline,
+    frame_capture: FrameCapture,
+    benchmark: Option<GraphicsBenchmark>,
     event_loop_sender: Sender<EventLoopRequest>,
 }
 
 impl App {
 
-    pub async fn new(window: &Window, event_loop_sender: Sender<EventLoopRequest>) -> Self {
-        let state = State::new(window)
+    pub async fn new(window: &Window, event_loop_sender: Sender<EventLoopRequest>, world: Option<WorldgenConfig>, sequence: Option<Sequence>, safe_mode: bool) -> Self {
+        let state = State::new(window, world, sequence, safe_mode)
             .await;
 
         let block_pipeline = BlockPipeline::new(
@@ -43,6 +61,30 @@ impl App {
             state.device(),
             state.queue(),
             state.config(),
+            state.hud_layout(),
+        );
+
+        let water_pipeline = WaterPipeline::new(
+            state.device(),
+            state.queue(),
+            state.config(),
+            state.camera_unfirom(),
+            state.water_uniform(),
+            state.scene_texture(),
+            state.scene_depth_texture(),
+        );
+
+        let speed_lines_pipeline = SpeedLinesPipeline::new(
+            state.device(),
+            state.config(),
+            state.speed_lines_uniform(),
+        );
+
+        let beacon_pipeline = BeaconPipeline::new(
+            state.device(),
+            state.config(),
+            state.camera_unfirom(),
+            state.beacon_uniform(),
         );
 
         Self {
@@ -50,14 +92,18 @@ impl App {
             block_pipeline,
             block_target_pipeline,
             hotbar_pipeline,
+            water_pipeline,
+            speed_lines_pipeline,
+            beacon_pipeline,
+            frame_capture: FrameCapture::new(),
+            benchmark: None,
             event_loop_sender,
         }
     }
 
     pub fn handle_device_event(&mut self, event: &DeviceEvent) {
-        match event {
-            DeviceEvent::MouseMotion { delta } => self.state.handle_cursor_move(delta),
-            _ => {},
+        if let DeviceEvent::MouseMotion { delta } = event {
+            self.state.handle_cursor_move(delta);
         }
     }
 
@@ -68,36 +114,240 @@ impl App {
                     &KeyboardInput { virtual_keycode: Some(VirtualKeyCode::Escape), state: ElementState::Pressed, .. } => {
                         self.event_loop_sender.send(EventLoopRequest::Close).unwrap();
                     },
+                    &KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F4), state: ElementState::Pressed, .. } => {
+                        self.block_pipeline.cycle_debug_view();
+                    },
+                    &KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F5), state: ElementState::Pressed, .. } => {
+                        self.state.toggle_spectator();
+                    },
+                    &KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F2), state: ElementState::Pressed, .. } => {
+                        self.state.toggle_photo_mode();
+                    },
+                    &KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F6), state: ElementState::Pressed, .. } => {
+                        self.speed_lines_pipeline.toggle();
+                    },
+                    &KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F7), state: ElementState::Pressed, .. } => {
+                        self.state.toggle_texture_info();
+                    },
+                    &KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F8), state: ElementState::Pressed, .. } => {
+                        self.state.toggle_letterbox();
+                        self.hotbar_pipeline.resize(self.state.queue(), self.state.hud_layout());
+                    },
+                    &KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F9), state: ElementState::Pressed, .. } => {
+                        self.frame_capture.trigger();
+                    },
+                    &KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F12), state: ElementState::Pressed, .. } if self.state.is_photo_mode() => {
+                        self.capture();
+                    },
                     input => self.state.handle_keyboard_input(input),
                 }
             },
             WindowEvent::MouseInput { button, state: e_state, .. } => self.state.handle_mouse_input(button, e_state),
             WindowEvent::CloseRequested => self.event_loop_sender.send(EventLoopRequest::Close).unwrap(),
-            WindowEvent::Resized(size) => self.state.resize(*size),
-            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => self.state.resize(**new_inner_size),
+            WindowEvent::Resized(size) => self.resize(*size),
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => self.resize(**new_inner_size),
             _ => {},
         }
     }
 
+    fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        self.state.resize(size);
+        self.hotbar_pipeline.resize(self.state.queue(), self.state.hud_layout());
+
+        self.water_pipeline.resize(
+            self.state.device(),
+            self.state.scene_texture(),
+            self.state.scene_depth_texture(),
+        );
+    }
+
+    fn capture(&mut self) {
+        match self.state.capture(
+            PhotoMode::CAPTURE_SCALE,
+            &self.block_pipeline,
+            &self.block_target_pipeline,
+            &self.hotbar_pipeline,
+            &mut self.water_pipeline,
+            &self.speed_lines_pipeline,
+            &self.beacon_pipeline,
+        ) {
+            Ok(path) => println!("Saved {:?}", path),
+            Err(e) => eprintln!("{:?}", e),
+        }
+    }
+
+    // Renders every golden scene and compares it against the stored image, or overwrites the
+    // stored images when blessing. Returns whether every scene matched.
+    pub fn run_golden(&mut self, bless: bool) -> bool {
+        let mut passed = true;
+
+        for scene in GoldenScene::all() {
+            self.state.load_golden_scene(&scene);
+
+            // Meshing happens on the workers, so keep updating until every chunk in view is uploaded.
+            let mut settled = false;
+
+            for _ in 0..GoldenScene::MAX_UPDATES {
+                self.state.update(
+                    Duration::ZERO,
+                    &mut self.block_pipeline,
+                    &mut self.block_target_pipeline,
+                    &mut self.hotbar_pipeline,
+                    &mut self.water_pipeline,
+                    &mut self.speed_lines_pipeline,
+                    &mut self.beacon_pipeline,
+                );
+
+                if self.state.is_world_settled() {
+                    settled = true;
+                    break;
+                }
+
+                std::thread::sleep(Duration::from_millis(5));
+            }
+
+            // A half meshed frame would fail or, when blessing, become a bad reference.
+            if !settled {
+                eprintln!("{}: the world did not settle after {} updates", scene.name, GoldenScene::MAX_UPDATES);
+                passed = false;
+                continue;
+            }
+
+            let result = self.state
+                .capture_image(
+                    1,
+                    &self.block_pipeline,
+                    &self.block_target_pipeline,
+                    &self.hotbar_pipeline,
+                    &mut self.water_pipeline,
+                    &self.speed_lines_pipeline,
+                    &self.beacon_pipeline,
+                )
+                .and_then(|image| Self::check_golden(&scene, &image, bless));
+
+            match result {
+                Ok(true) => println!("{}: ok", scene.name),
+                Ok(false) => {
+                    println!("{}: mismatch, wrote {:?}", scene.name, scene.actual_path());
+                    passed = false;
+                },
+                Err(e) => {
+                    eprintln!("{}: {:?}", scene.name, e);
+                    passed = false;
+                },
+            }
+        }
+
+        passed
+    }
+
+    fn check_golden(scene: &GoldenScene, image: &image::RgbaImage, bless: bool) -> anyhow::Result<bool> {
+        std::fs::create_dir_all(GoldenScene::DIR)?;
+
+        if bless {
+            image.save(scene.path())?;
+            return Ok(true);
+        }
+
+        let expected = image::open(scene.path())
+            .with_context(|| format!("missing golden image {:?}, run with --bless to create it", scene.path()))?
+            .to_rgba8();
+
+        let mismatch = GoldenScene::mismatch(image, &expected)?;
+
+        if mismatch > GoldenScene::MAX_MISMATCH {
+            image.save(scene.actual_path())?;
+            println!("{}: {:.2}% of pixels differ", scene.name, mismatch * 100.0);
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    pub fn save(&mut self) {
+        self.state.save();
+    }
+
+    pub fn camera_position(&self) -> Vector3<f32> {
+        self.state.camera_position()
+    }
+
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: &Block) {
+        self.state.set_block(x, y, z, block);
+    }
+
+    pub fn world_stats(&self) -> WorldStats {
+        self.state.world_stats()
+    }
+
+    pub fn set_overlay(&mut self, lines: Vec<String>) {
+        self.state.set_overlay(lines);
+    }
+
+    pub fn set_chunk_cache_memory(&mut self, bytes: usize) {
+        self.state.set_chunk_cache_memory(bytes);
+    }
+
+    pub fn set_worker_threads(&mut self, threads: usize) {
+        self.state.set_worker_threads(threads);
+    }
+
+    pub fn set_vsync(&mut self, vsync: bool) -> bool {
+        self.state.set_vsync(vsync)
+    }
+
+    pub fn set_graphics_preset(&mut self, preset: GraphicsPreset) {
+        self.state.set_graphics_preset(preset);
+        self.speed_lines_pipeline.set_enabled(preset.speed_lines());
+    }
+
+    // Runs at the default preset until the benchmark has timed enough frames, then switches to the
+    // preset it picked and saves it so later launches skip the benchmark.
+    pub fn start_graphics_benchmark(&mut self) {
+        self.set_graphics_preset(GraphicsPreset::DEFAULT);
+        self.benchmark = Some(GraphicsBenchmark::new());
+    }
+
     pub fn handle_redraw_request(&mut self, dt: Duration, fps: u32) {
+        let start = instant::Instant::now();
+
         self.state.update(
             dt,
             &mut self.block_pipeline,
             &mut self.block_target_pipeline,
             &mut self.hotbar_pipeline,
+            &mut self.water_pipeline,
+            &mut self.speed_lines_pipeline,
+            &mut self.beacon_pipeline,
         );
 
         match self.state.render(
             &self.block_pipeline,
             &self.block_target_pipeline,
             &self.hotbar_pipeline,
+            &self.water_pipeline,
+            &self.speed_lines_pipeline,
+            &self.beacon_pipeline,
             fps,
         ) {
             Ok(_) => {},
-            Err(wgpu::SurfaceError::Lost) => self.state.resize(self.state.size()),
+            Err(wgpu::SurfaceError::Lost) => self.resize(self.state.size()),
             Err(wgpu::SurfaceError::OutOfMemory) => self.event_loop_sender.send(EventLoopRequest::Close).unwrap(),
             Err(e) => eprintln!("{:?}", e),
         }
+
+        // Frames while chunks are still streaming in measure meshing rather than rendering.
+        if self.benchmark.is_some() && self.state.is_world_settled() {
+            self.state.wait_for_gpu();
+
+            if let Some(preset) = self.benchmark.as_mut().and_then(|b| b.record(start.elapsed())) {
+                println!("Picked the {:?} graphics preset", preset);
+
+                preset.save(GraphicsPreset::PATH);
+                self.set_graphics_preset(preset);
+                self.benchmark = None;
+            }
+        }
     }
 
 }
\ No newline at end of file
@@ -0,0 +1,228 @@
+use wgpu::{BindGroup, Buffer, CompareFunction, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureFormat};
+use wgpu::util::DeviceExt;
+use crate::scene::beacon_uniform::BeaconUniform;
+use crate::scene::camera_uniform::CameraUniform;
+
+pub struct BeaconPipeline {
+    pipeline: RenderPipeline,
+
+    camera_buffer: Buffer,
+    camera_bind_group: BindGroup,
+
+    beacon_buffer: Buffer,
+    beacon_bind_group: BindGroup,
+
+    instance_buffer: Buffer,
+    instance_capacity: usize,
+    instance_count: u32,
+}
+
+impl BeaconPipeline {
+
+    pub fn new(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        camera_uniform: &CameraUniform,
+        beacon_uniform: &BeaconUniform,
+    ) -> Self {
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Beacon Camera Buffer"),
+            contents: bytemuck::cast_slice(&[*camera_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let beacon_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Beacon Buffer"),
+            contents: bytemuck::cast_slice(&[*beacon_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let camera_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("beacon_camera_bind_group_layout"),
+        });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("beacon_camera_bind_group"),
+        });
+
+        let beacon_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("beacon_bind_group_layout"),
+        });
+
+        let beacon_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &beacon_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: beacon_buffer.as_entire_binding(),
+            }],
+            label: Some("beacon_bind_group"),
+        });
+
+        let instance_capacity = 16;
+        let instance_buffer = Self::create_instance_buffer(device, instance_capacity);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Beacon Beam Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/beacon-beam.wgsl").into()),
+        });
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Beacon Beam Render Pipeline Layout"),
+            bind_group_layouts: &[
+                &camera_bind_group_layout,
+                &beacon_bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Beacon Beam Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    // Additive, so overlapping beams don't need sorting.
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent::OVER,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+
+            camera_buffer,
+            camera_bind_group,
+
+            beacon_buffer,
+            beacon_bind_group,
+
+            instance_buffer,
+            instance_capacity,
+            instance_count: 0,
+        }
+    }
+
+    fn create_instance_buffer(device: &Device, capacity: usize) -> Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Beacon Instance Buffer"),
+            size: (capacity * std::mem::size_of::<[f32; 3]>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    // `beacons` are the bases of the beams, on top of each beacon block.
+    pub fn update(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        camera_uniform: &CameraUniform,
+        beacon_uniform: &BeaconUniform,
+        beacons: &[[f32; 3]],
+    ) {
+        if beacons.len() > self.instance_capacity {
+            self.instance_capacity = beacons.len().next_power_of_two();
+            self.instance_buffer = Self::create_instance_buffer(device, self.instance_capacity);
+        }
+
+        self.instance_count = beacons.len() as u32;
+
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[*camera_uniform]));
+        queue.write_buffer(&self.beacon_buffer, 0, bytemuck::cast_slice(&[*beacon_uniform]));
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(beacons));
+    }
+
+}
+
+pub trait DrawBeacons<'a> {
+    fn draw_beacons(
+        &mut self,
+        pipeline: &'a BeaconPipeline,
+    );
+}
+
+// Beams are drawn whole rather than culled against chunk bounds, since they reach far above the
+// chunk they stand in and are meant to be seen from anywhere.
+impl<'a, 'b> DrawBeacons<'b> for wgpu::RenderPass<'a>
+    where 'b: 'a {
+    fn draw_beacons(
+        &mut self,
+        pipeline: &'a BeaconPipeline,
+    ) {
+        if pipeline.instance_count == 0 {
+            return;
+        }
+
+        self.set_pipeline(&pipeline.pipeline);
+        self.set_bind_group(0, &pipeline.camera_bind_group, &[]);
+        self.set_bind_group(1, &pipeline.beacon_bind_group, &[]);
+        self.set_vertex_buffer(0, pipeline.instance_buffer.slice(..));
+        self.draw(0..6, 0..pipeline.instance_count);
+    }
+}
\ No newline at end of file
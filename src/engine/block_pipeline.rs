@@ -1,15 +1,19 @@
-use wgpu::{BindGroup, Buffer, CompareFunction, Device, Queue, RenderPass, RenderPipeline, SurfaceConfiguration, TextureFormat};
+use wgpu::{BindGroup, Buffer, CompareFunction, Device, PipelineLayout, Queue, RenderPipeline, ShaderModule, SurfaceConfiguration, TextureFormat};
 use wgpu::util::DeviceExt;
+use crate::engine::debug_view::DebugView;
 use crate::engine::texture::Texture;
 use crate::mesh::vertex::Vertex;
 use crate::objects::block::Block;
 use crate::scene::camera_uniform::CameraUniform;
 use crate::world::chunk::Chunk;
-use crate::world::chunk_buffer::ChunkBuffer;
+use crate::world::chunk_draws::ChunkDraws;
+use crate::world::quad_index_buffer::QuadIndexBuffer;
+use crate::world::chunk_buffer_pool::ChunkBufferPool;
 use crate::world::world::World;
 
 pub struct BlockPipeline {
-    pipeline: RenderPipeline,
+    pipelines: Vec<RenderPipeline>,
+    debug_view: DebugView,
 
     diffuse_bind_group: BindGroup,
     diffuse_texture: Texture,
@@ -115,22 +119,70 @@ impl BlockPipeline {
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let pipelines = DebugView::ALL
+            .iter()
+            .map(|view| Self::create_pipeline(device, config, &render_pipeline_layout, &shader, *view))
+            .collect();
+
+        Self {
+            pipelines,
+            debug_view: DebugView::None,
+
+            diffuse_bind_group,
+            diffuse_texture,
+
+            camera_buffer,
+            camera_bind_group,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        queue: &Queue,
+        camera_uniform: &CameraUniform,
+    ) {
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[*camera_uniform]));
+    }
+
+    // Debug views are permutations of the block shader that only swap the fragment entry point;
+    // overdraw also disables depth testing and accumulates additively.
+    fn create_pipeline(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        layout: &PipelineLayout,
+        shader: &ShaderModule,
+        view: DebugView,
+    ) -> RenderPipeline {
+        let overdraw = view == DebugView::Overdraw;
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Block Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
+            layout: Some(layout),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: shader,
                 entry_point: "vs_main",
                 buffers: &[
                     Vertex::desc(),
+                    Vertex::origin_desc(),
                 ],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
+                module: shader,
+                entry_point: view.entry_point(),
                 targets: &[Some(wgpu::ColorTargetState {
                     format: config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    blend: Some(if overdraw {
+                        wgpu::BlendState {
+                            color: wgpu::BlendComponent {
+                                src_factor: wgpu::BlendFactor::One,
+                                dst_factor: wgpu::BlendFactor::One,
+                                operation: wgpu::BlendOperation::Add,
+                            },
+                            alpha: wgpu::BlendComponent::REPLACE,
+                        }
+                    } else {
+                        wgpu::BlendState::ALPHA_BLENDING
+                    }),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
@@ -145,8 +197,8 @@ impl BlockPipeline {
             },
             depth_stencil: Some(wgpu::DepthStencilState {
                 format: TextureFormat::Depth32Float,
-                depth_write_enabled: true,
-                depth_compare: CompareFunction::Less,
+                depth_write_enabled: !overdraw,
+                depth_compare: if overdraw { CompareFunction::Always } else { CompareFunction::Less },
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
@@ -156,29 +208,25 @@ impl BlockPipeline {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
-        });
+        })
+    }
 
-        Self {
-            pipeline,
+    pub fn pipeline(&self) -> &RenderPipeline {
+        let i = DebugView::ALL.iter().position(|v| *v == self.debug_view).unwrap();
 
-            diffuse_bind_group,
-            diffuse_texture,
+        &self.pipelines[i]
+    }
 
-            camera_buffer,
-            camera_bind_group,
-        }
+    pub fn debug_view(&self) -> DebugView {
+        self.debug_view
     }
 
-    pub fn update(
-        &mut self,
-        queue: &Queue,
-        camera_uniform: &CameraUniform,
-    ) {
-        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[*camera_uniform]));
+    pub fn cycle_debug_view(&mut self) {
+        self.debug_view = self.debug_view.next();
     }
 
-    pub fn pipeline(&self) -> &RenderPipeline {
-        &self.pipeline
+    pub fn diffuse_texture(&self) -> &Texture {
+        &self.diffuse_texture
     }
 
 }
@@ -187,16 +235,13 @@ pub trait DrawBlock<'a> {
     fn attach_pipeline(
         &mut self,
         pipeline: &'a BlockPipeline,
+        quad_indices: &'a QuadIndexBuffer,
     );
 
-    fn draw_mesh(
-        &mut self,
-        buffer: &'a ChunkBuffer,
-    );
-
-    fn draw_alpha_mesh(
+    fn draw_chunks(
         &mut self,
-        buffer: &'a ChunkBuffer,
+        vertices: &'a ChunkBufferPool,
+        draws: &'a ChunkDraws,
     );
 }
 
@@ -205,27 +250,37 @@ where 'b: 'a {
     fn attach_pipeline(
         &mut self,
         pipeline: &'a BlockPipeline,
+        quad_indices: &'a QuadIndexBuffer,
     ) {
         self.set_pipeline(pipeline.pipeline());
         self.set_bind_group(0, &pipeline.camera_bind_group, &[]);
         self.set_bind_group(1, &pipeline.diffuse_bind_group, &[]);
+        self.set_index_buffer(quad_indices.buffer().slice(..), wgpu::IndexFormat::Uint32);
     }
 
-    fn draw_mesh(
+    fn draw_chunks(
         &mut self,
-        buffer: &'a ChunkBuffer,
+        vertices: &'a ChunkBufferPool,
+        draws: &'a ChunkDraws,
     ) {
-        self.set_vertex_buffer(0, buffer.vertex_buffer.slice(..));
-        self.set_index_buffer(buffer.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-        self.draw_indexed(0..buffer.index_count, 0, 0..1);
-    }
+        let args = draws.draws(false);
+        let buffer = if let Some(buffer) = vertices.buffer() { buffer } else { return };
 
-    fn draw_alpha_mesh(
-        &mut self,
-        buffer: &'a ChunkBuffer,
-    ) {
-        self.set_vertex_buffer(0, buffer.alpha_vertex_buffer.slice(..));
-        self.set_index_buffer(buffer.alpha_index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-        self.draw_indexed(0..buffer.alpha_index_count, 0, 0..1);
+        if draws.multi_draw() {
+            if !args.is_empty() {
+                self.set_vertex_buffer(0, buffer.slice(..));
+                self.set_vertex_buffer(1, draws.origins().slice(..));
+                self.multi_draw_indexed_indirect(draws.indirect_buffer(false), 0, args.len() as u32);
+            }
+            return;
+        }
+
+        for (i, (draw, label)) in args.iter().zip(draws.labels()).enumerate().filter(|(_, (d, _))| d.vertex_count > 0) {
+            self.push_debug_group(label);
+            self.set_vertex_buffer(0, vertices.slice(draw.vertex_offset as u32, QuadIndexBuffer::vertex_count(draw.vertex_count)));
+            self.set_vertex_buffer(1, draws.origin(i as u32));
+            self.draw_indexed(0..draw.vertex_count, 0, 0..1);
+            self.pop_debug_group();
+        }
     }
 }
\ No newline at end of file
@@ -119,6 +119,10 @@ impl BlockTargetPipeline {
             push_constant_ranges: &[],
         });
 
+        // Without line polygons the box is drawn filled and its fragment shader keeps only the edges,
+        // pulled slightly towards the camera so they don't fight with the block's own faces.
+        let lines = device.features().contains(wgpu::Features::POLYGON_MODE_LINE);
+
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Block Target Render Pipeline"),
             layout: Some(&render_pipeline_layout),
@@ -131,7 +135,7 @@ impl BlockTargetPipeline {
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
-                entry_point: "fs_main",
+                entry_point: if lines { "fs_main" } else { "fs_outline" },
                 targets: &[Some(wgpu::ColorTargetState {
                     format: config.format,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
@@ -139,20 +143,24 @@ impl BlockTargetPipeline {
                 })],
             }),
             primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                topology: if lines { wgpu::PrimitiveTopology::TriangleStrip } else { wgpu::PrimitiveTopology::TriangleList },
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Line,
-                unclipped_depth: true,
+                polygon_mode: if lines { wgpu::PolygonMode::Line } else { wgpu::PolygonMode::Fill },
+                unclipped_depth: device.features().contains(wgpu::Features::DEPTH_CLIP_CONTROL),
                 conservative: false,
             },
             depth_stencil: Some(wgpu::DepthStencilState {
                 format: TextureFormat::Depth32Float,
                 depth_write_enabled: false,
-                depth_compare: CompareFunction::Less,
+                depth_compare: if lines { CompareFunction::Less } else { CompareFunction::LessEqual },
                 stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
+                bias: if lines {
+                    wgpu::DepthBiasState::default()
+                } else {
+                    wgpu::DepthBiasState { constant: -2, slope_scale: -1.0, clamp: 0.0 }
+                },
             }),
             multisample: wgpu::MultisampleState {
                 count: 1,
@@ -0,0 +1,33 @@
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DebugView {
+    None,
+    Normals,
+    Uvs,
+    Overdraw,
+}
+
+impl DebugView {
+
+    pub const ALL: [DebugView; 4] = [
+        DebugView::None,
+        DebugView::Normals,
+        DebugView::Uvs,
+        DebugView::Overdraw,
+    ];
+
+    pub fn next(&self) -> DebugView {
+        let i = Self::ALL.iter().position(|v| v == self).unwrap();
+
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn entry_point(&self) -> &'static str {
+        match self {
+            DebugView::None => "fs_main",
+            DebugView::Normals => "fs_normals",
+            DebugView::Uvs => "fs_uvs",
+            DebugView::Overdraw => "fs_overdraw",
+        }
+    }
+
+}
@@ -0,0 +1,234 @@
+use std::time::Duration;
+use cgmath::Vector3;
+use crossbeam::channel::{unbounded, Receiver};
+use winit::dpi::{LogicalPosition, LogicalSize};
+use winit::event::{DeviceEvent, Event, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::window::{CursorGrabMode, Window, WindowBuilder};
+use crate::engine::app::App;
+use crate::objects::block::Block;
+use crate::scene::sequence::Sequence;
+use crate::window::event_loop_request::EventLoopRequest;
+use crate::window::frame_pacer::FramePacer;
+use crate::window::graphics_preset::GraphicsPreset;
+use crate::window::safe_mode;
+use crate::world::world_stats::WorldStats;
+use crate::world::worldgen_config::WorldgenConfig;
+
+// How the engine starts. The defaults are what the game does without any flags, except that the
+// graphics preset is always benchmarked since nothing is read from disk for it.
+pub struct EngineConfig {
+    // None reads `worldgen.toml` and regenerates the world whenever the file changes.
+    pub world: Option<WorldgenConfig>,
+    pub sequence: Option<Sequence>,
+    // Forces safe mode; it's also used when the last launch crashed during startup.
+    pub safe_mode: bool,
+    pub chunk_cache_memory: Option<usize>,
+    // Threads for generating and meshing chunks; None leaves one core for rendering and uses the
+    // rest.
+    pub worker_threads: Option<usize>,
+    // None benchmarks the machine and saves the preset it picks.
+    pub graphics: Option<GraphicsPreset>,
+    pub vsync: bool,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            world: None,
+            sequence: None,
+            safe_mode: false,
+            chunk_cache_memory: None,
+            worker_threads: None,
+            graphics: None,
+            vsync: true,
+        }
+    }
+}
+
+// Hooks for the frontend embedding the engine. Every one has a default, so a frontend only writes
+// the ones it needs.
+pub trait EngineCallbacks {
+    // Called before the engine's own controls see the event; returning true keeps it from them.
+    fn window_event(&mut self, _engine: &mut EngineContext, _event: &WindowEvent) -> bool {
+        false
+    }
+
+    fn device_event(&mut self, _engine: &mut EngineContext, _event: &DeviceEvent) -> bool {
+        false
+    }
+
+    // Once per frame, before the world and camera update.
+    fn update(&mut self, _engine: &mut EngineContext, _dt: Duration) {}
+
+    // Lines drawn in the bottom left corner of the HUD this frame.
+    fn overlay(&mut self, _engine: &EngineContext) -> Vec<String> {
+        vec![]
+    }
+}
+
+impl EngineCallbacks for () {}
+
+// What callbacks can reach of the running engine.
+pub struct EngineContext {
+    app: App,
+}
+
+impl EngineContext {
+
+    pub fn camera_position(&self) -> Vector3<f32> {
+        self.app.camera_position()
+    }
+
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: &Block) {
+        self.app.set_block(x, y, z, block);
+    }
+
+    pub fn world_stats(&self) -> WorldStats {
+        self.app.world_stats()
+    }
+
+    pub fn save(&mut self) {
+        self.app.save();
+    }
+
+}
+
+// Opens a window and runs the game in it: the world, the camera controls and the HUD. `main.rs`
+// is a frontend built on this, see `EngineCallbacks` for what another one can hook into.
+pub struct Engine {
+    event_loop: EventLoop<()>,
+    window: Window,
+    context: EngineContext,
+    receiver: Receiver<EventLoopRequest>,
+    pacer: FramePacer,
+}
+
+impl Engine {
+
+    pub async fn new(config: EngineConfig) -> Self {
+        let safe_mode = safe_mode::begin_startup() || config.safe_mode;
+
+        if safe_mode {
+            println!("Starting in safe mode");
+        }
+
+        let event_loop = EventLoop::new();
+        let window = WindowBuilder::new().build(&event_loop)
+            .unwrap();
+
+        // Not every platform can confine the cursor; macOS can only lock it in place.
+        let grab = window
+            .set_cursor_grab(CursorGrabMode::Confined)
+            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Locked));
+
+        if let Err(e) = grab {
+            eprintln!("{:?}", e);
+        }
+
+        window.set_cursor_visible(false);
+        window.set_inner_size(LogicalSize::new(1920, 1080));
+
+        if let Some(monitor) = window.current_monitor() {
+            let size = monitor.size();
+            let wsize = window.outer_size();
+
+            let off_x = (size.width - wsize.width) / 2;
+            let off_y = (size.height - wsize.height) / 2;
+
+            window.set_outer_position(LogicalPosition::new(off_x, off_y));
+        }
+
+        let (sender, receiver) = unbounded::<EventLoopRequest>();
+
+        let mut app = App::new(&window, sender, config.world, config.sequence, safe_mode).await;
+
+        safe_mode::finish_startup();
+
+        if let Some(bytes) = config.chunk_cache_memory {
+            app.set_chunk_cache_memory(bytes);
+        }
+
+        if let Some(threads) = config.worker_threads {
+            app.set_worker_threads(threads);
+        }
+
+        match config.graphics {
+            _ if safe_mode => app.set_graphics_preset(GraphicsPreset::Low),
+            Some(preset) => app.set_graphics_preset(preset),
+            None => app.start_graphics_benchmark(),
+        }
+
+        // With vsync presenting already waits for the monitor.
+        let vsync = app.set_vsync(config.vsync);
+        let pacer = FramePacer::new(&window, !vsync);
+
+        Self {
+            event_loop,
+            window,
+            context: EngineContext { app },
+            receiver,
+            pacer,
+        }
+    }
+
+    // Runs until the window is closed, then saves the world and exits the process.
+    pub fn run<C: EngineCallbacks + 'static>(self, mut callbacks: C) -> ! {
+        let Self { event_loop, window, mut context, receiver, mut pacer } = self;
+
+        let mut last_render_time = instant::Instant::now();
+        let mut last_fps_check = instant::Instant::now();
+        let mut fps = 0;
+
+        event_loop.run(move |event, _, control_flow| match event {
+            Event::DeviceEvent { event, .. } if !callbacks.device_event(&mut context, &event) => {
+                context.app.handle_device_event(&event);
+            },
+            Event::WindowEvent {
+                ref event,
+                window_id,
+            } if window.id() == window_id => {
+                if let WindowEvent::Moved(_) | WindowEvent::ScaleFactorChanged { .. } = event {
+                    pacer.refresh(&window);
+                }
+
+                if !callbacks.window_event(&mut context, event) {
+                    context.app.handle_window_event(event);
+                }
+            },
+            Event::RedrawRequested(id) if window.id() == id => {
+                let now = instant::Instant::now();
+                let dt = now - last_render_time;
+
+                if now - last_fps_check >= instant::Duration::from_secs(2) {
+                    fps = (1.0 / dt.as_secs_f32()).round() as u32;
+                    last_fps_check = now;
+                }
+
+                last_render_time = now;
+
+                callbacks.update(&mut context, dt);
+                let overlay = callbacks.overlay(&context);
+                context.app.set_overlay(overlay);
+
+                context.app.handle_redraw_request(dt, fps);
+            },
+            Event::MainEventsCleared => {
+                match pacer.next_frame(instant::Instant::now()) {
+                    Some(next) => *control_flow = ControlFlow::WaitUntil(next),
+                    None => {
+                        *control_flow = ControlFlow::Poll;
+                        window.request_redraw();
+                    },
+                }
+
+                if let Ok(EventLoopRequest::Close) = receiver.try_recv() {
+                    context.app.save();
+                    *control_flow = ControlFlow::Exit;
+                }
+            },
+            _ => {},
+        });
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,34 @@
+// Programmatic RenderDoc captures. The API is only there when the game was launched from
+// RenderDoc and built with the `renderdoc` feature; otherwise triggering does nothing.
+pub struct FrameCapture {
+    #[cfg(feature = "renderdoc")]
+    renderdoc: Option<renderdoc::RenderDoc<renderdoc::V110>>,
+}
+
+impl Default for FrameCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameCapture {
+
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "renderdoc")]
+            renderdoc: renderdoc::RenderDoc::new().ok(),
+        }
+    }
+
+    // Captures the next frame presented.
+    pub fn trigger(&mut self) {
+        #[cfg(feature = "renderdoc")]
+        if let Some(renderdoc) = &mut self.renderdoc {
+            renderdoc.trigger_capture();
+            return;
+        }
+
+        eprintln!("RenderDoc is not attached; launch the game from RenderDoc with --features renderdoc");
+    }
+
+}
@@ -1,6 +1,7 @@
 use wgpu::{BindGroup, Buffer, CompareFunction, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureFormat};
 use wgpu::util::DeviceExt;
 use crate::engine::texture::Texture;
+use crate::window::hud_layout::HudLayout;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -13,6 +14,8 @@ pub struct HotbarPipeline {
     pipeline: RenderPipeline,
 
     vertex_buffer: Buffer,
+    // Width and height in HUD units.
+    size: [f32; 2],
 
     diffuse_bind_group: BindGroup,
     diffuse_texture: Texture,
@@ -20,10 +23,16 @@ pub struct HotbarPipeline {
 
 impl HotbarPipeline {
 
+    // In HUD units, where the view is 2 high.
+    const HEIGHT: f32 = 0.125;
+    // Most of the view's width in normalized device coordinates the hotbar may take up.
+    const MAX_WIDTH: f32 = 1.8;
+
     pub fn new(
         device: &Device,
         queue: &Queue,
         config: &SurfaceConfiguration,
+        layout: &HudLayout,
     ) -> Self {
         let diffuse_image = image::io::Reader::open("assets/textures/hotbar.png")
             .unwrap()
@@ -31,25 +40,16 @@ impl HotbarPipeline {
             .unwrap()
             .flipv();
 
-        let (width, height) = (diffuse_image.width() as f32, diffuse_image.height() as f32);
-        let y_scale = height / width;
+        // Only the top half of the image is drawn.
+        let (width, height) = (diffuse_image.width() as f32, diffuse_image.height() as f32 * 0.5);
+        let size = [width / height * Self::HEIGHT, Self::HEIGHT];
 
         let diffuse_texture = Texture::from_image(device, queue, &diffuse_image, Some("hotbar")).unwrap();
 
-        let verts = vec![
-            HotbarVertex { position: [-0.25, -1.0, 0.0], uv: [0.0, 0.5] },
-            HotbarVertex { position: [0.25, -1.0, 0.0], uv: [1.0, 0.5] },
-            HotbarVertex { position: [0.25, -1.0 + y_scale * 0.5, 0.0], uv: [1.0, 1.0] },
-
-            HotbarVertex { position: [-0.25, -1.0, 0.0], uv: [0.0, 0.5] },
-            HotbarVertex { position: [0.25, -1.0 + y_scale * 0.5, 0.0], uv: [1.0, 1.0] },
-            HotbarVertex { position: [-0.25, -1.0 + y_scale * 0.5, 0.0], uv: [0.0, 1.0] },
-        ];
-
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Hotbar Vertex Buffer"),
-            contents: bytemuck::cast_slice(&verts),
-            usage: wgpu::BufferUsages::VERTEX,
+            contents: bytemuck::cast_slice(&Self::vertices(size, layout)),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
         let texture_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -142,7 +142,7 @@ impl HotbarPipeline {
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: None,
                 polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: true,
+                unclipped_depth: device.features().contains(wgpu::Features::DEPTH_CLIP_CONTROL),
                 conservative: false,
             },
             depth_stencil: Some(wgpu::DepthStencilState {
@@ -164,12 +164,38 @@ impl HotbarPipeline {
             pipeline,
 
             vertex_buffer,
+            size,
 
             diffuse_bind_group,
             diffuse_texture,
         }
     }
 
+    pub fn diffuse_texture(&self) -> &Texture {
+        &self.diffuse_texture
+    }
+
+    pub fn resize(&self, queue: &Queue, layout: &HudLayout) {
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&Self::vertices(self.size, layout)));
+    }
+
+    // Centered on the bottom edge, and shrunk to fit views too narrow for it.
+    fn vertices(size: [f32; 2], layout: &HudLayout) -> [HotbarVertex; 6] {
+        let [sx, sy] = layout.scale();
+        let fit = (Self::MAX_WIDTH / (size[0] * sx)).min(1.0);
+        let (w, h) = (size[0] * sx * fit * 0.5, size[1] * sy * fit);
+
+        [
+            HotbarVertex { position: [-w, -1.0, 0.0], uv: [0.0, 0.5] },
+            HotbarVertex { position: [w, -1.0, 0.0], uv: [1.0, 0.5] },
+            HotbarVertex { position: [w, -1.0 + h, 0.0], uv: [1.0, 1.0] },
+
+            HotbarVertex { position: [-w, -1.0, 0.0], uv: [0.0, 0.5] },
+            HotbarVertex { position: [w, -1.0 + h, 0.0], uv: [1.0, 1.0] },
+            HotbarVertex { position: [-w, -1.0 + h, 0.0], uv: [0.0, 1.0] },
+        ]
+    }
+
 }
 
 pub trait DrawBlock<'a> {
@@ -3,3 +3,11 @@ pub mod block_pipeline;
 pub mod texture;
 pub mod block_target_pipeline;
 pub mod hotbar_pipeline;
+pub mod water_pipeline;
+pub mod debug_view;
+
+pub mod speed_lines_pipeline;
+pub mod frame_capture;
+pub mod texture_info;
+pub mod beacon_pipeline;
+pub mod engine;
\ No newline at end of file
@@ -0,0 +1,153 @@
+use wgpu::{BindGroup, Buffer, CompareFunction, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureFormat};
+use wgpu::util::DeviceExt;
+use crate::scene::speed_lines_uniform::SpeedLinesUniform;
+
+pub struct SpeedLinesPipeline {
+    pipeline: RenderPipeline,
+
+    speed_lines_buffer: Buffer,
+    speed_lines_bind_group: BindGroup,
+
+    enabled: bool,
+    visible: bool,
+}
+
+impl SpeedLinesPipeline {
+
+    pub fn new(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        speed_lines_uniform: &SpeedLinesUniform,
+    ) -> Self {
+        let speed_lines_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Speed Lines Buffer"),
+            contents: bytemuck::cast_slice(&[*speed_lines_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let speed_lines_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("speed_lines_bind_group_layout"),
+        });
+
+        let speed_lines_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &speed_lines_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: speed_lines_buffer.as_entire_binding(),
+            }],
+            label: Some("speed_lines_bind_group"),
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Speed Lines Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/speed-lines.wgsl").into()),
+        });
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Speed Lines Render Pipeline Layout"),
+            bind_group_layouts: &[
+                &speed_lines_bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Speed Lines Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+
+            speed_lines_buffer,
+            speed_lines_bind_group,
+
+            enabled: true,
+            visible: false,
+        }
+    }
+
+    pub fn update(&mut self, queue: &Queue, speed_lines_uniform: &SpeedLinesUniform) {
+        self.visible = speed_lines_uniform.is_visible();
+        queue.write_buffer(&self.speed_lines_buffer, 0, bytemuck::cast_slice(&[*speed_lines_uniform]));
+    }
+
+    // Motion effects can be turned off entirely for players sensitive to them.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+}
+
+pub trait DrawSpeedLines<'a> {
+    fn draw_speed_lines(
+        &mut self,
+        pipeline: &'a SpeedLinesPipeline,
+    );
+}
+
+impl<'a, 'b> DrawSpeedLines<'b> for wgpu::RenderPass<'a>
+    where 'b: 'a {
+    fn draw_speed_lines(
+        &mut self,
+        pipeline: &'a SpeedLinesPipeline,
+    ) {
+        if !pipeline.enabled || !pipeline.visible {
+            return;
+        }
+
+        self.set_pipeline(&pipeline.pipeline);
+        self.set_bind_group(0, &pipeline.speed_lines_bind_group, &[]);
+        self.draw(0..3, 0..1);
+    }
+}
\ No newline at end of file
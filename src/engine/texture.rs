@@ -2,11 +2,13 @@ use std::fmt::Formatter;
 use image::GenericImageView;
 use anyhow::*;
 use wgpu::{AddressMode, Device, SurfaceConfiguration, TextureUsages};
+use crate::engine::texture_info::TextureInfo;
 
 pub struct Texture {
     texture: wgpu::Texture,
     view: wgpu::TextureView,
     sampler: wgpu::Sampler,
+    info: TextureInfo,
 }
 
 impl Texture {
@@ -39,7 +41,7 @@ impl Texture {
             depth_or_array_layers: 1,
         };
 
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
+        let desc = wgpu::TextureDescriptor {
             label,
             size,
             mip_level_count: 1,
@@ -47,7 +49,9 @@ impl Texture {
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8UnormSrgb,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-        });
+        };
+
+        let texture = device.create_texture(&desc);
 
         queue.write_texture(
             wgpu::ImageCopyTexture {
@@ -79,7 +83,7 @@ impl Texture {
             ..Default::default()
         });
 
-        Ok(Self { texture, view, sampler })
+        Ok(Self { texture, view, sampler, info: TextureInfo::from_descriptor(&desc) })
     }
 
     pub fn create_depth_texture(device: &Device, config: &SurfaceConfiguration, label: &str) -> Self {
@@ -96,7 +100,7 @@ impl Texture {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: Self::DEPTH_FORMAT,
-            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::COPY_DST,
         };
 
         let texture = device.create_texture(&desc);
@@ -116,7 +120,45 @@ impl Texture {
             ..Default::default()
         });
 
-        Self { texture, view, sampler }
+        Self { texture, view, sampler, info: TextureInfo::from_descriptor(&desc) }
+    }
+
+    pub fn create_scene_texture(device: &Device, config: &SurfaceConfiguration, label: &str) -> Self {
+        let size = wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        };
+
+        let desc = wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC,
+        };
+
+        let texture = device.create_texture(&desc);
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self { texture, view, sampler, info: TextureInfo::from_descriptor(&desc) }
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
     }
 
     pub fn view(&self) -> &wgpu::TextureView {
@@ -127,6 +169,10 @@ impl Texture {
         &self.sampler
     }
 
+    pub fn info(&self) -> &TextureInfo {
+        &self.info
+    }
+
 }
 
 impl std::fmt::Debug for Texture {
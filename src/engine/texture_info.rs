@@ -0,0 +1,41 @@
+use wgpu::{Extent3d, TextureDescriptor, TextureFormat};
+
+// What a texture was created with, kept around for the diagnostics overlay since wgpu 0.14
+// textures can't be queried for it.
+#[derive(Debug, Clone)]
+pub struct TextureInfo {
+    pub label: String,
+    pub format: TextureFormat,
+    pub size: Extent3d,
+    pub mip_level_count: u32,
+}
+
+impl TextureInfo {
+
+    pub fn from_descriptor(descriptor: &TextureDescriptor) -> Self {
+        Self {
+            label: descriptor.label.unwrap_or("unnamed").to_string(),
+            format: descriptor.format,
+            size: descriptor.size,
+            mip_level_count: descriptor.mip_level_count,
+        }
+    }
+
+    // Bytes across every mip level. Drivers pad and align allocations, so the real footprint is
+    // somewhat higher.
+    pub fn estimated_bytes(&self) -> u64 {
+        let info = self.format.describe();
+        let (bw, bh) = (info.block_dimensions.0 as u32, info.block_dimensions.1 as u32);
+
+        (0..self.mip_level_count)
+            .map(|level| {
+                let width = (self.size.width >> level).max(1);
+                let height = (self.size.height >> level).max(1);
+                let blocks = width.div_ceil(bw) as u64 * height.div_ceil(bh) as u64;
+
+                blocks * info.block_size as u64 * self.size.depth_or_array_layers as u64
+            })
+            .sum()
+    }
+
+}
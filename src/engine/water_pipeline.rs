@@ -0,0 +1,492 @@
+use wgpu::{BindGroup, BindGroupLayout, Buffer, CompareFunction, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureFormat};
+use wgpu::util::DeviceExt;
+use crate::engine::texture::Texture;
+use crate::mesh::vertex::Vertex;
+use crate::scene::camera_uniform::CameraUniform;
+use crate::scene::water_uniform::WaterUniform;
+use crate::world::chunk_draws::ChunkDraws;
+use crate::world::quad_index_buffer::QuadIndexBuffer;
+use crate::world::chunk_buffer_pool::ChunkBufferPool;
+
+pub struct WaterPipeline {
+    pipeline: RenderPipeline,
+    blit_pipeline: RenderPipeline,
+
+    diffuse_bind_group: BindGroup,
+    diffuse_texture: Texture,
+
+    camera_buffer: Buffer,
+    camera_bind_group: BindGroup,
+
+    water_buffer: Buffer,
+    water_bind_group: BindGroup,
+
+    scene_bind_group_layout: BindGroupLayout,
+    scene_bind_group: BindGroup,
+
+    blit_bind_group_layout: BindGroupLayout,
+    blit_bind_group: BindGroup,
+}
+
+impl WaterPipeline {
+
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        config: &SurfaceConfiguration,
+        camera_uniform: &CameraUniform,
+        water_uniform: &WaterUniform,
+        scene_texture: &Texture,
+        scene_depth_texture: &Texture,
+    ) -> Self {
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Water Camera Buffer"),
+            contents: bytemuck::cast_slice(&[*camera_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let water_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Water Buffer"),
+            contents: bytemuck::cast_slice(&[*water_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let diffuse_image = image::io::Reader::open("assets/textures/atlas.png")
+            .unwrap()
+            .decode()
+            .unwrap()
+            .flipv();
+
+        let diffuse_texture = Texture::from_image(device, queue, &diffuse_image, Some("water_atlas")).unwrap();
+
+        let texture_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("water_texture_bind_group_layout"),
+        });
+
+        let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(diffuse_texture.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(diffuse_texture.sampler()),
+                },
+            ],
+            label: Some("water_diffuse_bind_group"),
+        });
+
+        let camera_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("water_camera_bind_group_layout"),
+        });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("water_camera_bind_group"),
+        });
+
+        let water_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("water_bind_group_layout"),
+        });
+
+        let water_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &water_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: water_buffer.as_entire_binding(),
+            }],
+            label: Some("water_bind_group"),
+        });
+
+        let scene_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("water_scene_bind_group_layout"),
+        });
+
+        let blit_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("blit_bind_group_layout"),
+        });
+
+        let (scene_bind_group, blit_bind_group) = Self::create_scene_bind_groups(
+            device,
+            &scene_bind_group_layout,
+            &blit_bind_group_layout,
+            scene_texture,
+            scene_depth_texture,
+        );
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Water Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/water.wgsl").into()),
+        });
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Water Render Pipeline Layout"),
+            bind_group_layouts: &[
+                &camera_bind_group_layout,
+                &water_bind_group_layout,
+                &texture_bind_group_layout,
+                &scene_bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Water Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[
+                    Vertex::desc(),
+                    Vertex::origin_desc(),
+                ],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Front),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        let blit_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Blit Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/blit.wgsl").into()),
+        });
+
+        let blit_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Blit Render Pipeline Layout"),
+            bind_group_layouts: &[
+                &blit_bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let blit_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Blit Render Pipeline"),
+            layout: Some(&blit_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &blit_shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &blit_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            blit_pipeline,
+
+            diffuse_bind_group,
+            diffuse_texture,
+
+            camera_buffer,
+            camera_bind_group,
+
+            water_buffer,
+            water_bind_group,
+
+            scene_bind_group_layout,
+            scene_bind_group,
+
+            blit_bind_group_layout,
+            blit_bind_group,
+        }
+    }
+
+    fn create_scene_bind_groups(
+        device: &Device,
+        scene_bind_group_layout: &BindGroupLayout,
+        blit_bind_group_layout: &BindGroupLayout,
+        scene_texture: &Texture,
+        scene_depth_texture: &Texture,
+    ) -> (BindGroup, BindGroup) {
+        let scene_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: scene_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(scene_texture.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(scene_texture.sampler()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(scene_depth_texture.view()),
+                },
+            ],
+            label: Some("water_scene_bind_group"),
+        });
+
+        let blit_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: blit_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(scene_texture.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(scene_texture.sampler()),
+                },
+            ],
+            label: Some("blit_bind_group"),
+        });
+
+        (scene_bind_group, blit_bind_group)
+    }
+
+    pub fn resize(
+        &mut self,
+        device: &Device,
+        scene_texture: &Texture,
+        scene_depth_texture: &Texture,
+    ) {
+        let (scene_bind_group, blit_bind_group) = Self::create_scene_bind_groups(
+            device,
+            &self.scene_bind_group_layout,
+            &self.blit_bind_group_layout,
+            scene_texture,
+            scene_depth_texture,
+        );
+
+        self.scene_bind_group = scene_bind_group;
+        self.blit_bind_group = blit_bind_group;
+    }
+
+    pub fn update(
+        &mut self,
+        queue: &Queue,
+        camera_uniform: &CameraUniform,
+        water_uniform: &WaterUniform,
+    ) {
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[*camera_uniform]));
+        queue.write_buffer(&self.water_buffer, 0, bytemuck::cast_slice(&[*water_uniform]));
+    }
+
+    pub fn pipeline(&self) -> &RenderPipeline {
+        &self.pipeline
+    }
+
+    pub fn diffuse_texture(&self) -> &Texture {
+        &self.diffuse_texture
+    }
+
+}
+
+pub trait DrawWater<'a> {
+    fn draw_scene(
+        &mut self,
+        pipeline: &'a WaterPipeline,
+    );
+
+    fn attach_pipeline(
+        &mut self,
+        pipeline: &'a WaterPipeline,
+        quad_indices: &'a QuadIndexBuffer,
+    );
+
+    fn draw_water(
+        &mut self,
+        vertices: &'a ChunkBufferPool,
+        draws: &'a ChunkDraws,
+    );
+}
+
+impl<'a, 'b> DrawWater<'b> for wgpu::RenderPass<'a>
+    where 'b: 'a {
+    fn draw_scene(
+        &mut self,
+        pipeline: &'a WaterPipeline,
+    ) {
+        self.set_pipeline(&pipeline.blit_pipeline);
+        self.set_bind_group(0, &pipeline.blit_bind_group, &[]);
+        self.draw(0..3, 0..1);
+    }
+
+    fn attach_pipeline(
+        &mut self,
+        pipeline: &'a WaterPipeline,
+        quad_indices: &'a QuadIndexBuffer,
+    ) {
+        self.set_pipeline(pipeline.pipeline());
+        self.set_bind_group(0, &pipeline.camera_bind_group, &[]);
+        self.set_bind_group(1, &pipeline.water_bind_group, &[]);
+        self.set_bind_group(2, &pipeline.diffuse_bind_group, &[]);
+        self.set_bind_group(3, &pipeline.scene_bind_group, &[]);
+        self.set_index_buffer(quad_indices.buffer().slice(..), wgpu::IndexFormat::Uint32);
+    }
+
+    fn draw_water(
+        &mut self,
+        vertices: &'a ChunkBufferPool,
+        draws: &'a ChunkDraws,
+    ) {
+        let args = draws.draws(true);
+        let buffer = if let Some(buffer) = vertices.buffer() { buffer } else { return };
+
+        if draws.multi_draw() {
+            if !args.is_empty() {
+                self.set_vertex_buffer(0, buffer.slice(..));
+                self.set_vertex_buffer(1, draws.origins().slice(..));
+                self.multi_draw_indexed_indirect(draws.indirect_buffer(true), 0, args.len() as u32);
+            }
+            return;
+        }
+
+        for (i, (draw, label)) in args.iter().zip(draws.labels()).enumerate().filter(|(_, (d, _))| d.vertex_count > 0) {
+            self.push_debug_group(label);
+            self.set_vertex_buffer(0, vertices.slice(draw.vertex_offset as u32, QuadIndexBuffer::vertex_count(draw.vertex_count)));
+            self.set_vertex_buffer(1, draws.origin(i as u32));
+            self.draw_indexed(0..draw.vertex_count, 0, 0..1);
+            self.pop_debug_group();
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crossbeam::channel::{Receiver, Sender};
+use crate::events::game_event::GameEvent;
+
+// Publishers hold a sender and push events as they happen; the owner of the bus drains it once a
+// frame and hands every event to each listener, so publishers never call into subscribers.
+pub struct EventBus {
+    sender: Sender<GameEvent>,
+    receiver: Receiver<GameEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+
+    pub fn new() -> Self {
+        let (sender, receiver) = crossbeam::channel::unbounded();
+
+        Self { sender, receiver }
+    }
+
+    pub fn sender(&self) -> Sender<GameEvent> {
+        self.sender.clone()
+    }
+
+    pub fn drain(&self) -> Vec<GameEvent> {
+        self.receiver.try_iter().collect()
+    }
+
+}
@@ -0,0 +1,5 @@
+use crate::events::game_event::GameEvent;
+
+pub trait EventListener {
+    fn handle_event(&mut self, event: &GameEvent);
+}
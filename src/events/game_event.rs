@@ -0,0 +1,11 @@
+use crate::objects::block::Block;
+
+// Things that happened in the world which other subsystems may want to react to. Positions are
+// in world block coordinates, chunk positions in chunk coordinates.
+#[derive(Debug, Copy, Clone)]
+pub enum GameEvent {
+    BlockPlaced { position: (i32, i32, i32), block: Block },
+    BlockBroken { position: (i32, i32, i32), block: Block },
+    ChunkLoaded { position: (i32, i32) },
+    PlayerMovedChunk { from: (i32, i32), to: (i32, i32) },
+}
@@ -0,0 +1,11 @@
+pub mod window;
+pub mod engine;
+pub mod mesh;
+pub mod objects;
+pub mod scene;
+pub mod world;
+pub mod events;
+
+pub use crate::engine::engine::{Engine, EngineCallbacks, EngineConfig, EngineContext};
+pub use crate::objects::block_registry::BlockRegistry;
+pub use crate::world::worldgen_config::WorldgenConfig as WorldConfig;
\ No newline at end of file
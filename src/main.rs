@@ -1,76 +1,245 @@
-mod window;
-mod engine;
-mod mesh;
-mod objects;
-mod scene;
-mod world;
-
+use cgmath::{Deg, vec3};
 use crossbeam::channel::unbounded;
-use winit::dpi::{LogicalPosition, LogicalSize};
-use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
-use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::{CursorGrabMode, WindowBuilder};
-use crate::engine::app::App;
-use crate::window::event_loop_request::EventLoopRequest;
-use crate::window::state::State;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+use winit::window::WindowBuilder;
+use wgpu_voxels::{BlockRegistry, Engine, EngineConfig, WorldConfig};
+use wgpu_voxels::engine::app::App;
+use wgpu_voxels::scene::camera::Camera;
+use wgpu_voxels::scene::golden_scene::GoldenScene;
+use wgpu_voxels::scene::sequence::Sequence;
+use wgpu_voxels::window::graphics_preset::GraphicsPreset;
+use wgpu_voxels::world::chunk::Chunk;
+use wgpu_voxels::world::lod::LodDistances;
+use wgpu_voxels::world::seam_check;
+use wgpu_voxels::world::world::World;
+use wgpu_voxels::world::worldgen_config::WorldgenWatcher;
 
+// The game's own frontend on top of the library: command line tools that don't need a window, and
+// otherwise the engine with no callbacks of its own.
 async fn run() {
     // env_logger::init();
+    let sequence = sequence_flag();
+
+    let map_radius = number_flag::<i32>("--map");
+
+    // The map is twice the radius wide, so it needs at least one chunk on each side.
+    if map_radius.is_some_and(|radius| radius < 1) {
+        eprintln!("--map expects a radius of at least 1");
+        std::process::exit(2);
+    }
+
+    if let Some(radius) = map_radius {
+        save_map(radius);
+        return;
+    }
+
+    let pregen_radius = number_flag::<i32>("--pregen");
+
+    if let Some(radius) = pregen_radius {
+        pregenerate(radius);
+        return;
+    }
+
+    let seam_radius = number_flag::<i32>("--seam-check");
+
+    if let Some(radius) = seam_radius {
+        let passed = check_seams(radius);
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
+    let args = std::env::args().collect::<Vec<_>>();
+
+    let cache_mb = number_flag::<usize>("--chunk-cache-mb");
+
+    let threads = number_flag::<usize>("--threads");
+
+    // A preset name, or "auto" to benchmark again.
+    let graphics = std::env::args()
+        .skip_while(|arg| arg != "--graphics")
+        .nth(1);
+
+    if args.iter().any(|arg| arg == "--golden") {
+        let passed = run_golden(args.iter().any(|arg| arg == "--bless")).await;
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
+    // Without the flag the preset picked on first launch is used.
+    let preset = match &graphics {
+        Some(name) => GraphicsPreset::parse(name),
+        None => GraphicsPreset::load(GraphicsPreset::PATH),
+    };
+
+    let config = EngineConfig {
+        sequence,
+        safe_mode: args.iter().any(|arg| arg == "--safe-mode"),
+        chunk_cache_memory: cache_mb.map(|mb| mb * 1024 * 1024),
+        worker_threads: threads,
+        graphics: preset,
+        vsync: !args.iter().any(|arg| arg == "--no-vsync"),
+        ..EngineConfig::default()
+    };
+
+    Engine::new(config).await.run(());
+}
+
+// The number following `flag`, if it was given. A missing or malformed one ends the program with
+// usage status instead of a panic.
+fn number_flag<T: std::str::FromStr>(flag: &str) -> Option<T> {
+    let mut args = std::env::args().skip_while(|arg| arg != flag);
+    args.next()?;
+
+    match args.next() {
+        Some(value) => match value.parse() {
+            Ok(number) => Some(number),
+            Err(_) => {
+                eprintln!("{} expects a number, got {:?}", flag, value);
+                std::process::exit(2);
+            },
+        },
+        None => {
+            eprintln!("{} expects a number", flag);
+            std::process::exit(2);
+        },
+    }
+}
+
+// The sequence file following --sequence, loaded, if it was given. Like `number_flag`, a missing
+// or unloadable one ends the program with usage status.
+fn sequence_flag() -> Option<Sequence> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--sequence");
+    args.next()?;
+
+    let path = args.next().unwrap_or_else(|| {
+        eprintln!("--sequence expects a path");
+        std::process::exit(2);
+    });
+
+    Some(Sequence::load(path).unwrap_or_else(|e| {
+        eprintln!("{:?}", e);
+        std::process::exit(2);
+    }))
+}
+
+// Golden scenes render into an offscreen target, but wgpu still needs a surface to pick an adapter
+// and format, so a hidden window of the golden size is created.
+async fn run_golden(bless: bool) -> bool {
     let event_loop = EventLoop::new();
-    let window = WindowBuilder::new().build(&event_loop)
+    let window = WindowBuilder::new()
+        .with_visible(false)
+        .with_inner_size(PhysicalSize::new(GoldenScene::WIDTH, GoldenScene::HEIGHT))
+        .build(&event_loop)
         .unwrap();
 
-    window.set_cursor_grab(CursorGrabMode::Confined);
-    window.set_cursor_visible(false);
-    window.set_inner_size(LogicalSize::new(1920, 1080));
+    let mut app = App::new(&window, unbounded().0, None, None, false).await;
 
-    if let Some(monitor) = window.current_monitor() {
-        let size = monitor.size();
-        let wsize = window.outer_size();
+    app.run_golden(bless)
+}
 
-        let off_x = (size.width - wsize.width) / 2;
-        let off_y = (size.height - wsize.height) / 2;
+// Writes a top-down preview of the configured world without opening a window.
+fn save_map(radius: i32) {
+    let config = WorldgenWatcher::new(WorldConfig::PATH).config();
+    let world = World::new(0, config.world_type.generator(&config), unbounded().0);
+    let path = format!("screenshots/map-{}.png", config.seed);
 
-        window.set_outer_position(LogicalPosition::new(off_x, off_y));
-    }
+    std::fs::create_dir_all("screenshots").unwrap();
+    world.render_map(radius).save(&path).unwrap();
+
+    let (explored, total) = world.exploration().explored_within(radius);
+
+    println!("Saved {:?}", path);
+    println!("Explored {} of {} chunks ({:.1}%)", explored, total, explored as f32 / total as f32 * 100.0);
+}
+
+// Generates and saves the chunks around spawn of the configured world without opening a window.
+fn pregenerate(radius: i32) {
+    use std::io::Write;
+
+    let config = WorldgenWatcher::new(WorldConfig::PATH).config();
+    let mut world = World::new(0, config.world_type.generator(&config), unbounded().0);
+
+    world.pregenerate(radius, |done, total| {
+        print!("\rGenerating chunks: {}/{}", done, total);
+        let _ = std::io::stdout().flush();
+    });
+
+    println!();
+    println!("Saved {} chunks to {:?}", (radius * 2 + 1).pow(2), World::save_dir(config.seed));
+}
 
-    let (sender, receiver) = unbounded::<EventLoopRequest>();
+// Loads the chunks around spawn of the configured world the way the game does and compares their
+// meshes with the faces their blocks call for: once loaded, after moving over a chunk so new ones
+// stream in at the edge, and after edits along every chunk border. The world's save is left alone.
+fn check_seams(radius: i32) -> bool {
+    let config = WorldgenWatcher::new(WorldConfig::PATH).config();
+    let mut world = World::new(radius, config.world_type.generator(&config), unbounded().0);
+    world.set_saving(false);
 
-    let mut app = App::new(&window, sender).await;
+    let mut camera = Camera::new(vec3(8.0, 100.0, 8.0), Deg(0.0), Deg(0.0));
 
-    let mut last_render_time = instant::Instant::now();
-    let mut last_fps_check = instant::Instant::now();
-    let mut fps = 0;
+    // Everything within the render distance is meshed at full resolution.
+    world.set_lod_distances(LodDistances { half: radius + 1, quarter: radius + 1 });
+    world.generate(&camera);
 
-    event_loop.run(move |event, _, control_flow| match event {
-        Event::DeviceEvent { event, .. } => app.handle_device_event(&event),
-        Event::WindowEvent {
-            ref event,
-            window_id,
-        } if window.id() == window_id => app.handle_window_event(event),
-        Event::RedrawRequested(id) if window.id() == id => {
-            let now = instant::Instant::now();
-            let dt = now - last_render_time;
+    let check = |step: &str, world: &mut World, camera: &Camera| {
+        // Updates at least once, so the world sees where the camera moved.
+        loop {
+            world.update(camera);
+            world.take_updates();
 
-            if now - last_fps_check >= instant::Duration::from_secs(2) {
-                fps = (1.0 / dt.as_secs_f32()).round() as u32;
-                last_fps_check = now;
+            if world.is_settled() {
+                break;
             }
 
-            last_render_time = now;
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
 
-            app.handle_redraw_request(dt, fps);
-        },
-        Event::MainEventsCleared => {
-            window.request_redraw();
+        let mismatches = seam_check::check(world, World::to_local_position(camera.position()), radius).unwrap();
+
+        for m in mismatches.iter() {
+            println!(
+                "{}: chunk {:?} section {}: {} faces meshed, {} expected",
+                step, m.position, m.section, m.meshed, m.expected,
+            );
+        }
+
+        println!("{}: {} sections with missing or duplicate faces", step, mismatches.len());
+        mismatches.is_empty()
+    };
+
+    let mut passed = check("loaded", &mut world, &camera);
+
+    camera.set_position(vec3(8.0 + Chunk::WIDTH as f32, 100.0, 8.0));
+    passed &= check("moved", &mut world, &camera);
+
+    let (wx, wz) = Chunk::local_to_world_position(World::to_local_position(camera.position()));
+    let (air, stone) = (BlockRegistry::by_name("air").unwrap(), BlockRegistry::by_name("stone").unwrap());
+    let mut blocks = vec![];
+
+    // Digs out the surface along two borders of every other chunk and builds on the other two, so
+    // the chunks in between are only remeshed if they're told about it.
+    for x in wx - radius * Chunk::WIDTH..wx + radius * Chunk::WIDTH {
+        for z in wz - radius * Chunk::DEPTH..wz + radius * Chunk::DEPTH {
+            let (cx, cz) = World::chunk_position(x, z);
+            let (lx, lz) = (x.rem_euclid(Chunk::WIDTH), z.rem_euclid(Chunk::DEPTH));
+            let y = if let Some(y) = world.surface_height(x, z) { y } else { continue };
 
-            match receiver.try_recv() {
-                Ok(EventLoopRequest::Close) => *control_flow = ControlFlow::Exit,
-                _ => {},
+            if (cx + cz) % 2 != 0 || y == 0 {
+                continue;
             }
-        },
-        _ => {},
-    });
+
+            if (lx == 0 || lz == 0) && (x + z) % 2 == 0 {
+                blocks.push(((x, y - 1, z), air));
+            } else if (lx == Chunk::WIDTH - 1 || lz == Chunk::DEPTH - 1) && (x + z) % 3 == 0 {
+                blocks.push(((x, y, z), stone));
+            }
+        }
+    }
+
+    world.set_blocks(blocks);
+    passed &= check("edited", &mut world, &camera);
+
+    passed
 }
 
 fn main() {
@@ -1,4 +1,5 @@
 use crate::objects::block::Block;
+use crate::world::quad_index_buffer::QuadIndexBuffer;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -25,22 +26,18 @@ impl TargetVertex {
         let mut indices = vec![];
         let mut i_off = 0;
         let p = &Block::POSITIONS;
-        let ind = &Block::INDICES;
+        let ind = &QuadIndexBuffer::QUAD;
 
-        for i in 0..6 {
-            for v in 0..4 {
+        for face in p.iter() {
+            for corner in face.iter() {
                 vertices.push(TargetVertex {
-                    position: [
-                        p[i][v][0],
-                        p[i][v][1],
-                        p[i][v][2],
-                    ],
+                    position: *corner,
                 });
             }
 
             indices.extend_from_slice(&[
-                ind[i][0] + i_off, ind[i][1] + i_off, ind[i][2] + i_off,
-                ind[i][3] + i_off, ind[i][4] + i_off, ind[i][5] + i_off,
+                ind[0] + i_off, ind[1] + i_off, ind[2] + i_off,
+                ind[3] + i_off, ind[4] + i_off, ind[5] + i_off,
             ]);
 
             i_off += 4;
@@ -1,19 +1,54 @@
+// Chunk vertex packed into two words. Positions are relative to the origin of the section being
+// drawn, which comes in as a per-instance attribute, and are stored in tenths of a block so
+// scaled blocks like water keep their exact height.
+//
+// position: x, y and z in bits 0-7, 8-15 and 16-23, opacity as 0-255 in bits 24-31.
+// uv: u and v in atlas tiles, 0-16, in bits 0-4 and 5-9, then the overlay's u and v in bits 10-14
+// and 15-19, whether there is an overlay in bit 20, and whether the vertex is on a shore in bit 21.
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Vertex {
-    pub position: [f32; 3],
-    pub uv: [f32; 2],
-    pub opacity: f32,
+    pub position: u32,
+    pub uv: u32,
 }
 
 impl Vertex {
 
-    const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
-        0 => Float32x3,
-        1 => Float32x2,
-        2 => Float32
+    pub const POSITION_STEPS: f32 = 10.0;
+
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+        0 => Uint32,
+        1 => Uint32,
+    ];
+
+    const ORIGIN_ATTRIBUTES: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![
+        2 => Float32x3,
     ];
 
+    // `position` is relative to the section origin, `uv` in atlas tiles.
+    pub fn new(position: [f32; 3], uv: [f32; 2], opacity: f32) -> Self {
+        let [x, y, z] = position.map(|p| (p * Self::POSITION_STEPS).round() as u32 & 0xff);
+        let [u, v] = uv.map(|t| t as u32 & 0x1f);
+        let opacity = (opacity.clamp(0.0, 1.0) * 255.0).round() as u32;
+
+        Self {
+            position: x | y << 8 | z << 16 | opacity << 24,
+            uv: u | v << 5,
+        }
+    }
+
+    // A second texture drawn over the face, blended by its alpha; `uv` in atlas tiles.
+    pub fn set_overlay(&mut self, uv: [f32; 2]) {
+        let [u, v] = uv.map(|t| t as u32 & 0x1f);
+
+        self.uv = self.uv & 0x3ff | u << 10 | v << 15 | 1 << 20;
+    }
+
+    // Water vertices touching a solid block, where the water shader draws foam.
+    pub fn set_shore(&mut self) {
+        self.uv |= 1 << 21;
+    }
+
     pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
@@ -22,4 +57,13 @@ impl Vertex {
         }
     }
 
+    // The section origin, one per draw.
+    pub fn origin_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ORIGIN_ATTRIBUTES,
+        }
+    }
+
 }
\ No newline at end of file
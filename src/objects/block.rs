@@ -1,5 +1,6 @@
 use crate::mesh::vertex::Vertex;
 use crate::objects::block_material::BlockMaterial;
+use crate::objects::block_tag::BlockTag;
 
 #[derive(Debug, Copy, Clone)]
 pub struct Block {
@@ -9,6 +10,7 @@ pub struct Block {
     pub uv: [[u8; 2]; 6],
     pub scale: [f32; 3],
     pub opacity: f32,
+    pub tags: &'static [BlockTag],
 }
 
 impl Block {
@@ -40,62 +42,60 @@ impl Block {
         [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
     ];
 
-    pub const INDICES: [[u32; 6]; 6] = [
-        [0, 1, 2, 0, 2, 3],
-        [0, 1, 2, 0, 2, 3],
-        [0, 1, 2, 0, 2, 3],
-        [0, 1, 2, 0, 2, 3],
-        [0, 1, 2, 0, 2, 3],
-        [0, 1, 2, 0, 2, 3],
-    ];
+    pub fn has_tag(&self, tag: BlockTag) -> bool {
+        self.tags.contains(&tag)
+    }
+
+    // Drawn over the side of a dirt block under an exposed grass side, so slopes and ledges get the
+    // grass hanging over their edge and a little shadow under it instead of a hard line.
+    pub const GRASS_SIDE_OVERLAY: [u8; 2] = [1, 14];
 
+    // `size` scales the whole block, for distant chunks meshed at a lower resolution. `overlays`
+    // are atlas tiles drawn over each face, see `Vertex::set_overlay`.
     pub fn build_faces(
         &self,
         x: f32,
         y: f32,
         z: f32,
+        size: f32,
         faces: [bool; 6],
-        index_offset: u32,
-    ) -> (Vec<Vertex>, Vec<u32>) {
+        overlays: [Option<[u8; 2]>; 6],
+    ) -> Vec<Vertex> {
 
         let mut vertices = vec![];
-        let mut indices = vec![];
         let p = &Self::POSITIONS;
         let u = &Self::UV;
-        let ind = &Self::INDICES;
-        let mut i_off = index_offset;
-
-        let ux = 16.0 / 256.0;
-        let uy = 16.0 / 256.0;
         let uvi = self.uv;
 
         for i in 0..6 {
             if !faces[i] { continue; }
 
             for v in 0..4 {
-                vertices.push(Vertex {
-                    position: [
-                        (p[i][v][0] * self.scale[0]) + x,
-                        (p[i][v][1] * self.scale[1]) + y,
-                        (p[i][v][2] * self.scale[2]) + z,
+                let mut vertex = Vertex::new(
+                    [
+                        (p[i][v][0] * self.scale[0] * size) + x,
+                        (p[i][v][1] * self.scale[1] * size) + y,
+                        (p[i][v][2] * self.scale[2] * size) + z,
                     ],
-                    uv: [
-                        u[i][v][0] * ux + ux * uvi[i][0] as f32,
-                        u[i][v][1] * uy + uy * uvi[i][1] as f32,
+                    [
+                        u[i][v][0] + uvi[i][0] as f32,
+                        u[i][v][1] + uvi[i][1] as f32,
                     ],
-                    opacity: self.opacity,
-                });
-            }
+                    self.opacity,
+                );
 
-            indices.extend_from_slice(&[
-                ind[i][0] + i_off, ind[i][1] + i_off, ind[i][2] + i_off,
-                ind[i][3] + i_off, ind[i][4] + i_off, ind[i][5] + i_off,
-            ]);
+                if let Some(overlay) = overlays[i] {
+                    vertex.set_overlay([
+                        u[i][v][0] + overlay[0] as f32,
+                        u[i][v][1] + overlay[1] as f32,
+                    ]);
+                }
 
-            i_off += 4;
+                vertices.push(vertex);
+            }
         }
 
-        (vertices, indices)
+        vertices
     }
 
 }
\ No newline at end of file
@@ -0,0 +1,27 @@
+use crate::objects::block::Block;
+
+// Looks up the blocks the engine knows, for frontends that don't want to depend on ids. The
+// blocks themselves are fixed at compile time in `component.rs`.
+pub struct BlockRegistry;
+
+impl BlockRegistry {
+
+    // Ordered by id, air first.
+    pub fn all() -> Vec<Block> {
+        (0..=u8::MAX)
+            .map(Block::block)
+            .enumerate()
+            .filter(|(id, block)| block.id as usize == *id)
+            .map(|(_, block)| block)
+            .collect()
+    }
+
+    pub fn get(id: u8) -> Option<Block> {
+        Some(Block::block(id)).filter(|block| block.id == id)
+    }
+
+    pub fn by_name(name: &str) -> Option<Block> {
+        Block::by_name(name)
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+// Categories blocks belong to, so systems ask what a block is rather than keeping their own lists
+// of ids. A block's tags are listed with it in `component`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BlockTag {
+    // Placing a block, or decoration, may overwrite it.
+    Replaceable,
+    // Can't be broken.
+    Unbreakable,
+    Flammable,
+    // Which tool breaks the block fastest.
+    MineablePickaxe,
+    MineableShovel,
+    MineableAxe,
+}
+
+impl BlockTag {
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockTag::Replaceable => "replaceable",
+            BlockTag::Unbreakable => "unbreakable",
+            BlockTag::Flammable => "flammable",
+            BlockTag::MineablePickaxe => "mineable/pickaxe",
+            BlockTag::MineableShovel => "mineable/shovel",
+            BlockTag::MineableAxe => "mineable/axe",
+        }
+    }
+
+}
\ No newline at end of file
@@ -1,5 +1,6 @@
 use crate::objects::block::Block;
 use crate::objects::block_material::BlockMaterial;
+use crate::objects::block_tag::BlockTag;
 
 impl Block {
     
@@ -10,6 +11,7 @@ impl Block {
         uv: [[0;2]; 6],
         scale: [0.0, 0.0, 0.0],
         opacity: 0.0,
+        tags: &[BlockTag::Replaceable],
     };
 
     pub const Grass: Block = Block {
@@ -26,6 +28,7 @@ impl Block {
         ],
         scale: [1.0, 1.0, 1.0],
         opacity: 1.0,
+        tags: &[BlockTag::MineableShovel],
     };
 
     pub const Dirt: Block = Block {
@@ -35,6 +38,7 @@ impl Block {
         uv: [[2, 15]; 6],
         scale: [1.0, 1.0, 1.0],
         opacity: 1.0,
+        tags: &[BlockTag::MineableShovel],
     };
 
     pub const Stone: Block = Block {
@@ -44,6 +48,7 @@ impl Block {
         uv: [[3, 15]; 6],
         scale: [1.0, 1.0, 1.0],
         opacity: 1.0,
+        tags: &[BlockTag::MineablePickaxe],
     };
 
     pub const Water: Block = Block {
@@ -53,6 +58,114 @@ impl Block {
         uv: [[4, 15]; 6],
         scale: [1.0, 0.9, 1.0],
         opacity: 0.6,
+        tags: &[BlockTag::Replaceable],
+    };
+
+    pub const Wood: Block = Block {
+        name: "wood",
+        id: 5,
+        material: BlockMaterial::Solid,
+        uv: [
+            [5, 15],
+            [5, 15],
+            [5, 15],
+            [5, 15],
+            [6, 15],
+            [6, 15],
+        ],
+        scale: [1.0, 1.0, 1.0],
+        opacity: 1.0,
+        tags: &[BlockTag::Flammable, BlockTag::MineableAxe],
+    };
+
+    pub const Leaves: Block = Block {
+        name: "leaves",
+        id: 6,
+        material: BlockMaterial::Solid,
+        uv: [[7, 15]; 6],
+        scale: [1.0, 1.0, 1.0],
+        opacity: 1.0,
+        tags: &[BlockTag::Flammable],
+    };
+
+    pub const CoalOre: Block = Block {
+        name: "coal_ore",
+        id: 7,
+        material: BlockMaterial::Solid,
+        uv: [[8, 15]; 6],
+        scale: [1.0, 1.0, 1.0],
+        opacity: 1.0,
+        tags: &[BlockTag::MineablePickaxe],
+    };
+
+    pub const IronOre: Block = Block {
+        name: "iron_ore",
+        id: 8,
+        material: BlockMaterial::Solid,
+        uv: [[9, 15]; 6],
+        scale: [1.0, 1.0, 1.0],
+        opacity: 1.0,
+        tags: &[BlockTag::MineablePickaxe],
+    };
+
+    pub const Snow: Block = Block {
+        name: "snow",
+        id: 9,
+        material: BlockMaterial::Solid,
+        uv: [[10, 15]; 6],
+        scale: [1.0, 1.0, 1.0],
+        opacity: 1.0,
+        tags: &[BlockTag::MineableShovel],
+    };
+
+    pub const Sand: Block = Block {
+        name: "sand",
+        id: 10,
+        material: BlockMaterial::Solid,
+        uv: [[11, 15]; 6],
+        scale: [1.0, 1.0, 1.0],
+        opacity: 1.0,
+        tags: &[BlockTag::MineableShovel],
+    };
+
+    pub const Bedrock: Block = Block {
+        name: "bedrock",
+        id: 11,
+        material: BlockMaterial::Solid,
+        uv: [[12, 15]; 6],
+        scale: [1.0, 1.0, 1.0],
+        opacity: 1.0,
+        tags: &[BlockTag::Unbreakable],
+    };
+
+    pub const Beacon: Block = Block {
+        name: "beacon",
+        id: 12,
+        material: BlockMaterial::Solid,
+        uv: [[13, 15]; 6],
+        scale: [1.0, 1.0, 1.0],
+        opacity: 1.0,
+        tags: &[BlockTag::MineablePickaxe],
+    };
+
+    pub const GlowMushroom: Block = Block {
+        name: "glow_mushroom",
+        id: 13,
+        material: BlockMaterial::Solid,
+        uv: [[14, 15]; 6],
+        scale: [1.0, 1.0, 1.0],
+        opacity: 1.0,
+        tags: &[BlockTag::Flammable],
+    };
+
+    pub const Crystal: Block = Block {
+        name: "crystal",
+        id: 14,
+        material: BlockMaterial::Solid,
+        uv: [[15, 15]; 6],
+        scale: [1.0, 1.0, 1.0],
+        opacity: 1.0,
+        tags: &[BlockTag::MineablePickaxe],
     };
 
     pub fn block(id: u8) -> Block {
@@ -62,8 +175,43 @@ impl Block {
             2 => Self::Dirt,
             3 => Self::Stone,
             4 => Self::Water,
+            5 => Self::Wood,
+            6 => Self::Leaves,
+            7 => Self::CoalOre,
+            8 => Self::IronOre,
+            9 => Self::Snow,
+            10 => Self::Sand,
+            11 => Self::Bedrock,
+            12 => Self::Beacon,
+            13 => Self::GlowMushroom,
+            14 => Self::Crystal,
             _ => Self::Air,
         }
     }
+
+    pub fn by_name(name: &str) -> Option<Block> {
+        (0..=u8::MAX)
+            .map(Self::block)
+            .find(|b| b.name == name)
+    }
+
+    // Flat color used for top-down map previews.
+    pub fn map_color(&self) -> [u8; 3] {
+        match self.id {
+            1 => [92, 148, 60],
+            2 => [134, 96, 67],
+            3 | 7 | 8 => [125, 125, 125],
+            4 => [52, 92, 196],
+            5 => [102, 81, 51],
+            6 => [56, 110, 40],
+            9 => [240, 244, 248],
+            10 => [219, 207, 163],
+            11 => [60, 60, 60],
+            12 => [120, 230, 240],
+            13 => [96, 230, 184],
+            14 => [160, 110, 220],
+            _ => [0, 0, 0],
+        }
+    }
     
 }
\ No newline at end of file
@@ -3,4 +3,6 @@ pub mod block_material;
 pub mod component;
 pub mod block_face;
 pub mod target;
-pub mod target_uniform;
\ No newline at end of file
+pub mod target_uniform;
+pub mod block_tag;
+pub mod block_registry;
\ No newline at end of file
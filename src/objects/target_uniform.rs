@@ -9,6 +9,12 @@ pub struct TargetUniform {
     pub face: u32,
 }
 
+impl Default for TargetUniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TargetUniform {
 
     pub fn new() -> Self {
@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct BeaconUniform {
+    time: f32,
+    _padding: [f32; 3],
+}
+
+impl Default for BeaconUniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BeaconUniform {
+
+    pub fn new() -> Self {
+        Self {
+            time: 0.0,
+            _padding: [0.0; 3],
+        }
+    }
+
+    pub fn update(&mut self, dt: Duration) {
+        self.time = (self.time + dt.as_secs_f32()) % 3600.0;
+    }
+
+}
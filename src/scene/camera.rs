@@ -2,20 +2,25 @@ use std::f32::consts::FRAC_PI_2;
 use std::time::Duration;
 use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, Rad, vec3, Vector3, Zero};
 use winit::event::{ElementState, MouseButton, VirtualKeyCode};
+use crate::scene::movement_config::MovementConfig;
 
+#[derive(Clone)]
 pub struct Camera {
     position: Vector3<f32>,
     front: Vector3<f32>,
     up: Vector3<f32>,
     yaw: Rad<f32>,
     pitch: Rad<f32>,
+    roll: Rad<f32>,
 
     yaw_delta: Rad<f32>,
     pitch_delta: Rad<f32>,
 
     sensitivity: f32,
 
-    movement_speed: f32,
+    movement: MovementConfig,
+    velocity: Vector3<f32>,
+    grounded: bool,
 
     moving_forward: bool,
     moving_backward: bool,
@@ -39,6 +44,9 @@ impl Camera {
 
     pub const SAFE_FRAC_PI_2: f32 = FRAC_PI_2 - 0.0001;
 
+    // Coasting slower than this in blocks per second stops outright.
+    pub const MIN_SPEED: f32 = 0.01;
+
     pub fn new<
         V: Into<Vector3<f32>>,
         Y: Into<Rad<f32>>,
@@ -54,10 +62,13 @@ impl Camera {
             up: Vector3::zero(),
             yaw: yaw.into(),
             pitch: pitch.into(),
+            roll: Rad::zero(),
             yaw_delta: Rad::zero(),
             pitch_delta: Rad::zero(),
             sensitivity: 1.0,
-            movement_speed: 3.0,
+            movement: MovementConfig::default(),
+            velocity: Vector3::zero(),
+            grounded: false,
             moving_forward: false,
             moving_backward: false,
             moving_left: false,
@@ -92,6 +103,18 @@ impl Camera {
         self.pitch += pitch;
     }
 
+    pub fn roll(&self) -> &Rad<f32> {
+        &self.roll
+    }
+
+    pub fn set_roll(&mut self, roll: Rad<f32>) {
+        self.roll = roll;
+    }
+
+    pub fn add_roll(&mut self, roll: Rad<f32>) {
+        self.roll += roll;
+    }
+
     pub fn position(&self) -> &Vector3<f32> {
         &self.position
     }
@@ -157,27 +180,69 @@ impl Camera {
 
         let right = front.cross(Vector3::unit_y()).normalize();
 
+        let speed = self.movement.speed;
+
         let amount_forward = if self.moving_forward {
-            self.movement_speed * if self.is_sprinting { 4.0 } else { 1.0 }
+            speed * if self.is_sprinting { self.movement.sprint_multiplier } else { 1.0 }
         } else {
             0.0
         };
 
-        let amount_back = if self.moving_backward { self.movement_speed } else { 0.0 };
-        let amount_left = if self.moving_left { self.movement_speed } else { 0.0 };
-        let amount_right = if self.moving_right { self.movement_speed } else { 0.0 };
-        let amount_up = if self.moving_up { self.movement_speed } else { 0.0 };
-        let amount_down = if self.moving_down { self.movement_speed } else { 0.0 };
+        let amount_back = if self.moving_backward { speed } else { 0.0 };
+        let amount_left = if self.moving_left { speed } else { 0.0 };
+        let amount_right = if self.moving_right { speed } else { 0.0 };
+        let amount_up = if self.moving_up { speed } else { 0.0 };
+        let amount_down = if self.moving_down { speed } else { 0.0 };
 
-        self.translate(front * (amount_forward - amount_back) * dt);
-        self.translate(right * (amount_right - amount_left) * dt);
+        let target = front * (amount_forward - amount_back)
+            + right * (amount_right - amount_left)
+            + Vector3::unit_y() * (amount_up - amount_down);
 
-        self.translate_y((amount_up - amount_down) * dt);
+        let rate = if target != Vector3::zero() {
+            self.movement.acceleration
+        } else if self.grounded {
+            self.movement.ground_friction
+        } else {
+            self.movement.air_friction
+        };
+
+        // Independent of the frame rate, unlike adding a fixed step each frame.
+        self.velocity = target + (self.velocity - target) * (-rate * dt).exp();
+
+        if self.velocity.magnitude2() < Self::MIN_SPEED * Self::MIN_SPEED && target == Vector3::zero() {
+            self.velocity = Vector3::zero();
+        }
+
+        self.translate(self.velocity * dt);
 
         self.set_front(front);
         self.set_up(right.cross(front).normalize());
     }
 
+    pub fn stop(&mut self) {
+        self.moving_forward = false;
+        self.moving_backward = false;
+        self.moving_left = false;
+        self.moving_right = false;
+        self.moving_up = false;
+        self.moving_down = false;
+        self.is_sprinting = false;
+        self.velocity = Vector3::zero();
+    }
+
+    pub fn movement(&self) -> &MovementConfig {
+        &self.movement
+    }
+
+    pub fn set_movement(&mut self, movement: MovementConfig) {
+        self.movement = movement;
+    }
+
+    // Whether the camera is standing on a block, which decides how quickly it stops.
+    pub fn set_grounded(&mut self, grounded: bool) {
+        self.grounded = grounded;
+    }
+
     pub fn process_mouse_motion(&mut self, dx: f64, dy: f64) {
         self.yaw_delta = Rad(dx as f32);
         self.pitch_delta = Rad(dy as f32);
@@ -208,15 +273,30 @@ impl Camera {
         let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
         let (sin_yaw, cos_yaw) = self.yaw.0.sin_cos();
 
+        let front = Vector3::new(
+            sin_yaw * cos_pitch,
+            sin_pitch,
+            -cos_yaw * cos_pitch
+        ).normalize();
+
         Matrix4::look_at(
             Point3::from_vec(self.position),
-            Point3::from_vec(Vector3::new(
-                sin_yaw * cos_pitch,
-                sin_pitch,
-                -cos_yaw * cos_pitch
-            ).normalize() + self.position),
-            Vector3::unit_y(),
+            Point3::from_vec(front + self.position),
+            Self::rolled_up(front, self.roll),
         )
     }
 
+    // Roll turns the up vector around the view direction; zero roll keeps world up.
+    pub fn rolled_up(front: Vector3<f32>, roll: Rad<f32>) -> Vector3<f32> {
+        if roll == Rad::zero() {
+            return Vector3::unit_y();
+        }
+
+        let right = front.cross(Vector3::unit_y()).normalize();
+        let up = right.cross(front).normalize();
+        let (sin_roll, cos_roll) = roll.0.sin_cos();
+
+        up * cos_roll + right * sin_roll
+    }
+
 }
\ No newline at end of file
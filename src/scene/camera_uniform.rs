@@ -9,6 +9,12 @@ pub struct CameraUniform {
     projection: [[f32; 4]; 4],
 }
 
+impl Default for CameraUniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CameraUniform {
 
     pub fn new() -> Self {
@@ -1,7 +1,8 @@
-use cgmath::{Angle, InnerSpace, Vector3};
-use collision::Plane;
+use cgmath::{Angle, InnerSpace, Point3, Rad, Vector3};
+use collision::{Aabb3, Plane, Relation};
 use crate::scene::camera::Camera;
 use crate::scene::projection::Projection;
+use crate::world::chunk::Chunk;
 
 pub struct Frustum {
     pub near_plane: Plane<f32>,
@@ -15,51 +16,48 @@ pub struct Frustum {
 impl Frustum {
 
     pub fn new(camera: &Camera, projection: &Projection) -> Self {
-        let mut frustum = Self {
-            near_plane: Plane::from_abcd(0.0, 0.0, 0.0, 0.0),
-            far_plane: Plane::from_abcd(0.0, 0.0, 0.0, 0.0),
-            right_plane: Plane::from_abcd(0.0, 0.0, 0.0, 0.0),
-            left_plane: Plane::from_abcd(0.0, 0.0, 0.0, 0.0),
-            top_plane: Plane::from_abcd(0.0, 0.0, 0.0, 0.0),
-            bottom_plane: Plane::from_abcd(0.0, 0.0, 0.0, 0.0),
-        };
-
-        frustum.update(camera, projection);
-
-        frustum
+        Self::from_view(*camera.position(), *camera.front(), *camera.roll(), projection)
     }
 
     pub fn update(&mut self, camera: &Camera, projection: &Projection) {
-        let half_v_side = projection.zfar * (projection.fovy * 0.5).tan();
-        let half_h_side = half_v_side * projection.aspect;
-        let front_times_far = projection.zfar * camera.front();
-        let pos = camera.position();
-        let right = camera.front().cross(Vector3::unit_y()).normalize();
-        let up = camera.up();
+        *self = Self::from_view(*camera.position(), *camera.front(), *camera.roll(), projection);
+    }
 
-        let near_normal = camera.front().normalize();
-        let near_face = near_normal.dot(pos + projection.znear * camera.front());
-        self.near_plane = Plane::new(near_normal, near_face);
+    pub fn from_view(position: Vector3<f32>, front: Vector3<f32>, roll: Rad<f32>, projection: &Projection) -> Self {
+        // The camera has no facing until its first update; default to looking down -z like yaw 0 does.
+        let front = if front.magnitude2() > 0.0 { front.normalize() } else { -Vector3::unit_z() };
+        let right = Self::right_of(front);
+        let up = right.cross(front).normalize();
 
-        let far_normal = -camera.front().normalize();
-        let far_face = far_normal.dot(pos + front_times_far);
-        self.far_plane = Plane::new(far_normal, far_face);
+        let (sin_roll, cos_roll) = roll.0.sin_cos();
+        let (right, up) = (right * cos_roll - up * sin_roll, up * cos_roll + right * sin_roll);
 
-        let right_normal = up.cross(front_times_far + right * half_h_side);
-        let right_face = right_normal.dot(pos.clone());
-        self.right_plane = Plane::new(right_normal, right_face);
+        let half_v_side = projection.zfar * (projection.fovy * 0.5).tan();
+        let half_h_side = half_v_side * projection.aspect;
+        let front_times_far = projection.zfar * front;
+
+        let plane = |normal: Vector3<f32>, point: Vector3<f32>| Plane::new(normal, normal.dot(point));
 
-        let left_normal = (front_times_far - right * half_h_side).cross(up.clone());
-        let left_face = left_normal.dot(pos.clone());
-        self.left_plane = Plane::new(left_normal, left_face);
+        Self {
+            near_plane: plane(front, position + projection.znear * front),
+            far_plane: plane(-front, position + front_times_far),
+            right_plane: plane(up.cross(front_times_far + right * half_h_side), position),
+            left_plane: plane((front_times_far - right * half_h_side).cross(up), position),
+            top_plane: plane(right.cross(front_times_far - up * half_v_side), position),
+            bottom_plane: plane((front_times_far + up * half_v_side).cross(right), position),
+        }
+    }
 
-        let top_normal = right.cross(front_times_far - up * half_v_side);
-        let top_face = top_normal.dot(pos.clone());
-        self.top_plane = Plane::new(top_normal, top_face);
+    // Looking straight up or down leaves no horizontal component to build a right vector from,
+    // so fall back to the x axis rather than producing NaN planes.
+    fn right_of(front: Vector3<f32>) -> Vector3<f32> {
+        let right = front.cross(Vector3::unit_y());
 
-        let bottom_normal = (front_times_far + up * half_v_side).cross(right);
-        let bottom_face = bottom_normal.dot(pos.clone());
-        self.bottom_plane = Plane::new(bottom_normal, bottom_face);
+        if right.magnitude2() < 1e-8 {
+            Vector3::unit_x()
+        } else {
+            right.normalize()
+        }
     }
 
     pub fn get(&self) -> collision::Frustum<f32> {
@@ -73,4 +71,104 @@ impl Frustum {
         )
     }
 
+    // Columns are only as tall as their highest non-empty section, so the empty sky above the
+    // terrain doesn't keep chunks behind the camera's view from being culled.
+    pub fn chunk_bounds((x, z): (i32, i32), height: i32) -> Aabb3<f32> {
+        let (wx, wz) = Chunk::local_to_world_position((x, z));
+
+        Aabb3::new(
+            Point3::new(wx as f32, 0.0, wz as f32),
+            Point3::new((wx + Chunk::WIDTH) as f32, height as f32, (wz + Chunk::DEPTH) as f32),
+        )
+    }
+
+    pub fn is_chunk_visible(frustum: &collision::Frustum<f32>, position: (i32, i32), height: i32) -> bool {
+        frustum.contains(&Self::chunk_bounds(position, height)) != Relation::Out
+    }
+
+    // One section of a column, cut off at the column's height. Sections entirely above it hold
+    // only air and are never visible.
+    pub fn section_bounds((x, z): (i32, i32), section: i32, height: i32) -> Option<Aabb3<f32>> {
+        let (wx, wz) = Chunk::local_to_world_position((x, z));
+        let bottom = section * Chunk::SECTION_HEIGHT;
+        let top = (bottom + Chunk::SECTION_HEIGHT).min(height);
+
+        if top <= bottom {
+            return None;
+        }
+
+        Some(Aabb3::new(
+            Point3::new(wx as f32, bottom as f32, wz as f32),
+            Point3::new((wx + Chunk::WIDTH) as f32, top as f32, (wz + Chunk::DEPTH) as f32),
+        ))
+    }
+
+    pub fn is_section_visible(frustum: &collision::Frustum<f32>, position: (i32, i32), section: i32, height: i32) -> bool {
+        Self::section_bounds(position, section, height).is_some_and(|bounds| frustum.contains(&bounds) != Relation::Out)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, vec3, Vector3};
+    use super::*;
+
+    fn projection() -> Projection {
+        Projection::new(1920, 1080, Deg(70.0), 0.1, 500.0)
+    }
+
+    fn frustum(position: Vector3<f32>, front: Vector3<f32>) -> collision::Frustum<f32> {
+        Frustum::from_view(position, front, Rad(0.0), &projection()).get()
+    }
+
+    #[test]
+    fn looking_straight_up_or_down_has_no_nan_planes() {
+        for front in [Vector3::unit_y(), -Vector3::unit_y()] {
+            assert_eq!(Frustum::right_of(front), Vector3::unit_x());
+
+            let f = Frustum::from_view(vec3(8.0, 64.0, 8.0), front, Rad(0.0), &projection());
+
+            for plane in [f.near_plane, f.far_plane, f.left_plane, f.right_plane, f.top_plane, f.bottom_plane] {
+                assert!(plane.n.x.is_finite() && plane.n.y.is_finite() && plane.n.z.is_finite() && plane.d.is_finite());
+            }
+        }
+    }
+
+    #[test]
+    fn chunk_behind_the_camera_is_culled() {
+        let frustum = frustum(vec3(8.0, 10.0, 8.0), Vector3::unit_z());
+
+        assert!(!Frustum::is_chunk_visible(&frustum, (0, -3), Chunk::HEIGHT));
+    }
+
+    #[test]
+    fn chunk_straddling_the_near_plane_is_visible() {
+        // The near plane sits at z = 0.05, inside the chunk from z = 0 to 16.
+        let frustum = frustum(vec3(8.0, 10.0, -0.05), Vector3::unit_z());
+
+        assert!(Frustum::is_chunk_visible(&frustum, (0, 0), Chunk::HEIGHT));
+    }
+
+    #[test]
+    fn chunk_containing_the_camera_is_visible() {
+        for front in [Vector3::unit_x(), -Vector3::unit_x(), Vector3::unit_z(), -Vector3::unit_z(), Vector3::unit_y()] {
+            let frustum = frustum(vec3(8.0, 10.0, 8.0), front);
+
+            assert!(Frustum::is_chunk_visible(&frustum, (0, 0), Chunk::HEIGHT));
+        }
+    }
+
+    #[test]
+    fn chunk_past_the_side_planes_is_culled() {
+        // With a 70 degree vertical fov at 16:9 the side planes are about 51 degrees off the view
+        // axis, so between z = 64 and 80 they run from x = 80 to 100 on either side.
+        let frustum = frustum(vec3(0.0, 10.0, 0.0), Vector3::unit_z());
+
+        assert!(Frustum::is_chunk_visible(&frustum, (6, 4), Chunk::HEIGHT));
+        assert!(!Frustum::is_chunk_visible(&frustum, (7, 4), Chunk::HEIGHT));
+        assert!(Frustum::is_chunk_visible(&frustum, (-7, 4), Chunk::HEIGHT));
+        assert!(!Frustum::is_chunk_visible(&frustum, (-8, 4), Chunk::HEIGHT));
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,61 @@
+use anyhow::*;
+use cgmath::{Deg, Vector3, vec3};
+use image::RgbaImage;
+
+// A fixed camera over a fixed seed. Rendering every scene and diffing against the images in
+// `golden/` catches visual regressions from shader and mesher changes. References depend on the
+// GPU and driver, so they're blessed on the machine that runs the check; see `golden/README.md`.
+pub struct GoldenScene {
+    pub name: &'static str,
+    pub seed: u32,
+    pub position: Vector3<f32>,
+    pub yaw: Deg<f32>,
+    pub pitch: Deg<f32>,
+}
+
+impl GoldenScene {
+
+    pub const DIR: &'static str = "golden";
+    pub const WIDTH: u32 = 640;
+    pub const HEIGHT: u32 = 360;
+
+    // Small per-channel differences are expected between drivers, so only pixels that are off
+    // by more than this count as mismatched.
+    pub const CHANNEL_TOLERANCE: u8 = 8;
+    pub const MAX_MISMATCH: f32 = 0.005;
+
+    // Updates to wait for the world to finish meshing; a scene that isn't done by then fails.
+    pub const MAX_UPDATES: u32 = 2000;
+
+    pub fn all() -> Vec<GoldenScene> {
+        vec![
+            GoldenScene { name: "spawn", seed: 1, position: vec3(0.0, 90.0, 0.0), yaw: Deg(0.0), pitch: Deg(-10.0) },
+            GoldenScene { name: "overview", seed: 1, position: vec3(0.0, 160.0, 0.0), yaw: Deg(45.0), pitch: Deg(-45.0) },
+            GoldenScene { name: "ground", seed: 7, position: vec3(24.0, 80.0, -40.0), yaw: Deg(200.0), pitch: Deg(-70.0) },
+        ]
+    }
+
+    pub fn path(&self) -> String {
+        format!("{}/{}.png", Self::DIR, self.name)
+    }
+
+    pub fn actual_path(&self) -> String {
+        format!("{}/{}.actual.png", Self::DIR, self.name)
+    }
+
+    // Fraction of pixels where any channel differs by more than the tolerance.
+    pub fn mismatch(actual: &RgbaImage, expected: &RgbaImage) -> Result<f32> {
+        if actual.dimensions() != expected.dimensions() {
+            bail!("size mismatch: rendered {:?}, expected {:?}", actual.dimensions(), expected.dimensions());
+        }
+
+        let mismatched = actual
+            .pixels()
+            .zip(expected.pixels())
+            .filter(|(a, b)| a.0.iter().zip(b.0.iter()).any(|(a, b)| a.abs_diff(*b) > Self::CHANNEL_TOLERANCE))
+            .count();
+
+        Ok(mismatched as f32 / (actual.width() * actual.height()) as f32)
+    }
+
+}
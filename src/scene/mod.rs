@@ -1,4 +1,11 @@
 pub mod camera;
 pub mod camera_uniform;
 pub mod projection;
-pub mod frustum;
\ No newline at end of file
+pub mod frustum;
+pub mod water_uniform;
+pub mod sequence;
+pub mod photo_mode;
+pub mod speed_lines_uniform;
+pub mod golden_scene;
+pub mod beacon_uniform;
+pub mod movement_config;
\ No newline at end of file
@@ -0,0 +1,56 @@
+use std::path::Path;
+use anyhow::*;
+use serde::Deserialize;
+
+// How the camera speeds up and slows down. Velocity closes in on the speed the held keys ask for
+// at `acceleration`, and coasts to a stop at the friction for where the camera is once they're
+// released. Rates are per second; the gap left after t seconds is e^(-rate * t) of the original.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MovementConfig {
+    // Blocks per second.
+    pub speed: f32,
+    // Only applies moving forward.
+    pub sprint_multiplier: f32,
+    pub acceleration: f32,
+    // Standing on a block, see `Camera::set_grounded`.
+    pub ground_friction: f32,
+    pub air_friction: f32,
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        Self {
+            speed: 3.0,
+            sprint_multiplier: 4.0,
+            acceleration: 10.0,
+            ground_friction: 12.0,
+            air_friction: 3.0,
+        }
+    }
+}
+
+impl MovementConfig {
+
+    pub const PATH: &'static str = "movement.toml";
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = std::fs::read_to_string(path.as_ref())
+            .with_context(|| format!("failed to read movement config {:?}", path.as_ref()))?;
+
+        Ok(toml::from_str(&data)?)
+    }
+
+    // Falls back to the defaults when the file does not exist or can't be read.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Self {
+        if !path.as_ref().exists() {
+            return Self::default();
+        }
+
+        Self::load(path).unwrap_or_else(|e| {
+            eprintln!("{:?}", e);
+            Self::default()
+        })
+    }
+
+}
\ No newline at end of file
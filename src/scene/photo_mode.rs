@@ -0,0 +1,69 @@
+use std::time::Duration;
+use cgmath::{Deg, Rad};
+use winit::event::{ElementState, VirtualKeyCode};
+use crate::scene::camera::Camera;
+use crate::scene::projection::Projection;
+
+// Holds the camera controls that only exist while taking photos. The field of view the game was
+// using is kept so it can be restored on exit.
+pub struct PhotoMode {
+    fovy: Rad<f32>,
+
+    rolling_left: bool,
+    rolling_right: bool,
+    zooming_in: bool,
+    zooming_out: bool,
+}
+
+impl PhotoMode {
+
+    pub const ROLL_SPEED: f32 = 1.0;
+    pub const ZOOM_SPEED: f32 = 0.75;
+    pub const MIN_FOVY: Deg<f32> = Deg(10.0);
+    pub const MAX_FOVY: Deg<f32> = Deg(120.0);
+    pub const CAPTURE_SCALE: u32 = 3;
+
+    pub fn new(projection: &Projection) -> Self {
+        Self {
+            fovy: projection.fovy,
+            rolling_left: false,
+            rolling_right: false,
+            zooming_in: false,
+            zooming_out: false,
+        }
+    }
+
+    pub fn process_key_input(&mut self, key: VirtualKeyCode, state: ElementState) -> bool {
+        let pressed = state == ElementState::Pressed;
+
+        match key {
+            VirtualKeyCode::Q => self.rolling_left = pressed,
+            VirtualKeyCode::E => self.rolling_right = pressed,
+            VirtualKeyCode::Z => self.zooming_in = pressed,
+            VirtualKeyCode::X => self.zooming_out = pressed,
+            _ => return false,
+        }
+
+        true
+    }
+
+    pub fn update(&self, dt: Duration, camera: &mut Camera, projection: &mut Projection) {
+        let dt = dt.as_secs_f32();
+
+        let roll = if self.rolling_right { Self::ROLL_SPEED } else { 0.0 }
+            - if self.rolling_left { Self::ROLL_SPEED } else { 0.0 };
+
+        let zoom = if self.zooming_out { Self::ZOOM_SPEED } else { 0.0 }
+            - if self.zooming_in { Self::ZOOM_SPEED } else { 0.0 };
+
+        camera.add_roll(Rad(roll * dt));
+
+        let fovy = projection.fovy + Rad(zoom * dt);
+        projection.fovy = Rad(fovy.0.clamp(Rad::from(Self::MIN_FOVY).0, Rad::from(Self::MAX_FOVY).0));
+    }
+
+    pub fn exit(self, projection: &mut Projection) {
+        projection.fovy = self.fovy;
+    }
+
+}
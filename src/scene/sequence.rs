@@ -0,0 +1,163 @@
+use std::path::Path;
+use std::time::Duration;
+use anyhow::*;
+use cgmath::{Deg, Rad, vec3, VectorSpace};
+use serde::Deserialize;
+use crate::objects::block::Block;
+use crate::scene::camera::Camera;
+use crate::world::world_thread::WorldThread;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SequenceEvent {
+    Camera {
+        time: f32,
+        position: [f32; 3],
+        yaw: f32,
+        pitch: f32,
+    },
+    Text {
+        time: f32,
+        duration: f32,
+        text: String,
+    },
+    SetBlock {
+        time: f32,
+        position: [i32; 3],
+        block: String,
+    },
+}
+
+impl SequenceEvent {
+
+    pub fn time(&self) -> f32 {
+        match self {
+            SequenceEvent::Camera { time, .. } => *time,
+            SequenceEvent::Text { time, .. } => *time,
+            SequenceEvent::SetBlock { time, .. } => *time,
+        }
+    }
+
+    pub fn end_time(&self) -> f32 {
+        match self {
+            SequenceEvent::Text { time, duration, .. } => time + duration,
+            event => event.time(),
+        }
+    }
+
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Sequence {
+    pub events: Vec<SequenceEvent>,
+}
+
+impl Sequence {
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = std::fs::read_to_string(path.as_ref())
+            .with_context(|| format!("failed to read sequence {:?}", path.as_ref()))?;
+
+        let mut sequence: Sequence = serde_json::from_str(&data)
+            .with_context(|| format!("failed to parse sequence {:?}", path.as_ref()))?;
+
+        sequence.events.sort_by(|a, b| a.time().total_cmp(&b.time()));
+
+        for event in sequence.events.iter() {
+            if let SequenceEvent::SetBlock { block, .. } = event {
+                if Block::by_name(block).is_none() {
+                    bail!("unknown block {:?} in sequence", block);
+                }
+            }
+        }
+
+        Ok(sequence)
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.events
+            .iter()
+            .map(|e| e.end_time())
+            .fold(0.0, f32::max)
+    }
+
+}
+
+pub struct SequencePlayer {
+    sequence: Sequence,
+    time: f32,
+    next_edit: usize,
+}
+
+impl SequencePlayer {
+
+    pub fn new(sequence: Sequence) -> Self {
+        Self {
+            sequence,
+            time: 0.0,
+            next_edit: 0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.time > self.sequence.duration()
+    }
+
+    pub fn update(&mut self, dt: Duration, camera: &mut Camera, world: &mut WorldThread) {
+        self.time += dt.as_secs_f32();
+
+        self.update_camera(camera);
+
+        let events = &self.sequence.events;
+
+        while self.next_edit < events.len() && events[self.next_edit].time() <= self.time {
+            if let SequenceEvent::SetBlock { position, block, .. } = &events[self.next_edit] {
+                let [x, y, z] = *position;
+                world.set_block(x, y, z, &Block::by_name(block).unwrap());
+            }
+
+            self.next_edit += 1;
+        }
+    }
+
+    // Keyframes are eased between, and the last one is held until the sequence ends.
+    fn update_camera(&self, camera: &mut Camera) {
+        let keyframes: Vec<(f32, [f32; 3], f32, f32)> = self.sequence.events
+            .iter()
+            .filter_map(|e| match e {
+                SequenceEvent::Camera { time, position, yaw, pitch } => Some((*time, *position, *yaw, *pitch)),
+                _ => None,
+            })
+            .collect();
+
+        let next = keyframes.iter().position(|k| k.0 > self.time);
+
+        let (from, to, t) = match next {
+            None if keyframes.is_empty() => return,
+            None => (keyframes[keyframes.len() - 1], keyframes[keyframes.len() - 1], 0.0),
+            Some(0) => (keyframes[0], keyframes[0], 0.0),
+            Some(i) => {
+                let (a, b) = (keyframes[i - 1], keyframes[i]);
+                let t = (self.time - a.0) / (b.0 - a.0);
+                (a, b, t * t * (3.0 - 2.0 * t))
+            },
+        };
+
+        camera.set_position(vec3(from.1[0], from.1[1], from.1[2]).lerp(vec3(to.1[0], to.1[1], to.1[2]), t));
+        camera.set_yaw(Rad::from(Deg(from.2 + (to.2 - from.2) * t)));
+        camera.set_pitch(Rad::from(Deg(from.3 + (to.3 - from.3) * t)));
+    }
+
+    pub fn active_text(&self) -> Vec<&str> {
+        self.sequence.events
+            .iter()
+            .filter_map(|e| match e {
+                SequenceEvent::Text { time, duration, text } if *time <= self.time && self.time < time + duration => {
+                    Some(text.as_str())
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+use crate::scene::projection::Projection;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpeedLinesUniform {
+    intensity: f32,
+    time: f32,
+    aspect: f32,
+    _padding: f32,
+}
+
+impl Default for SpeedLinesUniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpeedLinesUniform {
+
+    // Lines start showing above walking pace and are at full strength when sprinting.
+    pub const MIN_SPEED: f32 = 4.0;
+    pub const MAX_SPEED: f32 = 12.0;
+    pub const FADE_SPEED: f32 = 4.0;
+
+    pub fn new() -> Self {
+        Self {
+            intensity: 0.0,
+            time: 0.0,
+            aspect: 1.0,
+            _padding: 0.0,
+        }
+    }
+
+    // Eases towards the strength for `speed` rather than jumping, which also hides the one-frame
+    // spikes from the camera being teleported.
+    pub fn update(&mut self, dt: Duration, speed: f32, projection: &Projection) {
+        let dt = dt.as_secs_f32();
+        let target = ((speed - Self::MIN_SPEED) / (Self::MAX_SPEED - Self::MIN_SPEED)).clamp(0.0, 1.0);
+        let step = Self::FADE_SPEED * dt;
+
+        self.intensity += (target - self.intensity).clamp(-step, step);
+        self.time = (self.time + dt) % 3600.0;
+        self.aspect = projection.aspect;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.intensity > 0.0
+    }
+
+}
@@ -0,0 +1,36 @@
+use std::time::Duration;
+use crate::scene::projection::Projection;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct WaterUniform {
+    time: f32,
+    znear: f32,
+    zfar: f32,
+    _padding: f32,
+}
+
+impl Default for WaterUniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WaterUniform {
+
+    pub fn new() -> Self {
+        Self {
+            time: 0.0,
+            znear: 0.0,
+            zfar: 0.0,
+            _padding: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, dt: Duration, projection: &Projection) {
+        self.time = (self.time + dt.as_secs_f32()) % 3600.0;
+        self.znear = projection.znear;
+        self.zfar = projection.zfar;
+    }
+
+}
@@ -0,0 +1,72 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI, TAU};
+use cgmath::{MetricSpace, Rad, Vector3};
+
+// Heading bar along the top of the screen pointing at spawn, or at a waypoint once one is set.
+// Headings are measured clockwise from north (-Z), the same way the camera's yaw is, so where a
+// heading sits on the bar is just its difference from the yaw.
+pub struct Compass {
+    spawn: Vector3<f32>,
+    waypoint: Option<Vector3<f32>>,
+}
+
+impl Compass {
+
+    // Half of the bar's field of view.
+    pub const SPAN: f32 = FRAC_PI_2;
+
+    pub const MARKS: [(f32, &'static str); 8] = [
+        (0.0, "N"),
+        (FRAC_PI_4, "NE"),
+        (FRAC_PI_2, "E"),
+        (FRAC_PI_2 + FRAC_PI_4, "SE"),
+        (PI, "S"),
+        (-FRAC_PI_2 - FRAC_PI_4, "SW"),
+        (-FRAC_PI_2, "W"),
+        (-FRAC_PI_4, "NW"),
+    ];
+
+    pub fn new(spawn: Vector3<f32>) -> Self {
+        Self {
+            spawn,
+            waypoint: None,
+        }
+    }
+
+    // Sets a waypoint at `position`, or clears the current one so the compass points home again.
+    pub fn toggle_waypoint(&mut self, position: Vector3<f32>) {
+        self.waypoint = if self.waypoint.is_some() { None } else { Some(position) };
+    }
+
+    pub fn target(&self) -> (&'static str, Vector3<f32>) {
+        match self.waypoint {
+            Some(waypoint) => ("Waypoint", waypoint),
+            None => ("Spawn", self.spawn),
+        }
+    }
+
+    pub fn heading(from: Vector3<f32>, to: Vector3<f32>) -> f32 {
+        let d = to - from;
+        d.x.atan2(-d.z)
+    }
+
+    // Where `heading` falls across the bar, from -1 at the left edge to 1 at the right, seen
+    // facing `yaw`. Headings behind the bar are pinned to the nearer edge.
+    pub fn offset(heading: f32, yaw: Rad<f32>) -> f32 {
+        let relative = (heading - yaw.0 + PI).rem_euclid(TAU) - PI;
+        (relative / Self::SPAN).clamp(-1.0, 1.0)
+    }
+
+    pub fn marks(yaw: Rad<f32>) -> Vec<(f32, &'static str)> {
+        Self::MARKS
+            .iter()
+            .map(|(heading, label)| (Self::offset(*heading, yaw), *label))
+            .filter(|(offset, _)| offset.abs() < 1.0)
+            .collect()
+    }
+
+    // Horizontal distance, since height matters little when heading somewhere.
+    pub fn distance(from: Vector3<f32>, to: Vector3<f32>) -> f32 {
+        Vector3::new(from.x, 0.0, from.z).distance(Vector3::new(to.x, 0.0, to.z))
+    }
+
+}
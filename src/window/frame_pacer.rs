@@ -0,0 +1,75 @@
+use instant::{Duration, Instant};
+use winit::monitor::MonitorHandle;
+use winit::window::Window;
+
+// Without vsync nothing stops frames from being rendered faster than the monitor can show them,
+// so they're paced to its refresh rate instead. The monitor is looked up again whenever the
+// window moves, since it may have moved to one with a different rate.
+pub struct FramePacer {
+    enabled: bool,
+    monitor: Option<MonitorHandle>,
+    interval: Duration,
+    next: Instant,
+}
+
+impl FramePacer {
+
+    // Used when the platform doesn't report a refresh rate.
+    pub const FALLBACK_MILLIHERTZ: u32 = 60_000;
+
+    pub fn new(window: &Window, enabled: bool) -> Self {
+        let mut pacer = Self {
+            enabled,
+            monitor: None,
+            interval: Duration::ZERO,
+            next: Instant::now(),
+        };
+
+        if enabled {
+            pacer.set_monitor(window.current_monitor());
+        }
+
+        pacer
+    }
+
+    pub fn refresh(&mut self, window: &Window) {
+        let monitor = window.current_monitor();
+
+        if self.enabled && monitor != self.monitor {
+            self.set_monitor(monitor);
+        }
+    }
+
+    // None when a frame is due now, otherwise when the next one is.
+    pub fn next_frame(&mut self, now: Instant) -> Option<Instant> {
+        if !self.enabled {
+            return None;
+        }
+
+        if now < self.next {
+            return Some(self.next);
+        }
+
+        // A late frame moves the schedule rather than rushing the ones after it.
+        self.next += self.interval;
+
+        if self.next < now {
+            self.next = now + self.interval;
+        }
+
+        None
+    }
+
+    fn set_monitor(&mut self, monitor: Option<MonitorHandle>) {
+        // Some virtual and remote displays report a rate of 0.
+        let millihertz = monitor
+            .as_ref()
+            .and_then(|m| m.refresh_rate_millihertz())
+            .filter(|&m| m > 0)
+            .unwrap_or(Self::FALLBACK_MILLIHERTZ);
+
+        self.monitor = monitor;
+        self.interval = Duration::from_secs_f64(1000.0 / millihertz as f64);
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,117 @@
+use std::path::Path;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::world::lod::LodDistances;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphicsPreset {
+    Low,
+    Medium,
+    High,
+    Ultra,
+}
+
+impl GraphicsPreset {
+
+    pub const PATH: &'static str = "graphics.json";
+    // What the game ran with before presets existed, and what the benchmark measures.
+    pub const DEFAULT: GraphicsPreset = GraphicsPreset::High;
+
+    pub fn parse(name: &str) -> Option<GraphicsPreset> {
+        match name {
+            "low" => Some(GraphicsPreset::Low),
+            "medium" => Some(GraphicsPreset::Medium),
+            "high" => Some(GraphicsPreset::High),
+            "ultra" => Some(GraphicsPreset::Ultra),
+            _ => None,
+        }
+    }
+
+    pub fn render_distance(&self) -> i32 {
+        match self {
+            GraphicsPreset::Low => 6,
+            GraphicsPreset::Medium => 8,
+            GraphicsPreset::High => 12,
+            GraphicsPreset::Ultra => 16,
+        }
+    }
+
+    pub fn lod_distances(&self) -> LodDistances {
+        match self {
+            GraphicsPreset::Low => LodDistances { half: 2, quarter: 4 },
+            GraphicsPreset::Medium => LodDistances { half: 4, quarter: 6 },
+            GraphicsPreset::High => LodDistances::DEFAULT,
+            GraphicsPreset::Ultra => LodDistances { half: 12, quarter: 16 },
+        }
+    }
+
+    pub fn speed_lines(&self) -> bool {
+        *self != GraphicsPreset::Low
+    }
+
+    // None on first launch, which is when the benchmark picks one.
+    pub fn load<P: AsRef<Path>>(path: P) -> Option<GraphicsPreset> {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| std::fs::write(path, data).map_err(anyhow::Error::from));
+
+        if let Err(e) = result {
+            eprintln!("{:?}", e);
+        }
+    }
+
+}
+
+// Times frames of the loaded world at the default preset and picks the preset whose cost the
+// machine has room for. Frames are timed up to the GPU finishing them rather than between
+// presents, since vsync would otherwise hold every machine to the monitor's refresh rate.
+pub struct GraphicsBenchmark {
+    frames: Vec<Duration>,
+}
+
+impl Default for GraphicsBenchmark {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphicsBenchmark {
+
+    pub const FRAMES: usize = 120;
+
+    pub fn new() -> Self {
+        Self {
+            frames: Vec::with_capacity(Self::FRAMES),
+        }
+    }
+
+    // Returns the picked preset once enough frames have been timed.
+    pub fn record(&mut self, frame: Duration) -> Option<GraphicsPreset> {
+        self.frames.push(frame);
+
+        if self.frames.len() < Self::FRAMES {
+            return None;
+        }
+
+        self.frames.sort();
+        let median = self.frames[self.frames.len() / 2].as_secs_f32() * 1000.0;
+
+        Some(if median < 4.0 {
+            GraphicsPreset::Ultra
+        } else if median < 8.0 {
+            GraphicsPreset::High
+        } else if median < 14.0 {
+            GraphicsPreset::Medium
+        } else {
+            GraphicsPreset::Low
+        })
+    }
+
+}
\ No newline at end of file
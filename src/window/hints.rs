@@ -0,0 +1,128 @@
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::events::event_listener::EventListener;
+use crate::events::game_event::GameEvent;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hint {
+    Move,
+    BreakBlock,
+    PlaceBlock,
+    Spectator,
+    PhotoMode,
+}
+
+impl Hint {
+
+    pub fn text(&self) -> &'static str {
+        match self {
+            Hint::Move => "Use WASD to move, Space and Ctrl to fly up and down",
+            Hint::BreakBlock => "Left click to break the targeted block",
+            Hint::PlaceBlock => "Right click to place stone against the targeted face",
+            Hint::Spectator => "Spectating: the player stays behind. Press F5 to snap back",
+            Hint::PhotoMode => "Photo mode: Q/E roll, Z/X zoom, F12 capture, F2 to leave",
+        }
+    }
+
+}
+
+// First-time hints, shown one at a time. A hint counts as seen once it times out, is dismissed
+// with H, or the player does what it describes; seen hints are saved and never shown again.
+pub struct Hints {
+    path: PathBuf,
+    seen: HashSet<Hint>,
+    queue: VecDeque<Hint>,
+    elapsed: f32,
+}
+
+impl Hints {
+
+    pub const PATH: &'static str = "hints.json";
+    pub const DURATION: f32 = 8.0;
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let seen = std::fs::read_to_string(path.as_ref())
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+
+        Self {
+            path: path.as_ref().to_path_buf(),
+            seen,
+            queue: VecDeque::new(),
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn trigger(&mut self, hint: Hint) {
+        if !self.seen.contains(&hint) && !self.queue.contains(&hint) {
+            self.queue.push_back(hint);
+        }
+    }
+
+    pub fn complete(&mut self, hint: Hint) {
+        if self.seen.contains(&hint) {
+            return;
+        }
+
+        if self.queue.front() == Some(&hint) {
+            self.elapsed = 0.0;
+        }
+
+        self.queue.retain(|h| *h != hint);
+        self.mark_seen(hint);
+    }
+
+    pub fn dismiss(&mut self) {
+        if let Some(hint) = self.queue.pop_front() {
+            self.elapsed = 0.0;
+            self.mark_seen(hint);
+        }
+    }
+
+    pub fn update(&mut self, dt: std::time::Duration) {
+        if self.queue.is_empty() {
+            return;
+        }
+
+        self.elapsed += dt.as_secs_f32();
+
+        if self.elapsed >= Self::DURATION {
+            self.dismiss();
+        }
+    }
+
+    pub fn active(&self) -> Option<Hint> {
+        self.queue.front().copied()
+    }
+
+    fn mark_seen(&mut self, hint: Hint) {
+        self.seen.insert(hint);
+
+        let result = serde_json::to_string_pretty(&self.seen)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| std::fs::write(&self.path, data).map_err(anyhow::Error::from));
+
+        if let Err(e) = result {
+            eprintln!("{:?}", e);
+        }
+    }
+
+}
+
+impl EventListener for Hints {
+
+    fn handle_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::BlockBroken { .. } => {
+                self.complete(Hint::BreakBlock);
+                self.trigger(Hint::PlaceBlock);
+            },
+            GameEvent::BlockPlaced { .. } => self.complete(Hint::PlaceBlock),
+            _ => {},
+        }
+    }
+
+}
@@ -0,0 +1,64 @@
+use wgpu::SurfaceConfiguration;
+
+// Where the 3D view and the HUD quads go on the surface, recomputed from its configuration on
+// every resize. HUD quads are sized in units of the 3D view's height so they keep their shape at
+// any aspect ratio. With letterboxing on, the 3D view is also kept between MIN_ASPECT and
+// MAX_ASPECT and the rest of the surface is left black.
+#[derive(Debug, Copy, Clone)]
+pub struct HudLayout {
+    width: u32,
+    height: u32,
+    letterbox: bool,
+}
+
+impl HudLayout {
+
+    pub const MIN_ASPECT: f32 = 4.0 / 3.0;
+    pub const MAX_ASPECT: f32 = 21.0 / 9.0;
+
+    pub fn new(config: &SurfaceConfiguration, letterbox: bool) -> Self {
+        Self {
+            width: config.width,
+            height: config.height,
+            letterbox,
+        }
+    }
+
+    pub fn resize(&mut self, config: &SurfaceConfiguration) {
+        self.width = config.width;
+        self.height = config.height;
+    }
+
+    pub fn toggle_letterbox(&mut self) {
+        self.letterbox = !self.letterbox;
+    }
+
+    // The 3D view as x, y, width and height in pixels of a target of `size`, which may be larger
+    // than the surface when capturing.
+    pub fn viewport(&self, (width, height): (u32, u32)) -> (u32, u32, u32, u32) {
+        let aspect = width as f32 / height.max(1) as f32;
+
+        if !self.letterbox || (Self::MIN_ASPECT..=Self::MAX_ASPECT).contains(&aspect) {
+            return (0, 0, width, height);
+        }
+
+        if aspect > Self::MAX_ASPECT {
+            let w = (height as f32 * Self::MAX_ASPECT).round() as u32;
+            ((width - w) / 2, 0, w, height)
+        } else {
+            let h = (width as f32 / Self::MIN_ASPECT).round() as u32;
+            (0, (height - h) / 2, width, h)
+        }
+    }
+
+    pub fn aspect(&self) -> f32 {
+        let (_, _, width, height) = self.viewport((self.width, self.height));
+        width as f32 / height.max(1) as f32
+    }
+
+    // Scale from HUD units to normalized device coordinates on each axis.
+    pub fn scale(&self) -> [f32; 2] {
+        [1.0 / self.aspect(), 1.0]
+    }
+
+}
\ No newline at end of file
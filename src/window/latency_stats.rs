@@ -0,0 +1,86 @@
+use std::collections::VecDeque;
+use crossbeam::channel::{Receiver, Sender};
+use instant::{Duration, Instant};
+
+// Estimates how long input takes to reach the screen, from the oldest input not yet drawn to the
+// present of the frame that first saw it. wgpu doesn't report when a frame is actually shown, so
+// the GPU side is when the queue reported the frame's work done, which is only noticed on the
+// next poll and so overestimates by up to a frame.
+pub struct LatencyStats {
+    pending: Option<Instant>,
+    presented: VecDeque<Duration>,
+    completed: VecDeque<Duration>,
+    sender: Sender<Duration>,
+    receiver: Receiver<Duration>,
+}
+
+impl Default for LatencyStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyStats {
+
+    pub const SAMPLES: usize = 120;
+
+    pub fn new() -> Self {
+        let (sender, receiver) = crossbeam::channel::unbounded();
+
+        Self {
+            pending: None,
+            presented: VecDeque::new(),
+            completed: VecDeque::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn input(&mut self) {
+        self.pending.get_or_insert_with(Instant::now);
+    }
+
+    // Right after presenting a frame whose work was just submitted to `queue`.
+    pub fn presented(&mut self, queue: &wgpu::Queue) {
+        if let Some(input) = self.pending.take() {
+            Self::push(&mut self.presented, input.elapsed());
+
+            let sender = self.sender.clone();
+            queue.on_submitted_work_done(move || {
+                let _ = sender.send(input.elapsed());
+            });
+        }
+
+        while let Ok(latency) = self.receiver.try_recv() {
+            Self::push(&mut self.completed, latency);
+        }
+    }
+
+    // Mean and 95th percentile of input to present, then of input to the GPU finishing.
+    pub fn summary(&self) -> Option<[(Duration, Duration); 2]> {
+        Some([Self::stats(&self.presented)?, Self::stats(&self.completed)?])
+    }
+
+    fn push(samples: &mut VecDeque<Duration>, latency: Duration) {
+        if samples.len() == Self::SAMPLES {
+            samples.pop_front();
+        }
+
+        samples.push_back(latency);
+    }
+
+    fn stats(samples: &VecDeque<Duration>) -> Option<(Duration, Duration)> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort();
+
+        let mean = sorted.iter().sum::<Duration>() / sorted.len() as u32;
+        let p95 = sorted[(sorted.len() - 1) * 95 / 100];
+
+        Some((mean, p95))
+    }
+
+}
\ No newline at end of file
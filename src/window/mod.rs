@@ -1,2 +1,10 @@
 pub mod state;
-pub mod event_loop_request;
\ No newline at end of file
+pub mod event_loop_request;
+pub mod hints;
+pub mod scoreboard;
+pub mod compass;
+pub mod graphics_preset;
+pub mod safe_mode;
+pub mod hud_layout;
+pub mod frame_pacer;
+pub mod latency_stats;
\ No newline at end of file
@@ -0,0 +1,24 @@
+use std::path::Path;
+
+// A sentinel file is written before the device, textures and pipelines are created and removed
+// once they all are. Finding it at launch means the previous launch died in between, most likely
+// on a driver that can't handle something that was asked of it, so the game starts in safe mode:
+// no optional wgpu features and the lowest graphics preset.
+pub const SENTINEL: &str = "startup.lock";
+
+// Whether the previous launch crashed during startup. Writes the sentinel for this one.
+pub fn begin_startup() -> bool {
+    let crashed = Path::new(SENTINEL).exists();
+
+    if let Err(e) = std::fs::write(SENTINEL, []) {
+        eprintln!("{:?}", e);
+    }
+
+    crashed
+}
+
+pub fn finish_startup() {
+    if let Err(e) = std::fs::remove_file(SENTINEL) {
+        eprintln!("{:?}", e);
+    }
+}
\ No newline at end of file
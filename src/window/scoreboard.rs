@@ -0,0 +1,67 @@
+use crate::events::event_listener::EventListener;
+use crate::events::game_event::GameEvent;
+
+// Named values shown in a sidebar, e.g. blocks mined. Anything holding the state can set or
+// bump an objective; a board without objectives is not drawn.
+pub struct Scoreboard {
+    title: String,
+    objectives: Vec<(String, i64)>,
+}
+
+impl Scoreboard {
+
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            objectives: vec![],
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn set(&mut self, name: &str, value: i64) {
+        match self.objectives.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => self.objectives.push((name.to_string(), value)),
+        }
+    }
+
+    pub fn add(&mut self, name: &str, delta: i64) {
+        let value = self.get(name).unwrap_or(0);
+        self.set(name, value + delta);
+    }
+
+    pub fn get(&self, name: &str) -> Option<i64> {
+        self.objectives.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.objectives.retain(|(n, _)| n != name);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objectives.is_empty()
+    }
+
+    // Highest value first; ties keep the order objectives were added in.
+    pub fn objectives(&self) -> Vec<(&str, i64)> {
+        let mut objectives = self.objectives.iter().map(|(n, v)| (n.as_str(), *v)).collect::<Vec<_>>();
+        objectives.sort_by_key(|o| std::cmp::Reverse(o.1));
+        objectives
+    }
+
+}
+
+impl EventListener for Scoreboard {
+
+    fn handle_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::BlockBroken { .. } => self.add("Blocks mined", 1),
+            GameEvent::BlockPlaced { .. } => self.add("Blocks placed", 1),
+            _ => {},
+        }
+    }
+
+}
@@ -1,38 +1,73 @@
-use std::time::Duration;
-use cgmath::{Deg, vec3};
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use cgmath::{Deg, InnerSpace, Rad, vec3, Vector3, Zero};
 use indoc::indoc;
 use wgpu::util::StagingBelt;
-use wgpu_glyph::{GlyphBrush, GlyphBrushBuilder, Section, Text};
+use wgpu_glyph::{GlyphBrush, GlyphBrushBuilder, HorizontalAlign, Layout, Section, Text, VerticalAlign};
 use winit::dpi::PhysicalPosition;
-use winit::event::{ElementState, KeyboardInput, MouseButton, WindowEvent};
+use winit::event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode};
 use winit::window::Window;
 use crate::scene::camera::Camera;
 use crate::scene::camera_uniform::CameraUniform;
 use crate::scene::projection::Projection;
 use crate::engine::block_pipeline;
 use crate::engine::block_pipeline::BlockPipeline;
+use crate::engine::debug_view::DebugView;
 use crate::engine::block_target_pipeline::{BlockTargetPipeline};
 use crate::engine::hotbar_pipeline::{DrawBlock, HotbarPipeline};
 use crate::engine::texture::Texture;
+use crate::engine::texture_info::TextureInfo;
+use crate::engine::water_pipeline::WaterPipeline;
+use crate::engine::speed_lines_pipeline::SpeedLinesPipeline;
+use crate::engine::beacon_pipeline::BeaconPipeline;
+use crate::objects::block::Block;
 use crate::objects::block_face::BlockFace;
 use crate::objects::target::Target;
 use crate::objects::target_uniform::TargetUniform;
 use crate::scene::frustum::Frustum;
+use crate::scene::golden_scene::GoldenScene;
+use crate::scene::movement_config::MovementConfig;
+use crate::scene::photo_mode::PhotoMode;
+use crate::scene::sequence::{Sequence, SequencePlayer};
+use crate::scene::water_uniform::WaterUniform;
+use crate::scene::speed_lines_uniform::SpeedLinesUniform;
+use crate::scene::beacon_uniform::BeaconUniform;
+use crate::window::hints::{Hint, Hints};
+use crate::window::scoreboard::Scoreboard;
+use crate::window::compass::Compass;
+use crate::events::event_bus::EventBus;
+use crate::events::event_listener::EventListener;
+use crate::window::hud_layout::HudLayout;
+use crate::window::latency_stats::LatencyStats;
+use crate::window::graphics_preset::GraphicsPreset;
+use crate::world::chunk_draws::ChunkDraws;
+use crate::world::quad_index_buffer::QuadIndexBuffer;
 use crate::world::world::World;
+use crate::world::world_thread::WorldThread;
+use crate::world::world_stats::WorldStats;
+use crate::world::worldgen_config::{WorldgenConfig, WorldgenWatcher};
 
 pub struct State {
     surface: wgpu::Surface,
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
+    present_modes: Vec<wgpu::PresentMode>,
     size: winit::dpi::PhysicalSize<u32>,
+    hud_layout: HudLayout,
 
     staging_belt: StagingBelt,
     glyph_brush: GlyphBrush<()>,
 
     depth_texture: Texture,
+    scene_texture: Texture,
+    scene_depth_texture: Texture,
+    quad_indices: QuadIndexBuffer,
+    chunk_draws: ChunkDraws,
 
     camera: Camera,
+    spectator: Option<Camera>,
     camera_uniform: CameraUniform,
     projection: Projection,
     frustum: Frustum,
@@ -40,11 +75,32 @@ pub struct State {
     target_uniform: TargetUniform,
     target: Option<Target>,
 
-    world: World,
+    water_uniform: WaterUniform,
+    speed_lines_uniform: SpeedLinesUniform,
+    beacon_uniform: BeaconUniform,
+    last_position: Vector3<f32>,
+
+    sequence: Option<SequencePlayer>,
+    photo_mode: Option<PhotoMode>,
+    texture_info: bool,
+    graphics: GraphicsPreset,
+    safe_mode: bool,
+    latency: LatencyStats,
+
+    world: WorldThread,
+    worldgen_watcher: Option<WorldgenWatcher>,
+
+    hints: Hints,
+    scoreboard: Scoreboard,
+    compass: Compass,
+    // Lines from the frontend embedding the engine, see `EngineCallbacks::overlay`.
+    overlay: Vec<String>,
+
+    events: EventBus,
 }
 
 impl State {
-    pub async fn new(window: &Window) -> Self {
+    pub async fn new(window: &Window, world: Option<WorldgenConfig>, sequence: Option<Sequence>, safe_mode: bool) -> Self {
         let size = window.inner_size();
 
         let instance = wgpu::Instance::new(wgpu::Backends::all());
@@ -56,8 +112,22 @@ impl State {
             force_fallback_adapter: false,
         }).await.unwrap();
 
+        // None of these are required: pipelines check the device's features and fall back when one
+        // is missing, so only what the adapter supports is requested.
+        let optional = wgpu::Features::POLYGON_MODE_LINE | wgpu::Features::DEPTH_CLIP_CONTROL | wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER | ChunkDraws::FEATURES;
+
+        let features = if safe_mode {
+            wgpu::Features::empty()
+        } else {
+            adapter.features() & optional
+        };
+
+        if !features.contains(optional) {
+            println!("Running without {:?}", optional - features);
+        }
+
         let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
-            features: wgpu::Features::default() | wgpu::Features::POLYGON_MODE_LINE | wgpu::Features::DEPTH_CLIP_CONTROL | wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER,
+            features,
             limits: wgpu::Limits::default(),
             label: None,
         }, None).await.unwrap();
@@ -71,22 +141,53 @@ impl State {
             alpha_mode: wgpu::CompositeAlphaMode::Auto,
         };
 
-        let camera = Camera::new(vec3(0.0, 70.0, 0.0), Deg(0.0), Deg(0.0));
+        let present_modes = surface.get_supported_present_modes(&adapter);
+
+        let mut camera = Camera::new(vec3(0.0, 70.0, 0.0), Deg(0.0), Deg(0.0));
         let camera_uniform = CameraUniform::new();
 
+        let hud_layout = HudLayout::new(&config, false);
         let (width, height) = (config.width, config.height);
 
         let projection = Projection::new(width, height, Deg(90.0), 0.1, 1000.0);
 
         let depth_texture = Texture::create_depth_texture(&device, &config, "depth_texture");
-        
+        let scene_texture = Texture::create_scene_texture(&device, &config, "scene_texture");
+        let scene_depth_texture = Texture::create_depth_texture(&device, &config, "scene_depth_texture");
+        let quad_indices = QuadIndexBuffer::new(&device);
+        let chunk_draws = ChunkDraws::new(&device);
+
+        let mut water_uniform = WaterUniform::new();
+        water_uniform.update(Duration::ZERO, &projection);
+
         let target_uniform = TargetUniform::new();
 
-        let mut world = World::new(12);
+        // A config given up front is kept; otherwise the file is read and watched for changes.
+        let worldgen_watcher = world.is_none().then(|| WorldgenWatcher::new(WorldgenConfig::PATH));
+
+        let worldgen = world.unwrap_or_else(|| worldgen_watcher.as_ref().unwrap().config());
+        let events = EventBus::new();
+
+        let render_distance = GraphicsPreset::DEFAULT.render_distance();
+        let mut world = World::new(render_distance, worldgen.world_type.generator(&worldgen), events.sender());
         world.generate(&camera);
 
+        // Spawn just above the terrain rather than at a fixed height that may be inside a hill.
+        if let Some(height) = world.surface_height(0, 0) {
+            camera.set_position(vec3(0.0, (height + World::GROUND_DISTANCE - 1) as f32, 0.0));
+        }
+
+        camera.set_movement(MovementConfig::load_or_default(MovementConfig::PATH));
+
+        let world = WorldThread::spawn(world, render_distance);
+
+        let last_position = *camera.position();
+
         let frustum = Frustum::new(&camera, &projection);
 
+        let mut hints = Hints::load(Hints::PATH);
+        hints.trigger(Hint::Move);
+
         let staging_belt = StagingBelt::new(1024);
 
         let font = wgpu_glyph::ab_glyph::FontArc::try_from_slice(include_bytes!("../../assets/fonts/YatraOne-Regular.ttf"))
@@ -100,25 +201,46 @@ impl State {
             device,
             queue,
             config,
+            present_modes,
             size,
+            hud_layout,
             staging_belt,
             glyph_brush,
             depth_texture,
+            scene_texture,
+            scene_depth_texture,
+            quad_indices,
+            chunk_draws,
             camera,
+            spectator: None,
             camera_uniform,
             frustum,
             target: None,
             target_uniform,
+            water_uniform,
+            speed_lines_uniform: SpeedLinesUniform::new(),
+            beacon_uniform: BeaconUniform::new(),
+            last_position,
+            sequence: sequence.map(SequencePlayer::new),
+            photo_mode: None,
+            texture_info: false,
+            graphics: GraphicsPreset::DEFAULT,
+            safe_mode,
+            latency: LatencyStats::new(),
             projection,
             world,
+            worldgen_watcher,
+            hints,
+            scoreboard: Scoreboard::new("Objectives"),
+            compass: Compass::new(last_position),
+            overlay: vec![],
+            events,
         }
     }
 
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         let (nw, nh) = (new_size.width, new_size.height);
 
-        self.projection.resize(nw, nh);
-
         if nw > 0 && nh > 0 {
             self.size = new_size;
             self.config.width = nw;
@@ -126,56 +248,233 @@ impl State {
             self.surface.configure(&self.device, &self.config);
         }
 
+        self.hud_layout.resize(&self.config);
+        self.resize_projection();
+
         self.depth_texture = Texture::create_depth_texture(&self.device, &self.config, "depth_texture");
+        self.scene_texture = Texture::create_scene_texture(&self.device, &self.config, "scene_texture");
+        self.scene_depth_texture = Texture::create_depth_texture(&self.device, &self.config, "scene_depth_texture");
     }
 
     pub fn handle_keyboard_input(&mut self, input: &KeyboardInput) {
+        self.latency.input();
+
+        if self.sequence.is_some() { return; }
+
         if let Some(code) = input.virtual_keycode {
-            self.camera.process_key_input(code, input.state);
+            if input.state == ElementState::Pressed {
+                match code {
+                    VirtualKeyCode::H => self.hints.dismiss(),
+                    VirtualKeyCode::V => self.compass.toggle_waypoint(*self.camera.position()),
+                    VirtualKeyCode::Minus => self.change_render_distance(-1),
+                    VirtualKeyCode::Equals => self.change_render_distance(1),
+                    VirtualKeyCode::W | VirtualKeyCode::A | VirtualKeyCode::S | VirtualKeyCode::D => self.hints.complete(Hint::Move),
+                    _ => {},
+                }
+            }
+
+            if let Some(photo_mode) = &mut self.photo_mode {
+                if photo_mode.process_key_input(code, input.state) { return; }
+            }
+
+            self.active_camera_mut().process_key_input(code, input.state);
         }
     }
 
+    // Overrides the graphics preset's render distance until another preset is picked. The world
+    // streams in or evicts chunks for it on its next update.
+    fn change_render_distance(&mut self, delta: i32) {
+        let render_distance = (self.world.render_distance() + delta)
+            .clamp(World::MIN_RENDER_DISTANCE, World::MAX_RENDER_DISTANCE);
+
+        self.world.set_render_distance(render_distance);
+    }
+
     pub fn handle_mouse_input(&mut self, button: &MouseButton, state: &ElementState) {
+        self.latency.input();
+
+        if self.sequence.is_some() || self.spectator.is_some() || self.photo_mode.is_some() || *state != ElementState::Pressed {
+            return;
+        }
+
         match *button {
-            MouseButton::Right => if *state == ElementState::Pressed {
-                self.world.place_block(self.target.as_ref());
-            },
-            MouseButton::Left => if *state == ElementState::Pressed {
-                self.world.remove_block(self.target.as_ref());
-            },
+            MouseButton::Right => self.world.place_block(self.target.as_ref(), &Block::Stone),
+            MouseButton::Middle => self.world.place_block(self.target.as_ref(), &Block::Beacon),
+            MouseButton::Left => self.world.remove_block(self.target.as_ref()),
             _ => {},
         }
     }
 
     pub fn handle_cursor_move(&mut self, position: &(f64, f64)) {
-        self.camera.process_mouse_motion(position.0, position.1);
+        self.latency.input();
+
+        if self.sequence.is_some() { return; }
+
+        self.active_camera_mut().process_mouse_motion(position.0, position.1);
+    }
+
+    // The spectator camera flies freely while the player camera stays where it was, so chunk
+    // streaming and block targeting keep following the player until the view snaps back.
+    pub fn toggle_spectator(&mut self) {
+        if self.spectator.take().is_none() {
+            let mut spectator = Camera::new(*self.camera.position(), *self.camera.yaw(), *self.camera.pitch());
+            spectator.set_movement(*self.camera.movement());
+            spectator.update(Duration::ZERO);
+
+            self.camera.stop();
+            self.spectator = Some(spectator);
+            self.hints.trigger(Hint::Spectator);
+        } else {
+            self.hints.complete(Hint::Spectator);
+        }
+    }
+
+    pub fn save(&mut self) {
+        self.world.save();
+    }
+
+    pub fn toggle_texture_info(&mut self) {
+        self.texture_info = !self.texture_info;
     }
 
+    // Without vsync frames are presented immediately, or replace the queued one where tearing
+    // isn't supported. Returns whether vsync ended up on.
+    pub fn set_vsync(&mut self, vsync: bool) -> bool {
+        let mode = [wgpu::PresentMode::Immediate, wgpu::PresentMode::Mailbox]
+            .into_iter()
+            .find(|m| !vsync && self.present_modes.contains(m))
+            .unwrap_or(wgpu::PresentMode::Fifo);
+
+        self.config.present_mode = mode;
+        self.surface.configure(&self.device, &self.config);
+
+        mode == wgpu::PresentMode::Fifo
+    }
+
+    pub fn toggle_letterbox(&mut self) {
+        self.hud_layout.toggle_letterbox();
+        self.resize_projection();
+    }
+
+    // The projection only covers the 3D view, which is smaller than the surface when letterboxed.
+    fn resize_projection(&mut self) {
+        let (_, _, width, height) = self.hud_layout.viewport((self.config.width, self.config.height));
+        self.projection.resize(width, height);
+    }
+
+    // Photo mode freezes the sequence and water animation, hides the HUD and unlocks roll and
+    // field of view on whichever camera is active. Both are restored on exit.
+    pub fn toggle_photo_mode(&mut self) {
+        if let Some(photo_mode) = self.photo_mode.take() {
+            photo_mode.exit(&mut self.projection);
+
+            self.camera.set_roll(Rad::zero());
+
+            if let Some(spectator) = &mut self.spectator {
+                spectator.set_roll(Rad::zero());
+            }
+
+            self.hints.complete(Hint::PhotoMode);
+        } else {
+            self.photo_mode = Some(PhotoMode::new(&self.projection));
+            self.hints.trigger(Hint::PhotoMode);
+        }
+    }
+
+    pub fn is_photo_mode(&self) -> bool {
+        self.photo_mode.is_some()
+    }
+
+    fn active_camera_mut(&mut self) -> &mut Camera {
+        self.spectator.as_mut().unwrap_or(&mut self.camera)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &mut self,
         dt: Duration,
         pipeline: &mut BlockPipeline,
         target_pipeline: &mut BlockTargetPipeline,
         hotbar_pipeline: &mut HotbarPipeline,
+        water_pipeline: &mut WaterPipeline,
+        speed_lines_pipeline: &mut SpeedLinesPipeline,
+        beacon_pipeline: &mut BeaconPipeline,
     ) {
-        self.world.update(&self.device, &self.camera);
+        let frozen = self.photo_mode.is_some();
+
+        // A running sequence owns the camera; player input is ignored until it ends.
+        if let Some(sequence) = self.sequence.as_mut().filter(|_| !frozen) {
+            sequence.update(dt, &mut self.camera, &mut self.world);
+
+            if sequence.is_finished() {
+                self.sequence = None;
+            }
+        }
+
+        if let Some(config) = self.worldgen_watcher.as_mut().and_then(|w| w.poll()) {
+            self.world.set_generator(config.world_type.generator(&config));
+        }
+
+        self.world.update(&self.device, &self.queue, &self.camera);
 
-        self.target = self.world.get_target(&self.camera);
+        self.target = if self.spectator.is_none() && !frozen {
+            self.world.target()
+        } else {
+            None
+        };
         self.target_uniform.update(self.target.as_ref());
 
+        if self.target.is_some() {
+            self.hints.trigger(Hint::BreakBlock);
+        }
+
+        for event in self.events.drain() {
+            self.hints.handle_event(&event);
+            self.scoreboard.handle_event(&event);
+        }
+
+        self.hints.update(dt);
+
+        self.camera.set_grounded(self.world.is_grounded());
         self.camera.update(dt);
-        self.camera_uniform.update(&self.camera, &self.projection);
-        self.frustum.update(&self.camera, &self.projection);
+
+        if let Some(spectator) = &mut self.spectator {
+            spectator.update(dt);
+        }
+
+        if let Some(photo_mode) = &self.photo_mode {
+            let camera = self.spectator.as_mut().unwrap_or(&mut self.camera);
+            photo_mode.update(dt, camera, &mut self.projection);
+        }
+
+        let camera = self.spectator.as_ref().unwrap_or(&self.camera);
+
+        let speed = if dt.is_zero() { 0.0 } else { (camera.position() - self.last_position).magnitude() / dt.as_secs_f32() };
+        self.last_position = *camera.position();
+
+        self.camera_uniform.update(camera, &self.projection);
+        self.frustum.update(camera, &self.projection);
+        self.chunk_draws.update(&self.device, &self.queue, &self.world.buffers(&self.frustum, camera.position()));
+        self.water_uniform.update(if frozen { Duration::ZERO } else { dt }, &self.projection);
+        self.speed_lines_uniform.update(dt, if frozen { 0.0 } else { speed }, &self.projection);
+        self.beacon_uniform.update(if frozen { Duration::ZERO } else { dt });
 
         pipeline.update(&self.queue, &self.camera_uniform);
         target_pipeline.update(&self.queue, &self.camera_uniform, &self.target_uniform);
+        water_pipeline.update(&self.queue, &self.camera_uniform, &self.water_uniform);
+        speed_lines_pipeline.update(&self.queue, &self.speed_lines_uniform);
+        beacon_pipeline.update(&self.device, &self.queue, &self.camera_uniform, &self.beacon_uniform, self.world.beacons());
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         &mut self,
         block_pipeline: &BlockPipeline,
         target_pipeline: &BlockTargetPipeline,
         hotbar_pipeline: &HotbarPipeline,
+        water_pipeline: &WaterPipeline,
+        speed_lines_pipeline: &SpeedLinesPipeline,
+        beacon_pipeline: &BeaconPipeline,
         fps: u32,
     ) -> Result<(), wgpu::SurfaceError> {
         let output = self.surface.get_current_texture()?;
@@ -186,11 +485,263 @@ impl State {
             label: Some("Render Encoder"),
         });
 
+        let size = (self.config.width, self.config.height);
+        let hud = self.photo_mode.is_none();
+
+        self.encode_frame(&mut encoder, &view, size, block_pipeline, target_pipeline, hotbar_pipeline, water_pipeline, speed_lines_pipeline, beacon_pipeline, hud);
+
+        if hud {
+            self.queue_hud(size, block_pipeline, fps);
+        }
+
+        if hud && self.texture_info {
+            let textures = [
+                block_pipeline.diffuse_texture(),
+                water_pipeline.diffuse_texture(),
+                hotbar_pipeline.diffuse_texture(),
+                &self.scene_texture,
+                &self.scene_depth_texture,
+                &self.depth_texture,
+            ].map(|texture| texture.info().clone());
+
+            self.queue_texture_info(size, &textures);
+        }
+
+        // Hints stay visible in photo mode, since that is where its controls are explained.
+        if let Some(hint) = self.hints.active() {
+            let (w, h) = (size.0 as f32, size.1 as f32);
+
+            self.glyph_brush.queue(Section {
+                screen_position: (w / 2.0, h * 0.1),
+                bounds: (w, h),
+                text: vec![
+                    Text::new(hint.text()).with_scale(44.0).with_color([1.0, 0.9, 0.5, 1.0]),
+                    Text::new("\n[H] dismiss").with_scale(28.0).with_color([1.0, 1.0, 1.0, 0.7]),
+                ],
+                layout: Layout::default().h_align(HorizontalAlign::Center),
+            });
+        }
+
+        encoder.push_debug_group("Text");
+
+        self.glyph_brush.draw_queued(
+            &self.device,
+            &mut self.staging_belt,
+            &mut encoder,
+            &view,
+            self.config.width,
+            self.config.height,
+        ).unwrap();
+
+        encoder.pop_debug_group();
+
+        self.staging_belt.finish();
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        self.latency.presented(&self.queue);
+        self.staging_belt.recall();
+
+        Ok(())
+    }
+
+    // Renders the current view at `scale` times the window size and saves it as a PNG.
+    #[allow(clippy::too_many_arguments)]
+    pub fn capture(
+        &mut self,
+        scale: u32,
+        block_pipeline: &BlockPipeline,
+        target_pipeline: &BlockTargetPipeline,
+        hotbar_pipeline: &HotbarPipeline,
+        water_pipeline: &mut WaterPipeline,
+        speed_lines_pipeline: &SpeedLinesPipeline,
+        beacon_pipeline: &BeaconPipeline,
+    ) -> anyhow::Result<PathBuf> {
+        let image = self.capture_image(scale, block_pipeline, target_pipeline, hotbar_pipeline, water_pipeline, speed_lines_pipeline, beacon_pipeline)?;
+
+        let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let path = PathBuf::from(format!("screenshots/photo-{}.png", time));
+
+        std::fs::create_dir_all("screenshots")?;
+        image.save(&path)?;
+
+        Ok(path)
+    }
+
+    // Renders the current frame without the HUD into an offscreen target `scale` times the size
+    // of the window and reads it back. The depth and scene textures are swapped for larger ones
+    // for this one frame.
+    #[allow(clippy::too_many_arguments)]
+    pub fn capture_image(
+        &mut self,
+        scale: u32,
+        block_pipeline: &BlockPipeline,
+        target_pipeline: &BlockTargetPipeline,
+        hotbar_pipeline: &HotbarPipeline,
+        water_pipeline: &mut WaterPipeline,
+        speed_lines_pipeline: &SpeedLinesPipeline,
+        beacon_pipeline: &BeaconPipeline,
+    ) -> anyhow::Result<image::RgbaImage> {
+        let max = self.device.limits().max_texture_dimension_2d;
+        let scale = scale.min(max / self.config.width.max(self.config.height)).max(1);
+
+        let mut config = self.config.clone();
+        config.width *= scale;
+        config.height *= scale;
+
+        let previous = (
+            std::mem::replace(&mut self.depth_texture, Texture::create_depth_texture(&self.device, &config, "depth_texture")),
+            std::mem::replace(&mut self.scene_texture, Texture::create_scene_texture(&self.device, &config, "scene_texture")),
+            std::mem::replace(&mut self.scene_depth_texture, Texture::create_depth_texture(&self.device, &config, "scene_depth_texture")),
+        );
+
+        water_pipeline.resize(&self.device, &self.scene_texture, &self.scene_depth_texture);
+
+        let target = Texture::create_scene_texture(&self.device, &config, "capture_texture");
+
+        let bytes_per_row = wgpu::util::align_to(config.width * 4, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture Buffer"),
+            size: (bytes_per_row * config.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture Encoder"),
+        });
+
+        let size = (config.width, config.height);
+
+        self.encode_frame(&mut encoder, target.view(), size, block_pipeline, target_pipeline, hotbar_pipeline, water_pipeline, speed_lines_pipeline, beacon_pipeline, false);
+
+        encoder.copy_texture_to_buffer(
+            target.texture().as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        (self.depth_texture, self.scene_texture, self.scene_depth_texture) = previous;
+        water_pipeline.resize(&self.device, &self.scene_texture, &self.scene_depth_texture);
+
+        let (sender, receiver) = crossbeam::channel::bounded(1);
+        let slice = buffer.slice(..);
+
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+
+        let bgra = matches!(config.format, wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb);
+        let mut image = image::RgbaImage::new(config.width, config.height);
+
+        for (y, row) in slice.get_mapped_range().chunks(bytes_per_row as usize).enumerate() {
+            for (x, p) in row.chunks(4).take(config.width as usize).enumerate() {
+                let pixel = if bgra { [p[2], p[1], p[0], 255] } else { [p[0], p[1], p[2], 255] };
+                image.put_pixel(x as u32, y as u32, image::Rgba(pixel));
+            }
+        }
+
+        buffer.unmap();
+
+        Ok(image)
+    }
+
+    pub fn camera_position(&self) -> Vector3<f32> {
+        *self.camera.position()
+    }
+
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: &Block) {
+        self.world.set_block(x, y, z, block);
+    }
+
+    pub fn world_stats(&self) -> WorldStats {
+        self.world.stats()
+    }
+
+    pub fn set_overlay(&mut self, lines: Vec<String>) {
+        self.overlay = lines;
+    }
+
+    pub fn set_chunk_cache_memory(&mut self, bytes: usize) {
+        self.world.set_cache_memory(bytes);
+    }
+
+    pub fn set_worker_threads(&mut self, threads: usize) {
+        self.world.set_worker_threads(threads);
+    }
+
+    pub fn set_graphics_preset(&mut self, preset: GraphicsPreset) {
+        self.graphics = preset;
+        self.world.set_render_distance(preset.render_distance());
+        self.world.set_lod_distances(preset.lod_distances());
+    }
+
+    // Blocks until the GPU has finished every submitted frame.
+    pub fn wait_for_gpu(&self) {
+        self.device.poll(wgpu::Maintain::Wait);
+    }
+
+    pub fn is_world_settled(&self) -> bool {
+        self.world.is_settled()
+    }
+
+    // Replaces the world and camera with a golden scene's fixed seed and viewpoint. The world
+    // thread loads every chunk before it updates again, and meshes are built by the updates that
+    // follow, see `is_world_settled`. Scenes share seeds with real worlds, so nothing is loaded
+    // from or saved to theirs.
+    pub fn load_golden_scene(&mut self, scene: &GoldenScene) {
+        let config = WorldgenConfig { seed: scene.seed, ..WorldgenConfig::default() };
+
+        self.camera = Camera::new(scene.position, scene.yaw, scene.pitch);
+        self.camera.update(Duration::ZERO);
+        self.last_position = *self.camera.position();
+
+        self.world.set_saving(false);
+        self.world.set_generator(config.world_type.generator(&config));
+        self.world.generate(&self.camera);
+    }
+
+    // Draws the world into `view`, which may be the swapchain or an offscreen capture target of
+    // the same size as the current depth and scene textures.
+    #[allow(clippy::too_many_arguments)]
+    fn encode_frame(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        (width, height): (u32, u32),
+        block_pipeline: &BlockPipeline,
+        target_pipeline: &BlockTargetPipeline,
+        hotbar_pipeline: &HotbarPipeline,
+        water_pipeline: &WaterPipeline,
+        speed_lines_pipeline: &SpeedLinesPipeline,
+        beacon_pipeline: &BeaconPipeline,
+        hud: bool,
+    ) {
+        let (x, y, w, h) = self.hud_layout.viewport((width, height));
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
+                label: Some("Scene Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: self.scene_texture.view(),
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -212,51 +763,155 @@ impl State {
                 }),
             });
 
-            let buffers = self.world.buffers(&self.frustum);
+            render_pass.set_viewport(x as f32, y as f32, w as f32, h as f32, 0.0, 1.0);
 
             {
                 use crate::engine::block_pipeline::DrawBlock;
-                render_pass.attach_pipeline(block_pipeline);
-                buffers
-                    .iter()
-                    .for_each(|b| {
-                        render_pass.draw_mesh(b);
-                    });
+                render_pass.push_debug_group("Chunks");
+                render_pass.attach_pipeline(block_pipeline, &self.quad_indices);
+                render_pass.draw_chunks(self.world.vertices(false), &self.chunk_draws);
+                render_pass.pop_debug_group();
             }
 
             {
                 use crate::engine::block_target_pipeline::DrawBlock;
+                render_pass.push_debug_group("Block Target");
                 render_pass.draw_mesh(target_pipeline);
+                render_pass.pop_debug_group();
             }
+        }
+
+        encoder.copy_texture_to_texture(
+            self.depth_texture.texture().as_image_copy(),
+            self.scene_depth_texture.texture().as_image_copy(),
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Water Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_texture.view(),
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+
+            render_pass.set_viewport(x as f32, y as f32, w as f32, h as f32, 0.0, 1.0);
 
             {
-                use crate::engine::block_pipeline::DrawBlock;
-                render_pass.attach_pipeline(block_pipeline);
-                buffers
-                    .iter()
-                    .for_each(|b| {
-                        render_pass.draw_alpha_mesh(b);
-                    });
+                use crate::engine::water_pipeline::DrawWater;
+                render_pass.push_debug_group("Scene Blit");
+                render_pass.draw_scene(water_pipeline);
+                render_pass.pop_debug_group();
+
+                render_pass.push_debug_group("Water");
+                render_pass.attach_pipeline(water_pipeline, &self.quad_indices);
+                render_pass.draw_water(self.world.vertices(true), &self.chunk_draws);
+                render_pass.pop_debug_group();
             }
 
             {
+                use crate::engine::beacon_pipeline::DrawBeacons;
+                render_pass.push_debug_group("Beacon Beams");
+                render_pass.draw_beacons(beacon_pipeline);
+                render_pass.pop_debug_group();
+            }
+
+            if hud {
+                use crate::engine::speed_lines_pipeline::DrawSpeedLines;
+                render_pass.push_debug_group("Speed Lines");
+                render_pass.draw_speed_lines(speed_lines_pipeline);
+                render_pass.pop_debug_group();
+            }
+
+            if hud {
                 use crate::engine::hotbar_pipeline::DrawBlock;
+                render_pass.push_debug_group("Hotbar");
                 render_pass.draw_hotbar(hotbar_pipeline);
+                render_pass.pop_debug_group();
             }
         }
+    }
 
+    fn queue_hud(&mut self, (width, height): (u32, u32), block_pipeline: &BlockPipeline, fps: u32) {
         let p = self.camera.position();
 
-        let (w, h) = (self.config.width as f32, self.config.height as f32);
+        let (w, h) = (width as f32, height as f32);
+
+        let debug_info = match block_pipeline.debug_view() {
+            DebugView::None => String::new(),
+            view => format!("Debug View: {:?}\n", view),
+        };
+
+        let spectator_info = if let Some(spectator) = &self.spectator {
+            let s = spectator.position();
+            format!("Spectator: [{:.2}, {:.2}, {:.2}]\n", s.x, s.y, s.z)
+        } else {
+            String::new()
+        };
 
         let target_info = if let Some(target) = &self.target {
+            let tags = Block::by_name(&target.name)
+                .map(|b| b.tags.iter().map(|t| t.name()).collect::<Vec<_>>().join(", "))
+                .unwrap_or_default();
+
             format!(indoc! {"
-                Targeted Block: {} [{:?}]
-            "}, target.name, target.face)
+                Targeted Block: {} [{:?}] ({})
+            "}, target.name, target.face, tags)
+        } else {
+            String::new()
+        };
+
+        let stats = self.world.stats();
+        let cache_info = format!(
+            "Chunk Cache: {} chunks, {:.1}/{:.0} MiB, {} hits, {} misses\n",
+            stats.cache_chunks,
+            stats.cache_bytes as f32 / (1024.0 * 1024.0),
+            stats.cache_max_bytes as f32 / (1024.0 * 1024.0),
+            stats.cache_hits,
+            stats.cache_misses,
+        );
+
+        let ms = |d: Duration| d.as_secs_f32() * 1000.0;
+        let latency_info = if let Some([(present, present_p95), (gpu, gpu_p95)]) = self.latency.summary() {
+            format!(
+                "Input Latency: {:.1} ms to present ({:.1} p95), {:.1} ms to GPU done ({:.1} p95)\n",
+                ms(present), ms(present_p95), ms(gpu), ms(gpu_p95),
+            )
         } else {
             String::new()
         };
 
+        let [meshing, generation, lighting] = stats.queued_jobs;
+        let worker_info = format!(
+            "Workers: {} threads, {} meshing, {} generation and {} lighting jobs queued\n",
+            stats.worker_threads, meshing, generation, lighting,
+        );
+
+        let pool_info = format!(
+            "Vertex Pools: {:.1}/{:.0} MiB opaque, {:.1}/{:.0} MiB alpha\n",
+            self.world.vertices(false).used_bytes() as f32 / (1024.0 * 1024.0),
+            self.world.vertices(false).capacity_bytes() as f32 / (1024.0 * 1024.0),
+            self.world.vertices(true).used_bytes() as f32 / (1024.0 * 1024.0),
+            self.world.vertices(true).capacity_bytes() as f32 / (1024.0 * 1024.0),
+        );
+
         self.glyph_brush.queue(Section {
             screen_position: (5.0, 0.0),
             bounds: (w, h),
@@ -264,38 +919,166 @@ impl State {
                 Text::new(&format!(
                     indoc! {"
                         FPS: {}
+                        Graphics: {:?}{}, {} chunk render distance [-/=]
                         Position: [{:.2}, {:.2}, {:.2}]
-                        {}
-                    "}, fps, p.x, p.y, p.z, target_info)
+                        Chunks: {} loaded, {} dirty, {} waiting for a mesh, {} meshed last update
+                        World Memory: {:.1} MiB of blocks, {:.1} MiB of vertices, {:.1} MiB of indices
+                        {}{}{}{}{}{}{}
+                    "}, fps, self.graphics, if self.safe_mode { " (safe mode)" } else { "" }, self.world.render_distance(), p.x, p.y, p.z,
+                    stats.loaded_chunks, stats.dirty_chunks, stats.queued_meshes, stats.meshes_built,
+                    stats.block_bytes as f32 / (1024.0 * 1024.0),
+                    stats.vertex_bytes as f32 / (1024.0 * 1024.0),
+                    stats.index_bytes as f32 / (1024.0 * 1024.0),
+                    latency_info, cache_info, worker_info, pool_info, spectator_info, target_info, debug_info)
                 ).with_scale(40.0).with_color([1.0, 1.0, 1.0, 1.0])
             ],
             ..Section::default()
         });
 
+        if let Some(sequence) = &self.sequence {
+            let text = sequence.active_text().join("\n");
+
+            self.glyph_brush.queue(Section {
+                screen_position: (w / 2.0, h * 0.8),
+                bounds: (w, h),
+                text: vec![Text::new(&text).with_scale(60.0).with_color([1.0, 1.0, 1.0, 1.0])],
+                layout: Layout::default().h_align(HorizontalAlign::Center),
+            });
+        }
+
+        let pending = self.world.stats().pending_chunks;
+
+        if pending > 0 {
+            self.glyph_brush.queue(Section {
+                screen_position: (w / 2.0, h * 0.2),
+                bounds: (w, h),
+                text: vec![
+                    Text::new(&format!("Generating world... {} chunks queued", pending))
+                        .with_scale(36.0)
+                        .with_color([1.0, 1.0, 1.0, 0.8]),
+                ],
+                layout: Layout::default().h_align(HorizontalAlign::Center),
+            });
+        }
+
+        if !self.scoreboard.is_empty() {
+            let objectives = self.scoreboard
+                .objectives()
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, value))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            self.glyph_brush.queue(Section {
+                screen_position: (w - 5.0, h * 0.3),
+                bounds: (w, h),
+                text: vec![
+                    Text::new(self.scoreboard.title()).with_scale(44.0).with_color([1.0, 0.9, 0.5, 1.0]),
+                    Text::new(&format!("\n{}", objectives)).with_scale(36.0).with_color([1.0, 1.0, 1.0, 1.0]),
+                ],
+                layout: Layout::default().h_align(HorizontalAlign::Right),
+            });
+        }
+
+        if !self.overlay.is_empty() {
+            self.glyph_brush.queue(Section {
+                screen_position: (5.0, h - 5.0),
+                bounds: (w, h),
+                text: vec![Text::new(&self.overlay.join("\n")).with_scale(36.0).with_color([1.0, 1.0, 1.0, 1.0])],
+                layout: Layout::default().v_align(VerticalAlign::Bottom),
+            });
+        }
+
+        self.queue_compass((w, h));
+
         self.glyph_brush.queue(Section {
             screen_position: (w / 2.0 - 30.0, h / 2.0 - 30.0),
             bounds: (w, h),
             text: vec![Text::new("+").with_scale(60.0).with_color([1.0, 1.0, 1.0, 1.0])],
             ..Section::default()
         });
+    }
 
-        self.glyph_brush.draw_queued(
-            &self.device,
-            &mut self.staging_belt,
-            &mut encoder,
-            &view,
-            self.config.width,
-            self.config.height,
-        ).unwrap();
+    fn queue_compass(&mut self, (w, h): (f32, f32)) {
+        let camera = self.spectator.as_ref().unwrap_or(&self.camera);
+        let (yaw, position) = (*camera.yaw(), *camera.position());
+        let half_width = w * 0.2;
 
-        self.staging_belt.finish();
+        for (offset, label) in Compass::marks(yaw) {
+            let (scale, alpha) = if label.len() == 1 { (36.0, 1.0) } else { (26.0, 0.6) };
 
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+            self.glyph_brush.queue(Section {
+                screen_position: (w / 2.0 + offset * half_width, 30.0),
+                bounds: (w, h),
+                text: vec![Text::new(label).with_scale(scale).with_color([1.0, 1.0, 1.0, alpha])],
+                layout: Layout::default().h_align(HorizontalAlign::Center),
+            });
+        }
 
-        self.staging_belt.recall();
+        let (name, target) = self.compass.target();
+        let offset = Compass::offset(Compass::heading(position, target), yaw);
 
-        Ok(())
+        // Pinned to an edge, the marker turns into an arrow showing which way to turn.
+        let marker = match offset {
+            o if o <= -1.0 => "<",
+            o if o >= 1.0 => ">",
+            _ => "v",
+        };
+
+        self.glyph_brush.queue(Section {
+            screen_position: (w / 2.0 + offset * half_width, 5.0),
+            bounds: (w, h),
+            text: vec![
+                Text::new(marker).with_scale(32.0).with_color([1.0, 0.9, 0.5, 1.0]),
+            ],
+            layout: Layout::default().h_align(HorizontalAlign::Center),
+        });
+
+        self.glyph_brush.queue(Section {
+            screen_position: (w / 2.0, 62.0),
+            bounds: (w, h),
+            text: vec![
+                Text::new(&format!("{} {:.0}m", name, Compass::distance(position, target)))
+                    .with_scale(28.0)
+                    .with_color([1.0, 0.9, 0.5, 0.8]),
+            ],
+            layout: Layout::default().h_align(HorizontalAlign::Center),
+        });
+    }
+
+    fn queue_texture_info(&mut self, (width, height): (u32, u32), textures: &[TextureInfo]) {
+        let (w, h) = (width as f32, height as f32);
+
+        let lines = textures
+            .iter()
+            .map(|info| {
+                format!(
+                    "{}: {}x{} {:?}, {} mip{}, {} KiB",
+                    info.label,
+                    info.size.width,
+                    info.size.height,
+                    info.format,
+                    info.mip_level_count,
+                    if info.mip_level_count == 1 { "" } else { "s" },
+                    info.estimated_bytes() / 1024,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let total = textures.iter().map(TextureInfo::estimated_bytes).sum::<u64>();
+
+        self.glyph_brush.queue(Section {
+            screen_position: (5.0, h - 5.0),
+            bounds: (w, h),
+            text: vec![
+                Text::new(&format!("Textures ({}), ~{:.1} MiB\n", textures.len(), total as f64 / (1024.0 * 1024.0)))
+                    .with_scale(36.0)
+                    .with_color([1.0, 0.9, 0.5, 1.0]),
+                Text::new(&lines).with_scale(28.0).with_color([1.0, 1.0, 1.0, 1.0]),
+            ],
+            layout: Layout::default().v_align(VerticalAlign::Bottom),
+        });
     }
 
     pub fn size(&self) -> winit::dpi::PhysicalSize<u32> {
@@ -306,6 +1089,8 @@ impl State {
 
     pub fn config(&self) -> &wgpu::SurfaceConfiguration { &self.config }
 
+    pub fn hud_layout(&self) -> &HudLayout { &self.hud_layout }
+
     pub fn queue(&self) -> &wgpu::Queue { &self.queue }
 
     pub fn camera_unfirom(&self) -> &CameraUniform {
@@ -315,4 +1100,24 @@ impl State {
     pub fn target_uniform(&self) -> &TargetUniform {
         &self.target_uniform
     }
+
+    pub fn water_uniform(&self) -> &WaterUniform {
+        &self.water_uniform
+    }
+
+    pub fn speed_lines_uniform(&self) -> &SpeedLinesUniform {
+        &self.speed_lines_uniform
+    }
+
+    pub fn beacon_uniform(&self) -> &BeaconUniform {
+        &self.beacon_uniform
+    }
+
+    pub fn scene_texture(&self) -> &Texture {
+        &self.scene_texture
+    }
+
+    pub fn scene_depth_texture(&self) -> &Texture {
+        &self.scene_depth_texture
+    }
 }
\ No newline at end of file
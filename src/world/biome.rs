@@ -0,0 +1,34 @@
+use serde::Deserialize;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Biome {
+    Plains,
+    Hills,
+    Mountains,
+}
+
+impl Biome {
+
+    // Picks a biome from a climate sample in -1..1.
+    pub fn from_climate(climate: f64) -> Self {
+        if climate < -0.25 {
+            Biome::Plains
+        } else if climate < 0.3 {
+            Biome::Hills
+        } else {
+            Biome::Mountains
+        }
+    }
+
+    // Offset added to the configured base height, and the factor the configured amplitude is
+    // scaled by.
+    pub fn height_profile(&self) -> (f64, f64) {
+        match self {
+            Biome::Plains => (-2.0, 0.35),
+            Biome::Hills => (0.0, 1.0),
+            Biome::Mountains => (18.0, 2.0),
+        }
+    }
+
+}
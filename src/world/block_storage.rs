@@ -0,0 +1,203 @@
+// Block ids for a run of blocks, stored as indices into a palette of the ids in use and packed
+// into as few bits as the palette needs. Most sections hold two or three kinds of block, and a
+// section of a single kind, like all air, stores no indices at all. Indices never straddle two
+// words, so a few bits per word may go unused.
+#[derive(Clone)]
+pub struct BlockStorage {
+    palette: Vec<u8>,
+    bits: u32,
+    data: Vec<u64>,
+    len: usize,
+}
+
+impl BlockStorage {
+
+    pub fn new(len: usize, id: u8) -> Self {
+        Self {
+            palette: vec![id],
+            bits: 0,
+            data: vec![],
+            len,
+        }
+    }
+
+    pub fn from_blocks(blocks: &[u8]) -> Self {
+        let palette = Self::palette_of(blocks);
+        let mut storage = Self::new(blocks.len(), palette.first().copied().unwrap_or(0));
+        storage.palette = palette;
+        storage.resize(Self::bits_for(storage.palette.len()), |i| blocks[i]);
+
+        storage
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        if self.bits == 0 {
+            return self.palette[0];
+        }
+
+        let (word, shift) = self.locate(index);
+        let entry = (self.data[word] >> shift) & self.mask();
+
+        self.palette[entry as usize]
+    }
+
+    pub fn set(&mut self, index: usize, id: u8) {
+        let entry = match self.palette.iter().position(|p| *p == id) {
+            Some(entry) => entry,
+            None if Self::bits_for(self.palette.len() + 1) == self.bits => {
+                self.palette.push(id);
+                self.palette.len() - 1
+            },
+            // Ids no longer in use are dropped from the palette while repacking anyway, so it
+            // only grows as far as the blocks actually stored need.
+            None => {
+                let mut blocks = self.to_vec();
+                blocks[index] = id;
+
+                self.palette = Self::palette_of(&blocks);
+                self.resize(Self::bits_for(self.palette.len()), |i| blocks[i]);
+
+                return;
+            },
+        };
+
+        if self.bits == 0 {
+            return;
+        }
+
+        let (word, shift) = self.locate(index);
+        self.data[word] = (self.data[word] & !(self.mask() << shift)) | ((entry as u64) << shift);
+    }
+
+    // Bytes held, including the struct itself.
+    pub fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.palette.capacity() + self.data.capacity() * std::mem::size_of::<u64>()
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        (0..self.len).map(|i| self.get(i)).collect()
+    }
+
+    // Repacks to `bits` per index, reading the current ids through `block` since the old packing
+    // is overwritten.
+    fn resize<F: Fn(usize) -> u8>(&mut self, bits: u32, block: F) {
+        self.bits = bits;
+
+        if bits == 0 {
+            self.data = vec![];
+            return;
+        }
+
+        let per_word = (64 / bits) as usize;
+        self.data = vec![0; self.len.div_ceil(per_word)];
+
+        for i in 0..self.len {
+            let entry = self.palette.iter().position(|p| *p == block(i)).unwrap() as u64;
+            let (word, shift) = self.locate(i);
+            self.data[word] |= entry << shift;
+        }
+    }
+
+    // Each id in `blocks` once, in order of first use.
+    fn palette_of(blocks: &[u8]) -> Vec<u8> {
+        let mut palette = vec![];
+
+        for id in blocks {
+            if !palette.contains(id) {
+                palette.push(*id);
+            }
+        }
+
+        palette
+    }
+
+    fn locate(&self, index: usize) -> (usize, u32) {
+        let per_word = (64 / self.bits) as usize;
+        (index / per_word, (index % per_word) as u32 * self.bits)
+    }
+
+    fn mask(&self) -> u64 {
+        (1 << self.bits) - 1
+    }
+
+    fn bits_for(count: usize) -> u32 {
+        if count <= 1 { 0 } else { usize::BITS - (count - 1).leading_zeros() }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockStorage;
+
+    const SECTION: usize = 16 * 16 * 16;
+
+    #[test]
+    fn grows_bits_as_the_palette_grows() {
+        let mut storage = BlockStorage::new(SECTION, 0);
+        assert_eq!(storage.bits, 0);
+
+        storage.set(1, 1);
+        assert_eq!(storage.bits, 1);
+
+        storage.set(2, 2);
+        assert_eq!(storage.bits, 2);
+
+        for id in 3..9 {
+            storage.set(id as usize, id);
+        }
+
+        assert_eq!(storage.bits, 4);
+        assert!((0..9).all(|i| storage.get(i) == i as u8));
+        assert!((9..SECTION).all(|i| storage.get(i) == 0));
+    }
+
+    #[test]
+    fn entries_around_word_boundaries() {
+        // 3 bits fit 21 entries a word, so words end on 20, 41 and 62.
+        let mut storage = BlockStorage::new(SECTION, 0);
+
+        for id in 1..5 {
+            storage.set(id as usize, id);
+        }
+
+        assert_eq!(storage.bits, 3);
+
+        for (i, index) in [19, 20, 21, 22, 41, 42, 62, 63].into_iter().enumerate() {
+            storage.set(index, (i % 4) as u8 + 4);
+        }
+
+        for (i, index) in [19, 20, 21, 22, 41, 42, 62, 63].into_iter().enumerate() {
+            assert_eq!(storage.get(index), (i % 4) as u8 + 4);
+        }
+
+        assert_eq!(storage.get(18), 0);
+        assert_eq!(storage.get(23), 0);
+    }
+
+    #[test]
+    fn set_then_get_a_full_section() {
+        let mut storage = BlockStorage::new(SECTION, 0);
+        let id = |i: usize| ((i * 7 + i / 16) % 13) as u8;
+
+        for i in 0..SECTION {
+            storage.set(i, id(i));
+        }
+
+        assert!((0..SECTION).all(|i| storage.get(i) == id(i)));
+        assert_eq!(BlockStorage::from_blocks(&storage.to_vec()).to_vec(), storage.to_vec());
+    }
+
+    #[test]
+    fn repacking_drops_unused_ids() {
+        let mut storage = BlockStorage::new(SECTION, 0);
+
+        for id in 1..100 {
+            storage.set(0, id);
+        }
+
+        assert_eq!(storage.palette.len(), 2);
+        assert_eq!(storage.bits, 1);
+        assert_eq!(storage.get(0), 99);
+    }
+}
\ No newline at end of file
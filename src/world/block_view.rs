@@ -0,0 +1,55 @@
+use cgmath::Point3;
+use collision::Aabb3;
+use crate::objects::block::Block;
+use crate::objects::block_material::BlockMaterial;
+
+// A copy of the blocks in a box of the world, filled by `World::get_blocks` with one chunk lookup
+// per chunk rather than one per block. The box holds every block from `min` up to but not
+// including `max`. Blocks outside it, above or below the world, or in chunks that aren't loaded
+// read as None.
+pub struct BlockView {
+    min: Point3<i32>,
+    max: Point3<i32>,
+    blocks: Vec<Option<u8>>,
+}
+
+impl BlockView {
+
+    pub fn new(aabb: &Aabb3<i32>) -> Self {
+        let size = aabb.max - aabb.min;
+
+        Self {
+            min: aabb.min,
+            max: aabb.max,
+            blocks: vec![None; (size.x * size.y * size.z).max(0) as usize],
+        }
+    }
+
+    pub fn get(&self, x: i32, y: i32, z: i32) -> Option<u8> {
+        self.blocks[self.index(x, y, z)?]
+    }
+
+    // Only changes the copy, e.g. to see earlier edits of a batch before it's written back.
+    pub fn set(&mut self, x: i32, y: i32, z: i32, id: u8) {
+        if let Some(i) = self.index(x, y, z) {
+            self.blocks[i] = Some(id);
+        }
+    }
+
+    pub fn is_solid(&self, x: i32, y: i32, z: i32) -> bool {
+        self.get(x, y, z).is_some_and(|id| Block::block(id).material == BlockMaterial::Solid)
+    }
+
+    fn index(&self, x: i32, y: i32, z: i32) -> Option<usize> {
+        let (min, max) = (self.min, self.max);
+
+        if !(min.x..max.x).contains(&x) || !(min.y..max.y).contains(&y) || !(min.z..max.z).contains(&z) {
+            return None;
+        }
+
+        let (w, h) = (max.x - min.x, max.y - min.y);
+
+        Some((x - min.x + (y - min.y) * w + (z - min.z) * w * h) as usize)
+    }
+
+}
\ No newline at end of file
@@ -1,100 +1,149 @@
-use bytemuck::Contiguous;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use crossbeam::channel::{Receiver, Sender};
-use noise::{Fbm, NoiseFn, Perlin};
-use threadpool::ThreadPool;
+use rayon::prelude::*;
+use crate::mesh::vertex::Vertex;
 use crate::objects::block::Block;
 use crate::objects::block_material::BlockMaterial;
 use crate::world::chunk_mesh::ChunkMesh;
+use crate::world::chunk_section::ChunkSection;
+use crate::world::job_system::{JobCategory, JobSystem};
+use crate::world::lod;
+use crate::world::occlusion::SectionVisibility;
+use crate::world::occupancy::{Bits, Occupancy};
+use crate::world::terrain_generator::TerrainGenerator;
 
 pub struct Chunk {
     local_position: (i32, i32),
     world_position: (i32, i32),
 
-    blocks: Vec<u8>,
-    mesh: ChunkMesh,
-    mesh_generated: bool,
+    sections: Vec<ChunkSection>,
+    occupancy: Occupancy,
+    // Per column, one above its highest non-air block or 0 when it's empty. Kept up to date by
+    // edits so surface queries don't scan the column.
+    heightmap: Vec<i32>,
+    // Per column, the height and id of the top block as generated. Kept until the chunk is
+    // decorated, so features a neighbor spills into it first don't move its own.
+    terrain_surface: Option<Vec<(i32, u8)>>,
+
+    // One mesh per section, so an edit only rebuilds the sections it touches. Bits of
+    // `dirty_sections` are sections waiting to be meshed, bits of `updated_sections` are sections
+    // meshed since their buffers were last built.
+    meshes: Vec<ChunkMesh>,
+    dirty_sections: u64,
+    updated_sections: u64,
     generating_mesh: bool,
-
-    needs_buffer: bool,
-
-    sender: Sender<ChunkMesh>,
-    receiver: Receiver<ChunkMesh>,
-
-    left: Option<Vec<u8>>,
-    right: Option<Vec<u8>>,
-    front: Option<Vec<u8>>,
-    back: Option<Vec<u8>>,
+    // Blocks per mesh cell along each axis, above 1 for distant chunks; see `lod`.
+    lod_scale: i32,
+
+    decorated: bool,
+    // Set by edits so the chunk is saved rather than regenerated once unloaded.
+    modified: bool,
+
+    sender: Sender<Vec<(i32, ChunkMesh)>>,
+    receiver: Receiver<Vec<(i32, ChunkMesh)>>,
+    // Set once the chunk is unloaded or dropped, so a mesh job that hasn't run yet skips the
+    // work nobody will receive.
+    mesh_cancelled: Arc<AtomicBool>,
+
+    left: Option<Vec<ChunkSection>>,
+    right: Option<Vec<ChunkSection>>,
+    front: Option<Vec<ChunkSection>>,
+    back: Option<Vec<ChunkSection>>,
 }
 
 impl Chunk {
     pub const WIDTH: i32 = 16;
-    pub const HEIGHT: i32 = 256;
     pub const DEPTH: i32 = 16;
+
+    // The column is a stack of sections, so the world height is raised by adding sections, up to
+    // the 64 that fit in a section mask: 1024 blocks. It has to stay a multiple of 64 for the
+    // occupancy masks.
+    pub const SECTION_HEIGHT: i32 = 16;
+    pub const SECTION_COUNT: i32 = 16;
+    pub const SECTION_SIZE: i32 = Chunk::WIDTH * Chunk::SECTION_HEIGHT * Chunk::DEPTH;
+
+    pub const HEIGHT: i32 = Chunk::SECTION_HEIGHT * Chunk::SECTION_COUNT;
     pub const SIZE: i32 = Chunk::WIDTH * Chunk::HEIGHT * Chunk::DEPTH;
 
-    pub fn new(local_position: (i32, i32), noise: &Fbm<Perlin>) -> Self {
+    pub const ALL_SECTIONS: u64 = u64::MAX >> (64 - Chunk::SECTION_COUNT);
+
+    pub fn new(local_position: (i32, i32), generator: &dyn TerrainGenerator) -> Self {
+        let mut blocks = generator.generate(local_position);
+
+        // The bottom layer is always bedrock, whatever the generator produced; y = 0 is the first
+        // WIDTH * DEPTH entries.
+        blocks[..(Chunk::WIDTH * Chunk::DEPTH) as usize].fill(Block::Bedrock.id);
+
+        let mut chunk = Self::from_blocks(local_position, &blocks);
+        chunk.terrain_surface = Some(
+            (0..Chunk::DEPTH)
+                .flat_map(|z| (0..Chunk::WIDTH).map(move |x| (x, z)))
+                .map(|(x, z)| chunk.top_block(x, z).unwrap_or((-1, Block::Air.id)))
+                .collect(),
+        );
+
+        chunk
+    }
+
+    pub fn from_blocks(local_position: (i32, i32), blocks: &[u8]) -> Self {
         let (sender, receiver) = crossbeam::channel::unbounded();
 
-        let mut chunk = Self {
+        Self {
             local_position,
             world_position: Self::local_to_world_position(local_position),
-            blocks: vec![Block::Air.id; Chunk::SIZE as usize],
-            mesh: ChunkMesh {
-                vertices: vec![],
-                indices: vec![],
-                alpha_vertices: vec![],
-                alpha_indices: vec![],
-            },
-            mesh_generated: false,
+            occupancy: Occupancy::from_blocks(blocks),
+            sections: ChunkSection::split(blocks),
+            heightmap: Self::build_heightmap(blocks),
+            terrain_surface: None,
+            meshes: (0..Chunk::SECTION_COUNT).map(|_| ChunkMesh::new()).collect(),
+            dirty_sections: Self::ALL_SECTIONS,
+            updated_sections: 0,
             generating_mesh: false,
-            needs_buffer: false,
+            lod_scale: 1,
+            decorated: false,
+            modified: false,
             sender,
             receiver,
+            mesh_cancelled: Arc::new(AtomicBool::new(false)),
             left: None,
             right: None,
             front: None,
             back: None,
-        };
-
-        chunk.generate_blocks(noise);
-
-        chunk
+        }
     }
 
-    fn generate_blocks(&mut self, noise: &Fbm<Perlin>) {
-        for x in 0..Chunk::WIDTH {
-            for z in 0..Chunk::DEPTH {
-                let n = ((noise.get([
-                    (x + self.world_position.0) as f64 + 0.01,
-                    (z + self.world_position.1) as f64 + 0.01
-                ]) + 2.0) * 32.0) as i32;
-
-                let chunk_height = n.max(60);
-
-                for y in 0..(chunk_height + 1) {
-                    if y > n {
-                        self.blocks[Self::xyz_to_index(x, y, z)] = Block::Water.id;
-                    } else {
-                        self.blocks[Self::xyz_to_index(x, y, z)] = Block::Grass.id;
-                    }
-                }
+    // Picks up a finished mesh job; returns whether one arrived.
+    pub fn receive_mesh(&mut self) -> bool {
+        if let Ok(meshes) = self.receiver.try_recv() {
+            for (section, mesh) in meshes {
+                self.meshes[section as usize] = mesh;
+                self.updated_sections |= 1 << section;
             }
+            self.generating_mesh = false;
+            return true;
         }
+
+        false
     }
 
-    pub fn update(&mut self, pool: &mut ThreadPool) {
-        if let Ok(mesh) = self.receiver.try_recv() {
-            self.mesh = mesh;
-            self.generating_mesh = false;
-            self.mesh_generated = true;
-            self.needs_buffer = true;
-            return;
-        } else if !self.mesh_generated && !self.generating_mesh {
-            self.generate_mesh(pool);
-        }
+    // Meshing waits for decoration and for every neighbor's blocks, which faces on the borders
+    // are culled against.
+    pub fn needs_mesh(&self) -> bool {
+        self.decorated
+            && self.dirty_sections != 0
+            && !self.generating_mesh
+            && self.left.is_some()
+            && self.right.is_some()
+            && self.front.is_some()
+            && self.back.is_some()
     }
 
-    fn generate_mesh(&mut self, pool: &mut ThreadPool) {
+    pub fn is_generating_mesh(&self) -> bool {
+        self.generating_mesh
+    }
+
+    pub fn generate_mesh(&mut self, jobs: &JobSystem) {
         let (
             left,
             right,
@@ -111,288 +160,403 @@ impl Chunk {
             return;
         };
 
+        // Sections edited while this job runs are dirtied again and picked up by the next one.
+        let dirty = self.dirty_sections;
+        self.dirty_sections = 0;
         self.generating_mesh = true;
-        self.mesh_generated = false;
 
-        let blocks = self.blocks.clone();
-        let world_position = self.world_position;
+        let sections = self.sections.clone();
+        let occupancy = self.occupancy.clone();
+        let scale = self.lod_scale;
         let sender = self.sender.clone();
+        let cancelled = self.mesh_cancelled.clone();
         let left = left.clone();
         let right = right.clone();
         let front = front.clone();
         let back = back.clone();
 
-        pool.execute(move || {
-            let mut vertices = vec![];
-            let mut indices = vec![];
-            let mut alpha_vertices = vec![];
-            let mut alpha_indices = vec![];
-            let mut solid_index_offset = 0;
-            let mut alpha_index_offset = 0;
-
-            for (i, b) in blocks.iter().enumerate() {
-                let b = *b;
-                let block = Block::block(b);
-
-                if Block::Air.id == block.id { continue; }
-
-                let faces = [
-                    Self::has_front(&blocks, &front, i),
-                    Self::has_back(&blocks, &back, i),
-                    Self::has_left(&blocks, &left, i),
-                    Self::has_right(&blocks, &right, i),
-                    Self::has_top(&blocks, i),
-                    Self::has_bottom(&blocks, i),
-                ];
-
-                let (x, y, z) = Self::index_to_xyz(i);
-                let (x, y, z) = ((x + world_position.0) as f32, y as f32, (z + world_position.1) as f32);
-
-                let (verts, inds) = block.build_faces(
-                    x,
-                    y,
-                    z,
-                    faces,
-                    if block.material == BlockMaterial::Solid { solid_index_offset } else { alpha_index_offset },
-                );
-
-                if block.material == BlockMaterial::Solid {
-                    solid_index_offset += verts.len() as u32;
-                    vertices.extend_from_slice(verts.as_slice());
-                    indices.extend_from_slice(inds.as_slice());
-                } else {
-                    alpha_index_offset += verts.len() as u32;
-                    alpha_vertices.extend_from_slice(verts.as_slice());
-                    alpha_indices.extend_from_slice(inds.as_slice());
-                }
+        jobs.spawn(JobCategory::Meshing, move || {
+            if cancelled.load(Ordering::Relaxed) {
+                return;
             }
 
-            sender.send(ChunkMesh {
-                vertices,
-                indices,
+            let neighbors = [left.as_slice(), right.as_slice(), front.as_slice(), back.as_slice()];
+            let meshes = Self::mesh_sections(&sections, &occupancy, neighbors, scale, dirty, &cancelled);
 
-                alpha_vertices,
-                alpha_indices,
-            });
+            if !cancelled.load(Ordering::Relaxed) {
+                let _ = sender.send(meshes);
+            }
         });
     }
 
-    pub fn has_left(blocks: &Vec<u8>, left: &Vec<u8>, index: usize) -> bool {
-        let (x, y, z) = Self::index_to_xyz(index);
-        let block = Block::block(blocks[index]);
-
-        if x == 0 {
-            let left = left.get(Self::xyz_to_index(Chunk::WIDTH - 1, y, z))
-                .unwrap_or(&Block::Air.id);
+    // Meshes every section right away, without a job and without touching the chunk's own
+    // meshes, so the mesher can be measured on its own; `generate_mesh` does the same work. None
+    // until every neighbor is known.
+    pub fn build_meshes(&self) -> Option<Vec<ChunkMesh>> {
+        let neighbors = [self.left.as_deref()?, self.right.as_deref()?, self.front.as_deref()?, self.back.as_deref()?];
+        let cancelled = AtomicBool::new(false);
+
+        let meshes = Self::mesh_sections(&self.sections, &self.occupancy, neighbors, self.lod_scale, Self::ALL_SECTIONS, &cancelled);
+
+        Some(meshes.into_iter().map(|(_, mesh)| mesh).collect())
+    }
+
+    // The meshes of the sections set in `dirty`, skipping what's left once `cancelled` is set.
+    fn mesh_sections(
+        sections: &[ChunkSection],
+        occupancy: &Occupancy,
+        neighbors: [&[ChunkSection]; 4],
+        scale: i32,
+        dirty: u64,
+        cancelled: &AtomicBool,
+    ) -> Vec<(i32, ChunkMesh)> {
+        let [left, right, front, back] = neighbors;
+        let faces = if scale == 1 { occupancy.visible_faces(left, right, front, back) } else { vec![] };
+        let shores = if scale == 1 { occupancy.shores(left, right, front, back) } else { vec![] };
+
+        (0..Chunk::SECTION_COUNT)
+            .into_par_iter()
+            .filter(|section| dirty >> section & 1 == 1 && !cancelled.load(Ordering::Relaxed))
+            .map(|section| {
+                let mut mesh = if scale == 1 {
+                    Self::build_section_mesh(sections, &faces, &shores, section)
+                } else {
+                    lod::build_section_mesh(sections, neighbors, section, scale)
+                };
 
-            let other = Block::block(*left);
+                mesh.visibility = SectionVisibility::from_section(&sections[section as usize]);
 
-            !Self::is_hidden(&block, &other)
-        } else {
-            let left = blocks.get(Self::xyz_to_index(x - 1, y, z))
-                .unwrap_or(&Block::Air.id);
+                (section, mesh)
+            })
+            .collect()
+    }
 
-            let other = Block::block(*left);
+    fn build_section_mesh(
+        sections: &[ChunkSection],
+        faces: &[[Bits; 6]],
+        shores: &[[Bits; 4]],
+        section: i32,
+    ) -> ChunkMesh {
+        let mut mesh = ChunkMesh::new();
 
-            !Self::is_hidden(&block, &other)
+        if sections[section as usize].is_empty() {
+            return mesh;
         }
-    }
+        let bottom = section * Chunk::SECTION_HEIGHT;
+        let word = (bottom / 64) as usize;
+        let section_bits = ((1u64 << Chunk::SECTION_HEIGHT) - 1) << (bottom % 64);
 
-    pub fn has_right(blocks: &Vec<u8>, right: &Vec<u8>, index: usize) -> bool {
-        let (x, y, z) = Self::index_to_xyz(index);
-        let block = Block::block(blocks[index]);
+        for (column, masks) in faces.iter().enumerate() {
+            let mut remaining = masks.iter().fold(0, |acc, m| acc | m[word]) & section_bits;
 
-        if x + 1 == Chunk::WIDTH {
-            let right = right.get(Self::xyz_to_index(0, y, z))
-                .unwrap_or(&Block::Air.id);
+            while remaining != 0 {
+                let bit = remaining.trailing_zeros();
+                remaining &= remaining - 1;
 
-            let other = Block::block(*right);
+                let (x, y, z) = (
+                    column as i32 % Chunk::WIDTH,
+                    word as i32 * 64 + bit as i32,
+                    column as i32 / Chunk::WIDTH,
+                );
 
-            !Self::is_hidden(&block, &other)
-        } else {
-            let right = blocks.get(Self::xyz_to_index(x + 1, y, z))
-                .unwrap_or(&Block::Air.id);
+                let block = Block::block(sections[section as usize].get(x, y - bottom, z));
 
-            let other = Block::block(*right);
+                let faces = [0, 1, 2, 3, 4, 5].map(|f| masks[f][word] >> bit & 1 == 1);
+                let overlays = Self::grass_overlays(sections, masks, &block, (x, y, z), faces);
 
-            !Self::is_hidden(&block, &other)
-        }
-    }
+                // Relative to the section origin, see `ChunkBuffer`.
+                let (x, y, z) = (x as f32, (y - bottom) as f32, z as f32);
 
-    pub fn has_top(blocks: &Vec<u8>, index: usize) -> bool {
-        let block = Block::block(blocks[index]);
+                let mut verts = block.build_faces(x, y, z, 1.0, faces, overlays);
 
-        let above = blocks.get(index + Chunk::HEIGHT as usize)
-            .unwrap_or(&Block::Air.id);
+                if faces[4] && shores[column].iter().any(|s| s[word] >> bit & 1 == 1) {
+                    // The top face comes after the visible side faces.
+                    let top = faces[..4].iter().filter(|f| **f).count() * 4;
+                    Self::mark_shore(&mut verts[top..top + 4], &shores[column], word, bit);
+                }
 
-        let other = Block::block(*above);
+                if block.material == BlockMaterial::Solid {
+                    mesh.vertices.extend_from_slice(verts.as_slice());
+                } else {
+                    mesh.alpha_vertices.extend_from_slice(verts.as_slice());
+                }
+            }
+        }
 
-        !Self::is_hidden(&block, &other)
+        mesh
     }
 
-    pub fn has_bottom(blocks: &Vec<u8>, index: usize) -> bool {
-        let index = if index < Chunk::HEIGHT as usize { std::num::NonZeroUsize::MAX_VALUE } else { index };
+    // The sides of a dirt block that continue an exposed side of the grass block on top of it.
+    // Whether the grass side is exposed comes from the same face masks, so it's as current as the
+    // dirt's own faces.
+    fn grass_overlays(
+        sections: &[ChunkSection],
+        masks: &[Bits; 6],
+        block: &Block,
+        (x, y, z): (i32, i32, i32),
+        faces: [bool; 6],
+    ) -> [Option<[u8; 2]>; 6] {
+        let mut overlays = [None; 6];
 
-        let below = blocks.get(index - Chunk::HEIGHT as usize)
-            .unwrap_or(&Block::Air.id);
-
-        let other = Block::block(*below);
-
-        if index == std::num::NonZeroUsize::MAX_VALUE {
-            true
-        } else {
-            !Self::is_hidden(&Block::block(blocks[index]), &other)
+        if block.id != Block::Dirt.id || Self::block_in_sections(sections, x, y + 1, z) != Some(Block::Grass.id) {
+            return overlays;
         }
-    }
 
-    pub fn has_front(blocks: &Vec<u8>, front: &Vec<u8>, index: usize) -> bool {
-        let (x, y, z) = Self::index_to_xyz(index);
-        let block = Block::block(blocks[index]);
+        let (word, bit) = (((y + 1) / 64) as usize, (y + 1) % 64);
 
-        if z == 0 {
-            let front = front.get(Self::xyz_to_index(x, y, Self::DEPTH - 1))
-                .unwrap_or(&Block::Air.id);
-
-            let other = Block::block(*front);
+        for side in 0..4 {
+            if faces[side] && masks[side][word] >> bit & 1 == 1 {
+                overlays[side] = Some(Block::GRASS_SIDE_OVERLAY);
+            }
+        }
 
-            !Self::is_hidden(&block, &other)
-        } else {
-            let front = blocks.get(Self::xyz_to_index(x, y, z - 1))
-                .unwrap_or(&Block::Air.id);
+        overlays
+    }
 
-            let other = Block::block(*front);
+    // Flags the corners of a water block's top face that lie along a solid neighbor, so the foam
+    // fades out towards the open water across the face.
+    fn mark_shore(top: &mut [Vertex], shore: &[Bits; 4], word: usize, bit: u32) {
+        let [front, back, left, right] = shore.map(|s| s[word] >> bit & 1 == 1);
 
-            !Self::is_hidden(&block, &other)
+        for (vertex, [cx, _, cz]) in top.iter_mut().zip(Block::POSITIONS[4]) {
+            if (cx == 0.0 && left) || (cx == 1.0 && right) || (cz == 0.0 && front) || (cz == 1.0 && back) {
+                vertex.set_shore();
+            }
         }
     }
 
-    pub fn has_back(blocks: &Vec<u8>, back: &Vec<u8>, index: usize) -> bool {
-        let (x, y, z) = Self::index_to_xyz(index);
-        let block = Block::block(blocks[index]);
+    // Index into a whole column laid out as one array, which is what generators produce. Each
+    // section's blocks are a contiguous run of it, bottom section first.
+    pub fn xyz_to_index(x: i32, y: i32, z: i32) -> usize {
+        (x + z * Chunk::WIDTH + y * Chunk::WIDTH * Chunk::DEPTH) as usize
+    }
 
-        if z + 1 == Chunk::DEPTH {
-            let back = back.get(Self::xyz_to_index(x, y, 0))
-                .unwrap_or(&Block::Air.id);
+    pub fn index_to_xyz(index: usize) -> (i32, i32, i32) {
+        let i = index as i32;
+        let layer = Chunk::WIDTH * Chunk::DEPTH;
+        (i % Chunk::WIDTH, i / layer, i % layer / Chunk::WIDTH)
+    }
 
-            let other = Block::block(*back);
+    pub fn place_block_at_world_position(&mut self, block: &Block, (x, y, z): (i32, i32, i32)) {
+        let (lx, lz) = ((x - self.world_position.0).abs(), (z - self.world_position.1).abs());
 
-            !Self::is_hidden(&block, &other)
-        } else {
-            let back = blocks.get(Self::xyz_to_index(x, y, z + 1))
-                .unwrap_or(&Block::Air.id);
+        if Self::in_bounds(lx, y, lz) {
+            self.sections[(y / Chunk::SECTION_HEIGHT) as usize].set(lx, y % Chunk::SECTION_HEIGHT, lz, block.id);
+            self.occupancy.set(lx, y, lz, block);
+            self.invalidate_section(y / Chunk::SECTION_HEIGHT);
 
-            let other = Block::block(*back);
+            let column = (lx + lz * Chunk::WIDTH) as usize;
 
-            !Self::is_hidden(&block, &other)
+            if block.id != Block::Air.id && y >= self.heightmap[column] {
+                self.heightmap[column] = y + 1;
+            } else if block.id == Block::Air.id && y + 1 == self.heightmap[column] {
+                self.heightmap[column] = (0..y)
+                    .rev()
+                    .find(|y| self.block_at_local_position((lx, *y, lz)) != Some(Block::Air.id))
+                    .map_or(0, |y| y + 1);
+            }
         }
     }
 
-    fn is_hidden(block: &Block, other: &Block) -> bool {
-        if other.material == BlockMaterial::Solid {
-            true
-        } else if other.id == Block::Water.id && block.id == Block::Water.id {
-            true
-        } else {
-            false
+    fn build_heightmap(blocks: &[u8]) -> Vec<i32> {
+        (0..Chunk::WIDTH * Chunk::DEPTH)
+            .map(|column| {
+                let (x, z) = (column % Chunk::WIDTH, column / Chunk::WIDTH);
+
+                (0..Chunk::HEIGHT)
+                    .rev()
+                    .find(|y| blocks[Self::xyz_to_index(x, *y, z)] != Block::Air.id)
+                    .map_or(0, |y| y + 1)
+            })
+            .collect()
+    }
+
+    // One above the highest non-air block of the local column, or 0 when it's empty.
+    pub fn surface_height(&self, x: i32, z: i32) -> i32 {
+        self.heightmap[(x + z * Chunk::WIDTH) as usize]
+    }
+
+    // Height and id of the column's top block as it was generated, or as it is for chunks that
+    // were loaded or already decorated. None for empty columns.
+    pub fn terrain_surface(&self, x: i32, z: i32) -> Option<(i32, u8)> {
+        match &self.terrain_surface {
+            Some(surface) => Some(surface[(x + z * Chunk::WIDTH) as usize]).filter(|(y, _)| *y >= 0),
+            None => self.top_block(x, z),
         }
     }
 
-    pub fn xyz_to_index(x: i32, y: i32, z: i32) -> usize {
-        (x + z * Chunk::WIDTH + y * Chunk::HEIGHT) as usize
+    fn top_block(&self, x: i32, z: i32) -> Option<(i32, u8)> {
+        let y = self.surface_height(x, z) - 1;
+        self.block_at_local_position((x, y, z)).map(|id| (y, id)).filter(|_| y >= 0)
     }
 
-    pub fn index_to_xyz(index: usize) -> (i32, i32, i32) {
-        let mut i = index as i32;
-        let y = i / Chunk::HEIGHT;
-        let yr = i % Chunk::HEIGHT;
-        let z = yr / Chunk::WIDTH;
-        let zr = yr % Chunk::WIDTH;
-        (zr, y, z)
+    pub fn block_at_local_position(&self, (x, y, z): (i32, i32, i32)) -> Option<u8> {
+        Self::block_in_sections(&self.sections, x, y, z)
     }
 
-    pub fn place_block_at_world_position(&mut self, block: &Block, (x, y, z): (i32, i32, i32)) {
+    pub fn block_at_world_position(&self, (x, y, z): (i32, i32, i32)) -> Option<u8> {
         let (lx, lz) = ((x - self.world_position.0).abs(), (z - self.world_position.1).abs());
 
-        let index = Self::xyz_to_index(lx, y, lz);
+        Self::block_in_sections(&self.sections, lx, y, lz)
+    }
 
-        if index < (Chunk::WIDTH * Chunk::HEIGHT * Chunk::DEPTH) as usize {
-            self.blocks[index] = block.id;
-            self.mesh_generated = false;
+    pub fn block_in_sections(sections: &[ChunkSection], x: i32, y: i32, z: i32) -> Option<u8> {
+        if !Self::in_bounds(x, y, z) {
+            return None;
         }
+
+        sections
+            .get((y / Chunk::SECTION_HEIGHT) as usize)
+            .map(|s| s.get(x, y % Chunk::SECTION_HEIGHT, z))
     }
 
-    pub fn block_at_local_position(&self, (x, y, z): (i32, i32, i32)) -> Option<&u8> {
-        self.blocks.get(Self::xyz_to_index(x, y, z))
+    fn in_bounds(x: i32, y: i32, z: i32) -> bool {
+        (0..Chunk::WIDTH).contains(&x) && (0..Chunk::HEIGHT).contains(&y) && (0..Chunk::DEPTH).contains(&z)
     }
 
-    pub fn block_at_world_position(&self, (x, y, z): (i32, i32, i32)) -> Option<&u8> {
+    // Top of the highest block in any column; nothing is drawn above it.
+    pub fn filled_height(&self) -> i32 {
+        self.heightmap.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn is_solid_at_world_position(&self, (x, y, z): (i32, i32, i32)) -> bool {
         let (lx, lz) = ((x - self.world_position.0).abs(), (z - self.world_position.1).abs());
 
-        self.blocks.get(Self::xyz_to_index(lx, y, lz))
+        self.occupancy.is_solid(lx, y, lz)
     }
 
     pub fn local_to_world_position(local_position: (i32, i32)) -> (i32, i32) {
         (local_position.0 * Chunk::WIDTH, local_position.1 * Chunk::DEPTH)
     }
 
-    pub fn blocks(&self) -> &Vec<u8> {
-        &self.blocks
+    pub fn sections(&self) -> &Vec<ChunkSection> {
+        &self.sections
     }
 
-    pub fn left(&self) -> &Option<Vec<u8>> {
-        &self.left
+    // All blocks as one array, the layout generators produce and saves store.
+    pub fn blocks(&self) -> Vec<u8> {
+        self.sections.iter().flat_map(|s| s.blocks()).collect()
     }
 
-    pub fn set_left(&mut self, left: &Vec<u8>) {
-        self.left = Some(left.clone());
+    // Rough size in memory: blocks, occupancy, meshes and neighbor copies. GPU buffers are owned
+    // by the world and not counted.
+    pub fn block_memory_size(&self) -> usize {
+        self.sections.iter().map(|s| s.memory_size()).sum()
     }
 
-    pub fn clear_left(&mut self) {
-        self.left = None;
+    pub fn memory_size(&self) -> usize {
+        let sections = |s: &[ChunkSection]| s.iter().map(|s| s.memory_size()).sum::<usize>();
+        let neighbors = [&self.left, &self.right, &self.front, &self.back]
+            .iter()
+            .filter_map(|n| n.as_ref())
+            .map(|n| sections(n))
+            .sum::<usize>();
+        let meshes = self.meshes
+            .iter()
+            .map(|m| (m.vertices.capacity() + m.alpha_vertices.capacity()) * std::mem::size_of::<Vertex>())
+            .sum::<usize>();
+
+        let heightmap = self.heightmap.len() * std::mem::size_of::<i32>()
+            + self.terrain_surface.as_ref().map_or(0, |s| s.len() * std::mem::size_of::<(i32, u8)>());
+
+        std::mem::size_of::<Self>() + self.block_memory_size() + self.occupancy.memory_size() + heightmap + meshes + neighbors
     }
 
-    pub fn right(&self) -> &Option<Vec<u8>> {
-        &self.right
+    pub fn find_blocks(&self, id: u8) -> Vec<(i32, i32, i32)> {
+        self.blocks()
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b == id)
+            .map(|(i, _)| {
+                let (x, y, z) = Self::index_to_xyz(i);
+                (x + self.world_position.0, y, z + self.world_position.1)
+            })
+            .collect()
     }
 
-    pub fn set_right(&mut self, right: &Vec<u8>) {
-        self.right = Some(right.clone());
+    pub fn left(&self) -> &Option<Vec<ChunkSection>> {
+        &self.left
     }
 
-    pub fn clear_right(&mut self) {
-        self.right = None;
+    pub fn set_left(&mut self, left: &Vec<ChunkSection>) {
+        self.left = Some(left.clone());
     }
 
-    pub fn front(&self) -> &Option<Vec<u8>> {
-        &self.front
+    pub fn right(&self) -> &Option<Vec<ChunkSection>> {
+        &self.right
     }
 
-    pub fn set_front(&mut self, front: &Vec<u8>) {
-        self.front = Some(front.clone());
+    pub fn set_right(&mut self, right: &Vec<ChunkSection>) {
+        self.right = Some(right.clone());
     }
 
-    pub fn clear_front(&mut self) {
-        self.front = None;
+    pub fn front(&self) -> &Option<Vec<ChunkSection>> {
+        &self.front
     }
 
-    pub fn back(&self) -> &Option<Vec<u8>> {
+    pub fn set_front(&mut self, front: &Vec<ChunkSection>) {
+        self.front = Some(front.clone());
+    }
+
+    pub fn back(&self) -> &Option<Vec<ChunkSection>> {
         &self.back
     }
 
-    pub fn set_back(&mut self, back: &Vec<u8>) {
+    pub fn set_back(&mut self, back: &Vec<ChunkSection>) {
         self.back = Some(back.clone());
     }
 
-    pub fn clear_back(&mut self) {
-        self.back = None;
+    // Called when the neighbor `offset` chunks away changed: its border copy is replaced and the
+    // sections in the mask, the ones its change can reach, are remeshed. The copy is kept even
+    // while it's being replaced, so the chunk never waits on neighbor data it already had.
+    pub fn neighbor_changed(&mut self, offset: (i32, i32), neighbor: &Vec<ChunkSection>, sections: u64) {
+        match offset {
+            (-1, 0) => self.set_left(neighbor),
+            (1, 0) => self.set_right(neighbor),
+            (0, -1) => self.set_front(neighbor),
+            (0, 1) => self.set_back(neighbor),
+            _ => return,
+        }
+
+        self.invalidate(sections);
+    }
+
+    pub fn section_mesh(&self, section: i32) -> &ChunkMesh {
+        &self.meshes[section as usize]
+    }
+
+    // Sections meshed since the last call, as a bitmask.
+    pub fn take_updated_sections(&mut self) -> u64 {
+        std::mem::take(&mut self.updated_sections)
+    }
+
+    // Has every section's buffer rebuilt once meshed, e.g. after the buffers were dropped.
+    pub fn reupload_sections(&mut self) {
+        self.updated_sections = Self::ALL_SECTIONS & !self.dirty_sections;
+    }
+
+    // Remeshes the sections in the mask. A mesh job already running keeps going with the old
+    // blocks, and the sections are picked up by the next one.
+    pub fn invalidate(&mut self, sections: u64) {
+        self.dirty_sections |= sections & Self::ALL_SECTIONS;
+    }
+
+    pub fn invalidate_section(&mut self, section: i32) {
+        if (0..Chunk::SECTION_COUNT).contains(&section) {
+            self.invalidate(1 << section);
+        }
     }
 
-    pub fn mesh(&self) -> &ChunkMesh {
-        &self.mesh
+    pub fn invalidate_all(&mut self) {
+        self.invalidate(Self::ALL_SECTIONS);
     }
 
-    pub fn has_mesh(&self) -> bool {
-        self.mesh_generated
+    // Remeshes the whole chunk when its level of detail changes.
+    pub fn set_lod_scale(&mut self, scale: i32) {
+        if scale != self.lod_scale {
+            self.lod_scale = scale;
+            self.invalidate_all();
+        }
     }
 
     pub fn local_position(&self) -> (i32, i32) { self.local_position }
@@ -401,15 +565,49 @@ impl Chunk {
         self.world_position
     }
 
-    pub fn needs_buffer(&self) -> bool {
-        self.needs_buffer
+    pub fn is_decorated(&self) -> bool {
+        self.decorated
+    }
+
+    pub fn set_decorated(&mut self, decorated: bool) {
+        self.decorated = decorated;
+
+        if decorated {
+            self.terrain_surface = None;
+        }
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    pub fn set_modified(&mut self, modified: bool) {
+        self.modified = modified;
     }
 
-    pub fn set_needs_buffer(&mut self, needs_buffer: bool) {
-        self.needs_buffer = needs_buffer;
+    // Drops everything that can be rebuilt from the blocks and the neighbors, so an unloaded chunk
+    // only holds on to its blocks. A mesh job still queued or running is cancelled, and its channel
+    // replaced in case it already finished.
+    pub fn unload(&mut self) {
+        let (sender, receiver) = crossbeam::channel::unbounded();
+
+        self.mesh_cancelled.store(true, Ordering::Relaxed);
+        self.mesh_cancelled = Arc::new(AtomicBool::new(false));
+        self.sender = sender;
+        self.receiver = receiver;
+        self.meshes.iter_mut().for_each(|m| *m = ChunkMesh::new());
+        self.dirty_sections = Self::ALL_SECTIONS;
+        self.updated_sections = 0;
+        self.generating_mesh = false;
+        self.left = None;
+        self.right = None;
+        self.front = None;
+        self.back = None;
     }
+}
 
-    pub fn set_mesh_generated(&mut self, mesh_generated: bool) {
-        self.mesh_generated = mesh_generated;
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        self.mesh_cancelled.store(true, Ordering::Relaxed);
     }
 }
\ No newline at end of file
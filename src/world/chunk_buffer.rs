@@ -1,56 +1,46 @@
-use wgpu::{Buffer, Device};
-use wgpu::util::DeviceExt;
+use crate::world::chunk::Chunk;
 use crate::world::chunk_mesh::ChunkMesh;
+use crate::world::quad_index_buffer::QuadIndexBuffer;
 
+// Where a section's vertices live in the world's two `ChunkBufferPool`s, opaque and alpha.
+// Indices come from the shared `QuadIndexBuffer`, and the section's origin, which the packed
+// vertices are offset by, is bound as a per-instance attribute when it's drawn, see `ChunkDraws`.
 pub struct ChunkBuffer {
-    pub vertex_buffer: Buffer,
+    // Shown in GPU debuggers around this section's draws.
+    pub label: String,
+    pub origin: [f32; 4],
+
+    pub first_vertex: u32,
     pub vertex_count: u32,
-    pub index_buffer: Buffer,
     pub index_count: u32,
 
-    pub alpha_vertex_buffer: Buffer,
+    pub first_alpha_vertex: u32,
     pub alpha_vertex_count: u32,
-    pub alpha_index_buffer: Buffer,
     pub alpha_index_count: u32,
 }
 
 impl ChunkBuffer {
 
-    pub fn new(device: &Device, mesh: &ChunkMesh) -> Self {
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Block Vertex Buffer"),
-            contents: bytemuck::cast_slice(&mesh.vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Block Index Buffer"),
-            contents: bytemuck::cast_slice(&mesh.indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-
-        let alpha_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Block Vertex Buffer"),
-            contents: bytemuck::cast_slice(&mesh.alpha_vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-
-        let alpha_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Block Index Buffer"),
-            contents: bytemuck::cast_slice(&mesh.alpha_indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
+    pub fn new(mesh: &ChunkMesh, position: (i32, i32), section: i32, first_vertex: u32, first_alpha_vertex: u32) -> Self {
+        let label = if mesh.scale > 1 {
+            format!("Chunk {:?} section {} at 1/{}", position, section, mesh.scale)
+        } else {
+            format!("Chunk {:?} section {}", position, section)
+        };
+
+        let (x, z) = Chunk::local_to_world_position(position);
 
         Self {
-            vertex_buffer,
+            label,
+            origin: [x as f32, (section * Chunk::SECTION_HEIGHT) as f32, z as f32, 0.0],
+
+            first_vertex,
             vertex_count: mesh.vertices.len() as u32,
-            index_buffer,
-            index_count: mesh.indices.len() as u32,
+            index_count: QuadIndexBuffer::index_count(mesh.vertices.len() as u32),
 
-            alpha_vertex_buffer,
+            first_alpha_vertex,
             alpha_vertex_count: mesh.alpha_vertices.len() as u32,
-            alpha_index_buffer,
-            alpha_index_count: mesh.alpha_indices.len() as u32,
+            alpha_index_count: QuadIndexBuffer::index_count(mesh.alpha_vertices.len() as u32),
         }
     }
 
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use wgpu::{Buffer, BufferAddress, BufferSlice, Device, Queue};
+use crate::mesh::vertex::Vertex;
+
+// Vertices of every uploaded section of one pass, sub-allocated from a single buffer so they can
+// all be drawn from one binding. Ranges freed by remeshed or unloaded sections go back on a free
+// list, merged with free neighbors, and are handed out again first fit. Only when nothing fits
+// does the buffer grow, copied into one twice the size so every allocation keeps its place, up to
+// the device's largest buffer. The buffer is created by the first allocation, so worlds that are
+// never drawn don't need a device.
+pub struct ChunkBufferPool {
+    label: &'static str,
+    buffer: Option<Buffer>,
+    capacity: u32,
+    used: u32,
+    // Free ranges by first vertex, with their lengths.
+    free: BTreeMap<u32, u32>,
+}
+
+impl ChunkBufferPool {
+
+    pub const INITIAL_CAPACITY: u32 = 1 << 20;
+
+    const VERTEX_SIZE: BufferAddress = std::mem::size_of::<Vertex>() as BufferAddress;
+
+    pub fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            buffer: None,
+            capacity: 0,
+            used: 0,
+            free: BTreeMap::new(),
+        }
+    }
+
+    // First vertex of `count` consecutive ones for the caller to fill, or None when they don't fit
+    // even in the largest buffer the device allows.
+    pub fn allocate(&mut self, device: &Device, queue: &Queue, count: u32) -> Option<u32> {
+        if count == 0 {
+            return Some(0);
+        }
+
+        let (first, len) = match self.find(count) {
+            Some(fit) => fit,
+            None if self.grow(device, queue, count) => self.find(count)?,
+            None => return None,
+        };
+
+        self.free.remove(&first);
+
+        if len > count {
+            self.free.insert(first + count, len - count);
+        }
+
+        self.used += count;
+        Some(first)
+    }
+
+    pub fn free(&mut self, first: u32, count: u32) {
+        if count == 0 {
+            return;
+        }
+
+        self.used -= count;
+        self.insert_free(first, count);
+    }
+
+    pub fn clear(&mut self) {
+        self.used = 0;
+        self.free.clear();
+
+        if self.capacity > 0 {
+            self.free.insert(0, self.capacity);
+        }
+    }
+
+    pub fn write(&self, queue: &Queue, first: u32, vertices: &[Vertex]) {
+        if let Some(buffer) = self.buffer.as_ref().filter(|_| !vertices.is_empty()) {
+            queue.write_buffer(buffer, first as BufferAddress * Self::VERTEX_SIZE, bytemuck::cast_slice(vertices));
+        }
+    }
+
+    pub fn buffer(&self) -> Option<&Buffer> {
+        self.buffer.as_ref()
+    }
+
+    // Panics before the first allocation.
+    pub fn slice(&self, first: u32, count: u32) -> BufferSlice<'_> {
+        let start = first as BufferAddress * Self::VERTEX_SIZE;
+        self.buffer.as_ref().unwrap().slice(start..start + count as BufferAddress * Self::VERTEX_SIZE)
+    }
+
+    pub fn used_bytes(&self) -> u64 {
+        self.used as u64 * Self::VERTEX_SIZE
+    }
+
+    pub fn capacity_bytes(&self) -> u64 {
+        self.capacity as u64 * Self::VERTEX_SIZE
+    }
+
+    fn find(&self, count: u32) -> Option<(u32, u32)> {
+        self.free.iter().find(|(_, len)| **len >= count).map(|(first, len)| (*first, *len))
+    }
+
+    // Doubles the buffer until `count` more vertices fit at its end, false if they wouldn't fit
+    // in the largest buffer the device allows.
+    fn grow(&mut self, device: &Device, queue: &Queue, count: u32) -> bool {
+        let limit = (device.limits().max_buffer_size / Self::VERTEX_SIZE).min(u32::MAX as u64);
+        let tail = self.free
+            .iter()
+            .next_back()
+            .filter(|(first, len)| *first + *len == self.capacity)
+            .map_or(0, |(_, len)| *len);
+
+        let old_capacity = self.capacity as u64;
+        let mut capacity = if old_capacity == 0 { Self::INITIAL_CAPACITY as u64 } else { old_capacity * 2 };
+
+        while capacity - old_capacity + (tail as u64) < count as u64 && capacity < limit {
+            capacity *= 2;
+        }
+
+        let capacity = capacity.min(limit);
+
+        if capacity <= old_capacity || capacity - old_capacity + (tail as u64) < count as u64 {
+            return false;
+        }
+
+        let capacity = capacity as u32;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(self.label),
+            size: capacity as BufferAddress * Self::VERTEX_SIZE,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        if let Some(old) = &self.buffer {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Chunk Buffer Pool Grow Encoder"),
+            });
+
+            encoder.copy_buffer_to_buffer(old, 0, &buffer, 0, self.capacity as BufferAddress * Self::VERTEX_SIZE);
+            queue.submit(std::iter::once(encoder.finish()));
+        }
+
+        let old_capacity = self.capacity;
+
+        self.buffer = Some(buffer);
+        self.capacity = capacity;
+        self.insert_free(old_capacity, capacity - old_capacity);
+        true
+    }
+
+    // Merged with the free ranges right before and after it.
+    fn insert_free(&mut self, mut first: u32, mut count: u32) {
+        if let Some((&before, &len)) = self.free.range(..first).next_back() {
+            if before + len == first {
+                self.free.remove(&before);
+                first = before;
+                count += len;
+            }
+        }
+
+        if let Some(len) = self.free.remove(&(first + count)) {
+            count += len;
+        }
+
+        self.free.insert(first, count);
+    }
+
+}
\ No newline at end of file
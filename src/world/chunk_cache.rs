@@ -0,0 +1,113 @@
+use std::collections::{HashMap, VecDeque};
+use crate::world::chunk::Chunk;
+
+// Chunks evicted from the world, kept so that turning back around doesn't read them from disk or
+// regenerate them. Once the chunks' estimated size passes the memory ceiling, the chunk that was
+// evicted longest ago is dropped. A ceiling of zero disables the cache.
+pub struct ChunkCache {
+    max_bytes: usize,
+    bytes: usize,
+    chunks: HashMap<(i32, i32), (Chunk, usize)>,
+    order: VecDeque<(i32, i32)>,
+
+    hits: u64,
+    misses: u64,
+}
+
+impl ChunkCache {
+
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            bytes: 0,
+            chunks: HashMap::new(),
+            order: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn insert(&mut self, chunk: Chunk) {
+        if self.max_bytes == 0 {
+            return;
+        }
+
+        let position = chunk.local_position();
+        let size = chunk.memory_size();
+
+        if let Some((_, previous)) = self.chunks.insert(position, (chunk, size)) {
+            self.bytes -= previous;
+            self.order.retain(|p| *p != position);
+        }
+
+        self.bytes += size;
+        self.order.push_back(position);
+        self.shrink();
+    }
+
+    // Counts a hit or a miss, so every lookup before loading a chunk should go through here.
+    pub fn take(&mut self, position: (i32, i32)) -> Option<Chunk> {
+        let (chunk, size) = if let Some(entry) = self.chunks.remove(&position) {
+            entry
+        } else {
+            self.misses += 1;
+            return None;
+        };
+
+        self.order.retain(|p| *p != position);
+        self.bytes -= size;
+        self.hits += 1;
+
+        Some(chunk)
+    }
+
+    pub fn contains(&self, position: (i32, i32)) -> bool {
+        self.chunks.contains_key(&position)
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.shrink();
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.order.clear();
+        self.bytes = 0;
+    }
+
+    fn shrink(&mut self) {
+        while self.bytes > self.max_bytes {
+            let oldest = if let Some(oldest) = self.order.pop_front() { oldest } else { break };
+
+            if let Some((_, size)) = self.chunks.remove(&oldest) {
+                self.bytes -= size;
+            }
+        }
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,129 @@
+use wgpu::{Buffer, BufferAddress, BufferSlice, Device, Queue};
+use wgpu::util::DrawIndexedIndirect;
+use crate::world::chunk_buffer::ChunkBuffer;
+
+// This frame's visible sections as draw arguments into the vertex pools, one per section and
+// pass. Section i's origin is the i-th per-instance attribute and its draws start at instance i,
+// so with MULTI_DRAW_INDIRECT each pass is a single call. Without it, sections are drawn one by one
+// from the same arguments.
+pub struct ChunkDraws {
+    multi_draw: bool,
+    capacity: u32,
+
+    origins: Buffer,
+    indirect_buffer: Buffer,
+    alpha_indirect_buffer: Buffer,
+
+    draws: Vec<DrawIndexedIndirect>,
+    alpha_draws: Vec<DrawIndexedIndirect>,
+    // Only kept for the one by one draws, the single call can't be annotated per section.
+    labels: Vec<String>,
+}
+
+impl ChunkDraws {
+
+    pub const FEATURES: wgpu::Features = wgpu::Features::MULTI_DRAW_INDIRECT.union(wgpu::Features::INDIRECT_FIRST_INSTANCE);
+
+    const INITIAL_CAPACITY: u32 = 1024;
+    const ORIGIN_SIZE: BufferAddress = std::mem::size_of::<[f32; 4]>() as BufferAddress;
+    const DRAW_SIZE: BufferAddress = std::mem::size_of::<DrawIndexedIndirect>() as BufferAddress;
+
+    pub fn new(device: &Device) -> Self {
+        let (origins, indirect_buffer, alpha_indirect_buffer) = Self::create_buffers(device, Self::INITIAL_CAPACITY);
+
+        Self {
+            multi_draw: device.features().contains(Self::FEATURES),
+            capacity: Self::INITIAL_CAPACITY,
+            origins,
+            indirect_buffer,
+            alpha_indirect_buffer,
+            draws: vec![],
+            alpha_draws: vec![],
+            labels: vec![],
+        }
+    }
+
+    pub fn update(&mut self, device: &Device, queue: &Queue, buffers: &[&ChunkBuffer]) {
+        let count = buffers.len() as u32;
+
+        if count > self.capacity {
+            self.capacity = count.next_power_of_two();
+            (self.origins, self.indirect_buffer, self.alpha_indirect_buffer) = Self::create_buffers(device, self.capacity);
+        }
+
+        let draw = |first: u32, index_count: u32, instance: u32| DrawIndexedIndirect {
+            vertex_count: index_count,
+            instance_count: 1,
+            base_index: 0,
+            vertex_offset: first as i32,
+            base_instance: instance,
+        };
+
+        self.draws = buffers.iter().zip(0..).map(|(b, i)| draw(b.first_vertex, b.index_count, i)).collect();
+        self.alpha_draws = buffers.iter().zip(0..).map(|(b, i)| draw(b.first_alpha_vertex, b.alpha_index_count, i)).collect();
+
+        self.labels.clear();
+
+        if !self.multi_draw {
+            self.labels.extend(buffers.iter().map(|b| b.label.clone()));
+        }
+
+        if buffers.is_empty() {
+            return;
+        }
+
+        let origins = buffers.iter().map(|b| b.origin).collect::<Vec<_>>();
+        queue.write_buffer(&self.origins, 0, bytemuck::cast_slice(&origins));
+        queue.write_buffer(&self.indirect_buffer, 0, &Self::contents(&self.draws));
+        queue.write_buffer(&self.alpha_indirect_buffer, 0, &Self::contents(&self.alpha_draws));
+    }
+
+    pub fn multi_draw(&self) -> bool {
+        self.multi_draw
+    }
+
+    pub fn origins(&self) -> &Buffer {
+        &self.origins
+    }
+
+    // Just section i's origin, for drawing it on its own at instance 0.
+    pub fn origin(&self, i: u32) -> BufferSlice<'_> {
+        let start = i as BufferAddress * Self::ORIGIN_SIZE;
+        self.origins.slice(start..start + Self::ORIGIN_SIZE)
+    }
+
+    pub fn indirect_buffer(&self, alpha: bool) -> &Buffer {
+        if alpha { &self.alpha_indirect_buffer } else { &self.indirect_buffer }
+    }
+
+    pub fn draws(&self, alpha: bool) -> &[DrawIndexedIndirect] {
+        if alpha { &self.alpha_draws } else { &self.draws }
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn contents(draws: &[DrawIndexedIndirect]) -> Vec<u8> {
+        draws.iter().flat_map(|d| d.as_bytes()).copied().collect()
+    }
+
+    fn create_buffers(device: &Device, capacity: u32) -> (Buffer, Buffer, Buffer) {
+        let origins = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Chunk Origin Buffer"),
+            size: capacity as BufferAddress * Self::ORIGIN_SIZE,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let indirect = |label| device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: capacity as BufferAddress * Self::DRAW_SIZE,
+            usage: wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        (origins, indirect("Chunk Indirect Buffer"), indirect("Chunk Alpha Indirect Buffer"))
+    }
+
+}
\ No newline at end of file
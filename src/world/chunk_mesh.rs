@@ -1,9 +1,36 @@
 use crate::mesh::vertex::Vertex;
+use crate::world::occlusion::SectionVisibility;
 
+// Quads only, four vertices each; they're drawn with the shared `QuadIndexBuffer`.
+#[derive(Clone)]
 pub struct ChunkMesh {
     pub vertices: Vec<Vertex>,
-    pub indices: Vec<u32>,
-
     pub alpha_vertices: Vec<Vertex>,
-    pub alpha_indices: Vec<u32>,
-}
\ No newline at end of file
+    // Blocks per cell along each axis; see `lod`.
+    pub scale: i32,
+    // Built alongside the mesh, so it's as current as what's drawn; see `World::visible_sections`.
+    pub visibility: SectionVisibility,
+}
+
+impl Default for ChunkMesh {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkMesh {
+
+    pub fn new() -> Self {
+        Self {
+            vertices: vec![],
+            alpha_vertices: vec![],
+            scale: 1,
+            visibility: SectionVisibility::ALL,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty() && self.alpha_vertices.is_empty()
+    }
+
+}
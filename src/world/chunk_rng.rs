@@ -0,0 +1,72 @@
+use std::ops::Range;
+
+// SplitMix64 seeded from the world seed, a chunk or column position and a salt, so every
+// feature that rolls for one sees the same numbers no matter which worker thread built it or when.
+pub struct ChunkRng {
+    state: u64,
+}
+
+impl ChunkRng {
+
+    pub fn new(seed: u32, (x, z): (i32, i32), salt: u64) -> Self {
+        let position = ((x as u32 as u64) << 32) | z as u32 as u64;
+
+        let mut rng = Self {
+            state: (seed as u64)
+                ^ position.wrapping_mul(0x9e3779b97f4a7c15)
+                ^ salt.wrapping_mul(0xd1b54a32d192ed03),
+        };
+
+        rng.next_u64();
+        rng
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    pub fn range(&mut self, range: Range<i32>) -> i32 {
+        let span = (range.end - range.start).max(1) as u32;
+        range.start + (self.next_u32() % span) as i32
+    }
+
+    // True once in every `n` rolls on average; `n == 0` never succeeds.
+    pub fn one_in(&mut self, n: u32) -> bool {
+        n != 0 && self.next_u32().is_multiple_of(n)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkRng;
+
+    fn sequence(seed: u32, position: (i32, i32), salt: u64) -> Vec<u64> {
+        let mut rng = ChunkRng::new(seed, position, salt);
+        (0..16).map(|_| rng.next_u64()).collect()
+    }
+
+    #[test]
+    fn same_inputs_give_the_same_sequence() {
+        assert_eq!(sequence(42, (3, -7), 1), sequence(42, (3, -7), 1));
+    }
+
+    #[test]
+    fn different_salts_give_different_sequences() {
+        let sequences = (0..8).map(|salt| sequence(42, (3, -7), salt)).collect::<Vec<_>>();
+
+        for (i, a) in sequences.iter().enumerate() {
+            assert!(sequences[i + 1..].iter().all(|b| a != b));
+        }
+    }
+}
\ No newline at end of file
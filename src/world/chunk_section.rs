@@ -0,0 +1,63 @@
+use crate::objects::block::Block;
+use crate::world::block_storage::BlockStorage;
+use crate::world::chunk::Chunk;
+
+// A 16 block tall slice of a chunk column. Sections keep a count of their non-air blocks so
+// empty ones, which is most of a tall column, can be skipped without looking at their blocks.
+#[derive(Clone)]
+pub struct ChunkSection {
+    blocks: BlockStorage,
+    filled: u32,
+}
+
+impl ChunkSection {
+
+    pub fn from_blocks(blocks: &[u8]) -> Self {
+        Self {
+            blocks: BlockStorage::from_blocks(blocks),
+            filled: blocks.iter().filter(|b| **b != Block::Air.id).count() as u32,
+        }
+    }
+
+    // Splits blocks laid out as `Chunk::xyz_to_index` expects into sections, bottom first.
+    pub fn split(blocks: &[u8]) -> Vec<ChunkSection> {
+        blocks
+            .chunks(Chunk::SECTION_SIZE as usize)
+            .map(Self::from_blocks)
+            .collect()
+    }
+
+    pub fn get(&self, x: i32, y: i32, z: i32) -> u8 {
+        self.blocks.get(Self::xyz_to_index(x, y, z))
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, z: i32, id: u8) {
+        let index = Self::xyz_to_index(x, y, z);
+
+        match (self.blocks.get(index) == Block::Air.id, id == Block::Air.id) {
+            (true, false) => self.filled += 1,
+            (false, true) => self.filled -= 1,
+            _ => {},
+        }
+
+        self.blocks.set(index, id);
+    }
+
+    pub fn blocks(&self) -> Vec<u8> {
+        self.blocks.to_vec()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filled == 0
+    }
+
+    pub fn memory_size(&self) -> usize {
+        self.blocks.memory_size() + std::mem::size_of::<u32>()
+    }
+
+    // `y` is relative to the bottom of the section.
+    pub fn xyz_to_index(x: i32, y: i32, z: i32) -> usize {
+        (x + z * Chunk::WIDTH + y * Chunk::WIDTH * Chunk::DEPTH) as usize
+    }
+
+}
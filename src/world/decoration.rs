@@ -0,0 +1,223 @@
+use noise::{NoiseFn, Perlin};
+use crate::objects::block::Block;
+use crate::world::chunk::Chunk;
+use crate::world::chunk_rng::ChunkRng;
+
+const SALT: u64 = 1;
+const CAVE_SALT: u64 = 2;
+const CAVE_FREQUENCY: f64 = 0.03;
+const CAVE_THRESHOLD: f64 = 0.25;
+
+pub struct Placement {
+    pub position: (i32, i32, i32),
+    pub block: Block,
+    pub replace: bool,
+}
+
+// Features are rooted inside the chunk but may reach into its neighbors, so placements are
+// returned in world coordinates and applied by the world. Neighbors decorate in whatever order
+// their jobs finish, so every column rolls with its own generator, seeded by its world position,
+// on the surface as it was generated.
+pub fn decorate(chunk: &Chunk, seed: u32) -> Vec<Placement> {
+    let mut placements = vec![];
+    let (wx, wz) = chunk.world_position();
+
+    for x in 0..Chunk::WIDTH {
+        for z in 0..Chunk::DEPTH {
+            let y = match chunk.terrain_surface(x, z) {
+                Some((y, id)) if id == Block::Grass.id => y,
+                _ => continue,
+            };
+
+            let (x, z) = (x + wx, z + wz);
+            let mut rng = ChunkRng::new(seed, (x, z), SALT);
+            let roll = rng.next_u32();
+
+            if roll.is_multiple_of(61) {
+                tree(&mut placements, (x, y + 1, z), &mut rng);
+            } else if roll % 397 == 1 {
+                boulder(&mut placements, (x, y + 1, z), &mut rng);
+            }
+        }
+    }
+
+    caves(&mut placements, chunk, seed);
+
+    placements
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum CaveBiome {
+    Plain,
+    Mushrooms,
+    Crystals,
+}
+
+// Caves are split into biomes by a 3D noise, so a single cave system can run from plain stone
+// into a mushroom grove or a crystal hollow. Both blocks are bright in the atlas and the block
+// shaders are unlit, which is what makes them read as glowing in the dark.
+fn caves(placements: &mut Vec<Placement>, chunk: &Chunk, seed: u32) {
+    let (wx, wz) = chunk.world_position();
+    let noise = Perlin::new(seed.wrapping_add(6));
+
+    let air = |x, y, z| chunk.block_at_local_position((x, y, z)) == Some(Block::Air.id);
+    let rock = |x, y, z| chunk.block_at_local_position((x, y, z)) == Some(Block::Stone.id);
+
+    for x in 0..Chunk::WIDTH {
+        for z in 0..Chunk::DEPTH {
+            // Open air near the surface is a ravine or an entrance rather than a cave.
+            let top = if let Some((y, _)) = chunk.terrain_surface(x, z) { y - 4 } else { continue };
+            let mut rng = ChunkRng::new(seed, (x + wx, z + wz), CAVE_SALT);
+
+            for y in 1..top {
+                if !air(x, y, z) {
+                    continue;
+                }
+
+                let floor = rock(x, y - 1, z);
+                let ceiling = rock(x, y + 1, z);
+
+                if !floor && !ceiling {
+                    continue;
+                }
+
+                let (x, z) = (x + wx, z + wz);
+
+                match cave_biome(&noise, (x, y, z)) {
+                    CaveBiome::Plain => {},
+                    CaveBiome::Mushrooms => {
+                        if floor && rng.one_in(7) {
+                            mushroom(placements, (x, y, z), &mut rng);
+                        }
+                    },
+                    CaveBiome::Crystals => {
+                        if floor && rng.one_in(13) {
+                            crystal(placements, (x, y, z), 1, &mut rng);
+                        } else if ceiling && rng.one_in(11) {
+                            crystal(placements, (x, y, z), -1, &mut rng);
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+fn cave_biome(noise: &Perlin, (x, y, z): (i32, i32, i32)) -> CaveBiome {
+    let value = noise.get([
+        x as f64 * CAVE_FREQUENCY,
+        y as f64 * CAVE_FREQUENCY * 1.5,
+        z as f64 * CAVE_FREQUENCY,
+    ]);
+
+    if value > CAVE_THRESHOLD {
+        CaveBiome::Mushrooms
+    } else if value < -CAVE_THRESHOLD {
+        CaveBiome::Crystals
+    } else {
+        CaveBiome::Plain
+    }
+}
+
+fn mushroom(placements: &mut Vec<Placement>, (x, y, z): (i32, i32, i32), rng: &mut ChunkRng) {
+    let height = if rng.one_in(4) { 2 } else { 1 };
+
+    for dy in 0..height {
+        placements.push(Placement { position: (x, y + dy, z), block: Block::GlowMushroom, replace: false });
+    }
+}
+
+// `direction` is 1 for a crystal growing up from the floor and -1 for one hanging from the ceiling.
+fn crystal(placements: &mut Vec<Placement>, (x, y, z): (i32, i32, i32), direction: i32, rng: &mut ChunkRng) {
+    let height = rng.range(1..4);
+
+    for dy in 0..height {
+        placements.push(Placement { position: (x, y + dy * direction, z), block: Block::Crystal, replace: false });
+    }
+}
+
+fn tree(placements: &mut Vec<Placement>, (x, y, z): (i32, i32, i32), rng: &mut ChunkRng) {
+    let height = rng.range(4..7);
+
+    // Placements only ever fill air, so the trunk has to come before the canopy around it.
+    for dy in 0..height {
+        placements.push(Placement { position: (x, y + dy, z), block: Block::Wood, replace: false });
+    }
+
+    for dy in (height - 2)..(height + 2) {
+        let radius: i32 = if dy < height { 2 } else { 1 };
+
+        for dx in -radius..=radius {
+            for dz in -radius..=radius {
+                let corner = dx.abs() == radius && dz.abs() == radius;
+
+                if corner && (dy >= height || rng.one_in(2)) {
+                    continue;
+                }
+
+                placements.push(Placement { position: (x + dx, y + dy, z + dz), block: Block::Leaves, replace: false });
+            }
+        }
+    }
+}
+
+fn boulder(placements: &mut Vec<Placement>, (x, y, z): (i32, i32, i32), rng: &mut ChunkRng) {
+    let radius = rng.range(1..3);
+
+    for dx in -radius..=radius {
+        for dy in -1..=radius {
+            for dz in -radius..=radius {
+                if dx * dx + dy * dy + dz * dz <= radius * radius {
+                    placements.push(Placement { position: (x + dx, y + dy, z + dz), block: Block::Stone, replace: false });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::block::Block;
+    use crate::world::chunk::Chunk;
+    use crate::world::terrain_generator::TerrainGenerator;
+
+    struct Flat;
+
+    impl TerrainGenerator for Flat {
+        fn generate(&self, _: (i32, i32)) -> Vec<u8> {
+            let mut blocks = vec![Block::Air.id; Chunk::SIZE as usize];
+            let layer = (Chunk::WIDTH * Chunk::DEPTH) as usize;
+
+            blocks[..layer * 60].fill(Block::Stone.id);
+            blocks[layer * 60..layer * 61].fill(Block::Grass.id);
+            blocks
+        }
+
+        fn seed(&self) -> u32 {
+            7
+        }
+    }
+
+    fn placements(chunk: &Chunk) -> Vec<((i32, i32, i32), u8)> {
+        super::decorate(chunk, 7).into_iter().map(|p| (p.position, p.block.id)).collect()
+    }
+
+    #[test]
+    fn spills_from_neighbors_dont_change_decoration() {
+        let position = (3, -2);
+        let before = placements(&Chunk::new(position, &Flat));
+        assert!(!before.is_empty());
+
+        // A neighbor decorated first and covered every column of this one.
+        let mut chunk = Chunk::new(position, &Flat);
+        let (wx, wz) = chunk.world_position();
+
+        for x in 0..Chunk::WIDTH {
+            for z in 0..Chunk::DEPTH {
+                chunk.place_block_at_world_position(&Block::Leaves, (wx + x, 61, wz + z));
+            }
+        }
+
+        assert_eq!(placements(&chunk), before);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,58 @@
+use std::collections::HashSet;
+
+// Chunks the next update has something to do for: a mesh to start or pick up, border copies to
+// fill in, or decoration that may have become possible. Loads, edits and neighbor changes add
+// chunks here, so an update visits those rather than the whole render area.
+pub struct DirtySet {
+    chunks: HashSet<(i32, i32)>,
+}
+
+impl Default for DirtySet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirtySet {
+
+    pub fn new() -> Self {
+        Self {
+            chunks: HashSet::new(),
+        }
+    }
+
+    pub fn insert(&mut self, position: (i32, i32)) {
+        self.chunks.insert(position);
+    }
+
+    // The chunk and the eight around it, every chunk whose neighborhood changes when it loads.
+    pub fn insert_around(&mut self, (x, z): (i32, i32)) {
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                self.chunks.insert((x + dx, z + dz));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, position: (i32, i32)) {
+        self.chunks.remove(&position);
+    }
+
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    // Empties the set; chunks with work left are inserted again as they're visited.
+    pub fn take(&mut self) -> Vec<(i32, i32)> {
+        self.chunks.drain().collect()
+    }
+
+}
\ No newline at end of file
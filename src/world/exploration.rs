@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use anyhow::*;
+use crate::world::file_format;
+
+// Which chunks have ever been generated or loaded while playing a world, one bit per chunk in
+// rows of REGION_SIZE bits, grouped by region like `RegionStorage`. Saved next to the world's
+// regions so the map can tell explored terrain from the rest. A map without a path is never
+// saved.
+//
+// Layout, little endian: magic, version, region count, then per region its position and its rows.
+pub struct ExplorationMap {
+    path: Option<PathBuf>,
+    regions: HashMap<(i32, i32), [u32; ExplorationMap::REGION_SIZE as usize]>,
+    dirty: bool,
+}
+
+impl ExplorationMap {
+
+    pub const REGION_SIZE: i32 = 32;
+    pub const FILE: &'static str = "explored.bin";
+    pub const MAGIC: &'static [u8; 4] = b"WVEX";
+    pub const VERSION: u32 = 1;
+
+    // Starts out empty when the world has no saved map yet.
+    pub fn load<P: Into<PathBuf>>(dir: P) -> Result<Self> {
+        let path = dir.into().join(Self::FILE);
+        let mut map = Self { path: Some(path.clone()), regions: HashMap::new(), dirty: false };
+
+        if path.exists() {
+            let data = std::fs::read(&path).with_context(|| format!("failed to read exploration map {:?}", path))?;
+            map.read(&data).with_context(|| format!("failed to read exploration map {:?}", path))?;
+        }
+
+        Ok(map)
+    }
+
+    // Loads the map, or starts an empty one in the same place if it can't be read. The unreadable
+    // file is moved aside first, like a corrupt region, so saving doesn't overwrite it; if even
+    // that fails the new map is never saved.
+    pub fn open<P: Into<PathBuf>>(dir: P) -> Self {
+        let dir = dir.into();
+        let path = dir.join(Self::FILE);
+
+        Self::load(&dir).unwrap_or_else(|e| {
+            let aside = path.with_extension("bin.corrupt");
+            eprintln!("{:?}", e.context(format!("exploration map {:?} is unreadable, moving it to {:?}", path, aside)));
+
+            if let Err(e) = std::fs::rename(&path, &aside) {
+                eprintln!("{:?}", e);
+                return Self::in_memory();
+            }
+
+            Self { path: Some(path), regions: HashMap::new(), dirty: false }
+        })
+    }
+
+    pub fn in_memory() -> Self {
+        Self { path: None, regions: HashMap::new(), dirty: false }
+    }
+
+    pub fn mark(&mut self, position: (i32, i32)) {
+        let (region, row, bit) = Self::locate(position);
+        let rows = self.regions.entry(region).or_insert([0; Self::REGION_SIZE as usize]);
+
+        if rows[row] & bit == 0 {
+            rows[row] |= bit;
+            self.dirty = true;
+        }
+    }
+
+    pub fn is_explored(&self, position: (i32, i32)) -> bool {
+        let (region, row, bit) = Self::locate(position);
+        self.regions.get(&region).is_some_and(|rows| rows[row] & bit != 0)
+    }
+
+    // Explored chunks out of every chunk within `radius` chunks of the origin, the area
+    // `World::render_map` draws.
+    pub fn explored_within(&self, radius: i32) -> (usize, usize) {
+        let explored = (-radius..radius)
+            .flat_map(|z| (-radius..radius).map(move |x| (x, z)))
+            .filter(|p| self.is_explored(*p))
+            .count();
+
+        (explored, (radius * 2).pow(2) as usize)
+    }
+
+    // Writes the map if anything was explored since it was last saved.
+    pub fn save(&mut self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) if self.dirty => path,
+            _ => return Ok(()),
+        };
+
+        let mut data = file_format::header(Self::MAGIC, Self::VERSION);
+        data.extend_from_slice(&(self.regions.len() as u32).to_le_bytes());
+
+        for ((x, z), rows) in self.regions.iter() {
+            data.extend_from_slice(&x.to_le_bytes());
+            data.extend_from_slice(&z.to_le_bytes());
+            rows.iter().for_each(|row| data.extend_from_slice(&row.to_le_bytes()));
+        }
+
+        file_format::write(path, &data)?;
+
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    fn read(&mut self, data: &[u8]) -> Result<()> {
+        let mut reader = data;
+
+        file_format::read_header(&mut reader, Self::MAGIC, Self::VERSION)?;
+
+        for _ in 0..file_format::read_u32(&mut reader)? {
+            let x = file_format::read_u32(&mut reader)? as i32;
+            let z = file_format::read_u32(&mut reader)? as i32;
+
+            let mut rows = [0; Self::REGION_SIZE as usize];
+
+            for row in rows.iter_mut() {
+                *row = file_format::read_u32(&mut reader)?;
+            }
+
+            self.regions.insert((x, z), rows);
+        }
+
+        Ok(())
+    }
+
+    // The chunk's region, its row in the region and its bit in the row.
+    fn locate((x, z): (i32, i32)) -> ((i32, i32), usize, u32) {
+        let region = (x.div_euclid(Self::REGION_SIZE), z.div_euclid(Self::REGION_SIZE));
+        let (row, column) = (z.rem_euclid(Self::REGION_SIZE), x.rem_euclid(Self::REGION_SIZE));
+
+        (region, row as usize, 1 << column)
+    }
+
+}
\ No newline at end of file
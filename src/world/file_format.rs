@@ -0,0 +1,53 @@
+use std::io::Read;
+use std::path::Path;
+use anyhow::*;
+
+// What the world's binary save files have in common: a four byte magic and a version up front,
+// integers little endian, and writes that can't leave a file half written.
+
+// The start of a file's data.
+pub fn header(magic: &[u8; 4], version: u32) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(magic);
+    data.extend_from_slice(&version.to_le_bytes());
+    data
+}
+
+// Checks the header and moves `reader` past it.
+pub fn read_header(reader: &mut &[u8], magic: &[u8; 4], version: u32) -> Result<()> {
+    let mut found = [0; 4];
+    reader.read_exact(&mut found)?;
+
+    if &found != magic {
+        bail!("wrong magic {:?}, expected {:?}", String::from_utf8_lossy(&found), String::from_utf8_lossy(magic));
+    }
+
+    let found = read_u32(reader)?;
+
+    if found != version {
+        bail!("unsupported version {}, expected {}", found, version);
+    }
+
+    Ok(())
+}
+
+pub fn read_u32(reader: &mut &[u8]) -> Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u32::from_le_bytes(bytes))
+}
+
+// Written next to the file first and then moved over it, so a crash mid-write can't truncate what
+// was saved before.
+pub fn write(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let temp = path.with_extension("tmp");
+    std::fs::write(&temp, data)?;
+    std::fs::rename(&temp, path)?;
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,99 @@
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use rayon::prelude::*;
+use crate::objects::block::Block;
+use crate::world::chunk::Chunk;
+use crate::world::terrain_generator::TerrainGenerator;
+use crate::world::worldgen_config::WorldgenConfig;
+
+pub struct FloatingIslandsGenerator {
+    noise: Fbm<Perlin>,
+    seed: u32,
+}
+
+impl FloatingIslandsGenerator {
+
+    pub const CENTER: i32 = 110;
+    pub const HALF_THICKNESS: i32 = 48;
+    pub const THRESHOLD: f64 = 0.3;
+    pub const DIRT_DEPTH: i32 = 3;
+
+    pub fn new(config: &WorldgenConfig) -> Self {
+        let noise = Fbm::new(config.seed)
+            .set_octaves(3)
+            .set_frequency(0.03)
+            .set_persistence(0.5)
+            .set_lacunarity(2.0);
+
+        Self { noise, seed: config.seed }
+    }
+
+    // Density falls off towards the top and bottom of the island band, so the noise only clears
+    // the threshold near its middle and islands taper into rounded undersides.
+    fn is_solid(&self, (x, y, z): (i32, i32, i32)) -> bool {
+        let falloff = (y - Self::CENTER) as f64 / Self::HALF_THICKNESS as f64;
+
+        if falloff.abs() >= 1.0 {
+            return false;
+        }
+
+        let density = self.noise.get([x as f64, y as f64 * 1.5, z as f64]);
+
+        density - falloff * falloff > Self::THRESHOLD
+    }
+
+}
+
+impl TerrainGenerator for FloatingIslandsGenerator {
+
+    fn generate(&self, chunk_position: (i32, i32)) -> Vec<u8> {
+        let world_position = Chunk::local_to_world_position(chunk_position);
+        let mut blocks = vec![Block::Air.id; Chunk::SIZE as usize];
+
+        blocks
+            .par_chunks_mut(Chunk::SECTION_SIZE as usize)
+            .enumerate()
+            .for_each(|(section, blocks)| {
+                let offset = section * Chunk::SECTION_SIZE as usize;
+
+                for (i, block) in blocks.iter_mut().enumerate() {
+                    let (x, y, z) = Chunk::index_to_xyz(offset + i);
+
+                    if self.is_solid((x + world_position.0, y, z + world_position.1)) {
+                        *block = Block::Stone.id;
+                    }
+                }
+            });
+
+        // Islands can stack, so every exposed top gets its own grass and dirt, counted from the
+        // nearest air above rather than from the column's highest block.
+        for x in 0..Chunk::WIDTH {
+            for z in 0..Chunk::DEPTH {
+                let mut depth = -1;
+
+                for y in (0..Chunk::HEIGHT).rev() {
+                    let index = Chunk::xyz_to_index(x, y, z);
+
+                    if blocks[index] == Block::Air.id {
+                        depth = -1;
+                        continue;
+                    }
+
+                    depth += 1;
+
+                    if depth == 0 {
+                        blocks[index] = Block::Grass.id;
+                    } else if depth <= Self::DIRT_DEPTH {
+                        blocks[index] = Block::Dirt.id;
+                    }
+                }
+            }
+        }
+
+        blocks
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+
+}
@@ -0,0 +1,114 @@
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin, RidgedMulti};
+use crate::world::biome::Biome;
+use crate::world::worldgen_config::WorldgenConfig;
+
+// The terrain's surface height: shared height noise, scaled and offset by a blend of the biomes
+// around the column, with rivers carved into it.
+pub struct HeightField {
+    noise: Fbm<Perlin>,
+    river_noise: RidgedMulti<Perlin>,
+    climate_noise: Fbm<Perlin>,
+
+    config: WorldgenConfig,
+}
+
+impl HeightField {
+
+    pub const RIVER_DEPTH: i32 = 4;
+    pub const RIVER_THRESHOLD: f64 = 0.8;
+    pub const BIOME_BLEND_RADIUS: i32 = 12;
+    pub const BIOME_BLEND_STEP: i32 = 4;
+
+    pub fn new(config: &WorldgenConfig) -> Self {
+        let noise = Fbm::new(config.seed)
+            .set_octaves(config.octaves)
+            .set_frequency(config.frequency)
+            .set_persistence(config.persistence)
+            .set_lacunarity(config.lacunarity);
+
+        let river_noise = RidgedMulti::new(config.seed.wrapping_add(3))
+            .set_octaves(1)
+            .set_frequency(0.004);
+
+        let climate_noise = Fbm::new(config.seed.wrapping_add(5))
+            .set_octaves(2)
+            .set_frequency(0.003)
+            .set_persistence(0.5)
+            .set_lacunarity(2.0);
+
+        Self {
+            noise,
+            river_noise,
+            climate_noise,
+            config: config.clone(),
+        }
+    }
+
+    pub fn biome(&self, x: i32, z: i32) -> Biome {
+        Biome::from_climate(self.climate_noise.get([x as f64 + 0.01, z as f64 + 0.01]))
+    }
+
+    // Heights of the `width` by `depth` columns from `origin`, row by row along x.
+    //
+    // Each biome only sets how high and how rough the terrain is. Averaging the profiles of the
+    // biomes on a grid around the column turns every biome edge into a slope about twice the blend
+    // radius wide instead of a cliff. Neighboring columns share most of their grid, so the climate
+    // is sampled once per column of the tile and its border rather than once per grid point per
+    // column, about a tenth of the noise for a chunk.
+    pub fn sample_tile(&self, origin: (i32, i32), width: i32, depth: i32) -> Vec<i32> {
+        let (r, step) = (Self::BIOME_BLEND_RADIUS, Self::BIOME_BLEND_STEP);
+        let stride = width + r * 2;
+
+        let mut profiles = Vec::with_capacity((stride * (depth + r * 2)) as usize);
+
+        for z in origin.1 - r..origin.1 + depth + r {
+            for x in origin.0 - r..origin.0 + width + r {
+                profiles.push(self.biome(x, z).height_profile());
+            }
+        }
+
+        let mut heights = Vec::with_capacity((width * depth) as usize);
+
+        for z in 0..depth {
+            for x in 0..width {
+                let (mut offset, mut scale, mut samples) = (0.0, 0.0, 0.0);
+
+                for dx in (-r..=r).step_by(step as usize) {
+                    for dz in (-r..=r).step_by(step as usize) {
+                        let (o, s) = profiles[(x + r + dx + (z + r + dz) * stride) as usize];
+                        offset += o;
+                        scale += s;
+                        samples += 1.0;
+                    }
+                }
+
+                let (offset, scale) = (offset / samples, scale / samples);
+                let (wx, wz) = (origin.0 + x, origin.1 + z);
+                let noise = self.noise.get([wx as f64 + 0.01, wz as f64 + 0.01]);
+                let height = (self.config.base_height + offset + noise * self.config.amplitude * scale) as i32;
+
+                heights.push(self.carve_river(height, wx, wz));
+            }
+        }
+
+        heights
+    }
+
+    // Ridged noise peaks along thin continuous lines; near a ridge the terrain is pulled down to a
+    // bed below sea level, which the water fill then turns into a river. The falloff towards the
+    // threshold gives the channel sloped banks.
+    fn carve_river(&self, height: i32, x: i32, z: i32) -> i32 {
+        let ridge = self.river_noise.get([x as f64 + 0.01, z as f64 + 0.01]);
+
+        if ridge < Self::RIVER_THRESHOLD {
+            return height;
+        }
+
+        let t = ((ridge - Self::RIVER_THRESHOLD) / (1.0 - Self::RIVER_THRESHOLD)).min(1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        let bed = (self.config.sea_level - Self::RIVER_DEPTH) as f64;
+
+        height.min((height as f64 + (bed - height as f64) * t).round() as i32)
+    }
+
+}
\ No newline at end of file
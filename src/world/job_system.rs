@@ -0,0 +1,78 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+// Kinds of background work, most urgent first. Meshing goes ahead of generation so chunks that
+// are already loaded show up while a new area streams in; only a few mesh jobs are queued at a
+// time, while generation requests come in by the hundred.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JobCategory {
+    Meshing = 0,
+    Generation = 1,
+    // Nothing is lit yet; reserved so lighting slots in below the rest.
+    Lighting = 2,
+}
+
+impl JobCategory {
+    pub const ALL: [JobCategory; 3] = [JobCategory::Meshing, JobCategory::Generation, JobCategory::Lighting];
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// One pool of workers for all of the world's background work. Jobs wait in a queue per category
+// and every spawn hands the pool a task that runs whichever queued job is most urgent once a
+// worker gets to it, so a mesh job spawned behind a hundred generation jobs still runs next.
+pub struct JobSystem {
+    pool: ThreadPool,
+    queues: Arc<Mutex<[VecDeque<Job>; JobCategory::ALL.len()]>>,
+}
+
+impl JobSystem {
+
+    pub fn new(threads: usize) -> Self {
+        Self {
+            pool: ThreadPoolBuilder::new()
+                .num_threads(threads.max(1))
+                .thread_name(|i| format!("world-worker-{}", i))
+                .build()
+                .unwrap(),
+            queues: Arc::new(Mutex::new(Default::default())),
+        }
+    }
+
+    // Every core but the one the render thread keeps busy. The simulation thread mostly waits on
+    // the workers, so it doesn't get one of its own.
+    pub fn default_threads() -> usize {
+        std::thread::available_parallelism().map_or(1, |n| n.get()).saturating_sub(1).max(1)
+    }
+
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    pub fn spawn(&self, category: JobCategory, job: impl FnOnce() + Send + 'static) {
+        self.queues.lock().unwrap()[category as usize].push_back(Box::new(job));
+
+        let queues = self.queues.clone();
+
+        self.pool.spawn(move || {
+            // Taken out of the lock first, since the job may spawn more.
+            let job = queues.lock().unwrap().iter_mut().find_map(|queue| queue.pop_front());
+
+            if let Some(job) = job {
+                job();
+            }
+        });
+    }
+
+    // Jobs of the category that haven't started yet.
+    pub fn queued(&self, category: JobCategory) -> usize {
+        self.queues.lock().unwrap()[category as usize].len()
+    }
+
+    // Runs `op` on the workers, so parallel iterators in it share them with the queued jobs.
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        self.pool.install(op)
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,161 @@
+use crate::objects::block::Block;
+use crate::objects::block_material::BlockMaterial;
+use crate::world::chunk::Chunk;
+use crate::world::chunk_mesh::ChunkMesh;
+use crate::world::chunk_section::ChunkSection;
+
+// Distant chunks are meshed from their blocks downsampled into cells of `scale`³ blocks, each cell
+// drawn as one block `scale` times the size. At a quarter resolution a section has a sixty-fourth
+// of the cells, so far terrain costs a fraction of the vertices.
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LodDistances {
+    // Chunks further than this from the camera's chunk are meshed at half resolution.
+    pub half: i32,
+    // And further than this at a quarter.
+    pub quarter: i32,
+}
+
+impl LodDistances {
+
+    pub const DEFAULT: LodDistances = LodDistances { half: 8, quarter: 16 };
+
+    // Blocks per cell along each axis for a chunk `distance` chunks away.
+    pub fn scale(&self, distance: i32) -> i32 {
+        if distance > self.quarter {
+            4
+        } else if distance > self.half {
+            2
+        } else {
+            1
+        }
+    }
+
+}
+
+// Cells sample the neighbors in `[left, right, front, back]` along the borders, so faces between
+// chunks are culled like in the full resolution mesh.
+pub fn build_section_mesh(
+    sections: &[ChunkSection],
+    neighbors: [&[ChunkSection]; 4],
+    section: i32,
+    scale: i32,
+) -> ChunkMesh {
+    let mut mesh = ChunkMesh::new();
+    mesh.scale = scale;
+
+    if sections[section as usize].is_empty() {
+        return mesh;
+    }
+
+    // The section's cells with a border of one cell all around.
+    let n = Chunk::SECTION_HEIGHT / scale;
+    let size = n + 2;
+    let bottom = section * Chunk::SECTION_HEIGHT;
+    let index = |x: i32, y: i32, z: i32| ((x + 1) + (z + 1) * size + (y + 1) * size * size) as usize;
+
+    let mut cells = vec![Block::Air.id; (size * size * size) as usize];
+
+    for y in -1..=n {
+        for z in -1..=n {
+            for x in -1..=n {
+                cells[index(x, y, z)] = cell(sections, &neighbors, x * scale, bottom + y * scale, z * scale, scale);
+            }
+        }
+    }
+
+    for y in 0..n {
+        for z in 0..n {
+            for x in 0..n {
+                let block = Block::block(cells[index(x, y, z)]);
+
+                if block.id == Block::Air.id {
+                    continue;
+                }
+
+                let faces = [
+                    (x, y, z - 1),
+                    (x, y, z + 1),
+                    (x - 1, y, z),
+                    (x + 1, y, z),
+                    (x, y + 1, z),
+                    (x, y - 1, z),
+                ].map(|(nx, ny, nz)| {
+                    let neighbor = Block::block(cells[index(nx, ny, nz)]);
+                    neighbor.material != BlockMaterial::Solid
+                        && !(block.id == Block::Water.id && neighbor.id == Block::Water.id)
+                });
+
+                let verts = block.build_faces(
+                    (x * scale) as f32,
+                    (y * scale) as f32,
+                    (z * scale) as f32,
+                    scale as f32,
+                    faces,
+                    // Grass overlays are too fine to make out this far away.
+                    [None; 6],
+                );
+
+                if block.material == BlockMaterial::Solid {
+                    mesh.vertices.extend_from_slice(verts.as_slice());
+                } else {
+                    mesh.alpha_vertices.extend_from_slice(verts.as_slice());
+                }
+            }
+        }
+    }
+
+    mesh
+}
+
+// The most common block in the cell at (x, y, z), or air when at least half of it is air. Below
+// the world counts as solid, like in the full resolution mesh, so no bottom faces are drawn.
+fn cell(sections: &[ChunkSection], neighbors: &[&[ChunkSection]; 4], x: i32, y: i32, z: i32, scale: i32) -> u8 {
+    if y < 0 {
+        return Block::Bedrock.id;
+    }
+
+    let mut counts: Vec<(u8, i32)> = vec![];
+
+    for dy in 0..scale {
+        for dz in 0..scale {
+            for dx in 0..scale {
+                let id = block(sections, neighbors, x + dx, y + dy, z + dz);
+
+                match counts.iter_mut().find(|(c, _)| *c == id) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((id, 1)),
+                }
+            }
+        }
+    }
+
+    let air = counts.iter().find(|(id, _)| *id == Block::Air.id).map_or(0, |(_, count)| *count);
+
+    if air * 2 >= scale * scale * scale {
+        return Block::Air.id;
+    }
+
+    counts
+        .iter()
+        .filter(|(id, _)| *id != Block::Air.id)
+        .max_by_key(|(_, count)| *count)
+        .map_or(Block::Air.id, |(id, _)| *id)
+}
+
+// Looks past the chunk's sides into the neighbors; corners beyond two sides are never needed
+// and count as air.
+fn block(sections: &[ChunkSection], neighbors: &[&[ChunkSection]; 4], x: i32, y: i32, z: i32) -> u8 {
+    let [left, right, front, back] = neighbors;
+
+    let (sections, x, z) = match (x, z) {
+        (x, z) if (0..Chunk::WIDTH).contains(&x) && (0..Chunk::DEPTH).contains(&z) => (sections, x, z),
+        (x, z) if x < 0 && (0..Chunk::DEPTH).contains(&z) => (*left, x + Chunk::WIDTH, z),
+        (x, z) if x >= Chunk::WIDTH && (0..Chunk::DEPTH).contains(&z) => (*right, x - Chunk::WIDTH, z),
+        (x, z) if z < 0 && (0..Chunk::WIDTH).contains(&x) => (*front, x, z + Chunk::DEPTH),
+        (x, z) if z >= Chunk::DEPTH && (0..Chunk::WIDTH).contains(&x) => (*back, x, z - Chunk::DEPTH),
+        _ => return Block::Air.id,
+    };
+
+    Chunk::block_in_sections(sections, x, y, z).unwrap_or(Block::Air.id)
+}
\ No newline at end of file
@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use cgmath::{InnerSpace, vec3};
+use crate::scene::camera::Camera;
+use crate::world::chunk::Chunk;
+
+// Chunks waiting for a mesh job, most urgent first. Mesh jobs run in the order they're spawned, so
+// only about one per worker is handed to the `JobSystem` at a time and the rest wait here, where
+// they're reordered as the camera moves and turns.
+pub struct MeshQueue {
+    jobs: BinaryHeap<Job>,
+}
+
+struct Job {
+    priority: f32,
+    position: (i32, i32),
+}
+
+impl Default for MeshQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MeshQueue {
+
+    pub fn new() -> Self {
+        Self {
+            jobs: BinaryHeap::new(),
+        }
+    }
+
+    pub fn push(&mut self, position: (i32, i32), camera: &Camera) {
+        self.jobs.push(Job { priority: Self::priority(position, camera), position });
+    }
+
+    pub fn pop(&mut self) -> Option<(i32, i32)> {
+        self.jobs.pop().map(|j| j.position)
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.jobs.clear();
+    }
+
+    // Horizontal distance to the chunk's center, stretched up to twice as far for chunks behind
+    // the camera so the ones in view are meshed first.
+    fn priority(position: (i32, i32), camera: &Camera) -> f32 {
+        let (x, z) = Chunk::local_to_world_position(position);
+        let p = camera.position();
+
+        let offset = vec3(
+            x as f32 + Chunk::WIDTH as f32 / 2.0 - p.x,
+            0.0,
+            z as f32 + Chunk::DEPTH as f32 / 2.0 - p.z,
+        );
+        let front = vec3(camera.front().x, 0.0, camera.front().z);
+
+        let distance = offset.magnitude();
+
+        // The camera's own chunk, or looking straight up or down.
+        if distance < Chunk::WIDTH as f32 || front.magnitude2() < 0.0001 {
+            return distance;
+        }
+
+        let facing = offset.normalize().dot(front.normalize());
+
+        distance * (1.5 - facing * 0.5)
+    }
+
+}
+
+// Lowest priority first.
+impl Ord for Job {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.total_cmp(&self.priority)
+    }
+}
+
+impl PartialOrd for Job {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Job {}
\ No newline at end of file
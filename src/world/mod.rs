@@ -1,4 +1,33 @@
 pub mod chunk;
 pub mod chunk_mesh;
 pub mod world;
-pub mod chunk_buffer;
\ No newline at end of file
+pub mod chunk_buffer;
+pub mod occupancy;
+pub mod decoration;
+pub mod terrain_generator;
+pub mod noise_terrain_generator;
+pub mod worldgen_config;
+pub mod structures;
+pub mod world_type;
+pub mod floating_islands_generator;
+pub mod chunk_rng;
+pub mod chunk_section;
+pub mod biome;
+pub mod chunk_cache;
+pub mod storage;
+pub mod block_storage;
+pub mod quad_index_buffer;
+pub mod mesh_queue;
+pub mod lod;
+pub mod occlusion;
+pub mod dirty_set;
+pub mod chunk_buffer_pool;
+pub mod chunk_draws;
+pub mod world_thread;
+pub mod world_stats;
+pub mod block_view;
+pub mod exploration;
+pub mod seam_check;
+pub mod height_field;
+pub mod job_system;
+pub mod file_format;
\ No newline at end of file
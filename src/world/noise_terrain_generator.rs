@@ -0,0 +1,200 @@
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use rayon::prelude::*;
+use crate::objects::block::Block;
+use crate::world::biome::Biome;
+use crate::world::chunk::Chunk;
+use crate::world::height_field::HeightField;
+use crate::world::terrain_generator::TerrainGenerator;
+use crate::world::worldgen_config::WorldgenConfig;
+
+pub struct NoiseTerrainGenerator {
+    height_field: HeightField,
+    cave_noise: Fbm<Perlin>,
+    ore_noise: Perlin,
+    entrance_noise: Perlin,
+
+    config: WorldgenConfig,
+}
+
+impl NoiseTerrainGenerator {
+
+    pub const CAVE_ROOF_DEPTH: i32 = 5;
+    pub const ORE_MIN_DEPTH: i32 = 4;
+    pub const DIRT_DEPTH: i32 = 3;
+    pub const BEACH_RADIUS: i32 = 2;
+    pub const BEACH_HEIGHT: i32 = 2;
+    pub const BEACH_DEPTH: i32 = 3;
+    pub const ENTRANCE_THRESHOLD: f64 = 0.45;
+    pub const ENTRANCE_FREQUENCY: f64 = 0.02;
+
+    pub fn new(config: &WorldgenConfig) -> Self {
+        let cave_noise = Fbm::new(config.seed.wrapping_add(1))
+            .set_octaves(2)
+            .set_frequency(0.045)
+            .set_persistence(0.5)
+            .set_lacunarity(2.0);
+
+        Self {
+            height_field: HeightField::new(config),
+            cave_noise,
+            ore_noise: Perlin::new(config.seed.wrapping_add(2)),
+            entrance_noise: Perlin::new(config.seed.wrapping_add(4)),
+            config: config.clone(),
+        }
+    }
+
+    // Columns close to sea level that have water within a couple of blocks become beach; this
+    // includes the shallow floor on the water side of the shore.
+    fn is_beach(&self, heights: &[i32], stride: i32, (x, z): (i32, i32)) -> bool {
+        let r = Self::BEACH_RADIUS;
+        let n = heights[(x + r + (z + r) * stride) as usize];
+
+        if n < self.config.sea_level - Self::BEACH_DEPTH || n > self.config.sea_level + Self::BEACH_HEIGHT {
+            return false;
+        }
+
+        (-r..=r).any(|dx| (-r..=r).any(|dz| {
+            heights[(x + r + dx + (z + r + dz) * stride) as usize] < self.config.sea_level
+        }))
+    }
+
+    // Surface columns get grass, or snow above the snow line; a few layers of dirt sit between the
+    // surface and the stone. Beaches replace both the surface and the dirt with sand.
+    fn layer(&self, y: i32, height: i32, beach: bool) -> Block {
+        if beach && y >= height - Self::DIRT_DEPTH {
+            Block::Sand
+        } else if y == height {
+            if height >= self.config.snow_height {
+                Block::Snow
+            } else if height < self.config.sea_level {
+                Block::Dirt
+            } else {
+                Block::Grass
+            }
+        } else if y >= height - Self::DIRT_DEPTH {
+            Block::Dirt
+        } else {
+            Block::Stone
+        }
+    }
+
+    // Caves normally stop a few blocks under the surface. In scattered dry patches the roof thins
+    // out towards the middle until it is gone, so tunnels passing under them break through as
+    // funnel-shaped openings. Underwater and beach columns keep their roof so the sea floor holds.
+    fn cave_roof(&self, height: i32, beach: bool, (x, z): (i32, i32)) -> i32 {
+        if beach || height <= self.config.sea_level {
+            return Self::CAVE_ROOF_DEPTH;
+        }
+
+        let f = Self::ENTRANCE_FREQUENCY;
+        let n = self.entrance_noise.get([x as f64 * f + 0.01, z as f64 * f + 0.01]);
+
+        if n < Self::ENTRANCE_THRESHOLD {
+            return Self::CAVE_ROOF_DEPTH;
+        }
+
+        let t = ((n - Self::ENTRANCE_THRESHOLD) / (1.0 - Self::ENTRANCE_THRESHOLD) * 4.0).min(1.0);
+
+        Self::CAVE_ROOF_DEPTH - ((Self::CAVE_ROOF_DEPTH + 1) as f64 * t).round() as i32
+    }
+
+    // Tunnels follow the lines where two independent noise fields both cross zero; caverns are the
+    // rare peaks of a lower frequency sample of the same noise.
+    fn is_cave(&self, (x, y, z): (i32, i32, i32)) -> bool {
+        if y < 2 {
+            return false;
+        }
+
+        let (x, y, z) = (x as f64, y as f64 * 1.5, z as f64);
+
+        let a = self.cave_noise.get([x, y, z]);
+        let b = self.cave_noise.get([x + 4096.0, y, z - 4096.0]);
+
+        if a.abs() < 0.06 && b.abs() < 0.06 {
+            return true;
+        }
+
+        self.cave_noise.get([x * 0.5 - 8192.0, y * 0.5, z * 0.5 + 8192.0]) > 0.55
+    }
+
+    // Each ore samples its own offset of the noise at a high frequency, so only thin, blobby veins
+    // clear the threshold. Rarer ores are restricted to deeper layers.
+    fn ore(&self, (x, y, z): (i32, i32, i32)) -> Option<Block> {
+        let (x, y, z) = (x as f64, y as f64, z as f64);
+
+        if y < 40.0 && self.ore_noise.get([x * 0.19 + 512.0, y * 0.19, z * 0.19 - 512.0]) > 0.63 {
+            return Some(Block::IronOre);
+        }
+
+        if self.ore_noise.get([x * 0.15, y * 0.15, z * 0.15]) > 0.6 {
+            return Some(Block::CoalOre);
+        }
+
+        None
+    }
+
+}
+
+impl TerrainGenerator for NoiseTerrainGenerator {
+
+    fn generate(&self, chunk_position: (i32, i32)) -> Vec<u8> {
+        let world_position = Chunk::local_to_world_position(chunk_position);
+        let mut blocks = vec![Block::Air.id; Chunk::SIZE as usize];
+        // Heights are sampled with a border so shorelines can see columns in neighboring chunks.
+        let r = Self::BEACH_RADIUS;
+        let stride = Chunk::WIDTH + r * 2;
+        let origin = (world_position.0 - r, world_position.1 - r);
+        let heights = self.height_field.sample_tile(origin, stride, Chunk::DEPTH + r * 2);
+
+        let mut columns = vec![(0, false, 0); (Chunk::WIDTH * Chunk::DEPTH) as usize];
+
+        for x in 0..Chunk::WIDTH {
+            for z in 0..Chunk::DEPTH {
+                let height = heights[(x + r + (z + r) * stride) as usize];
+                let beach = self.is_beach(&heights, stride, (x, z));
+                let roof = self.cave_roof(height, beach, (x + world_position.0, z + world_position.1));
+
+                columns[(x + z * Chunk::WIDTH) as usize] = (height, beach, roof);
+            }
+        }
+
+        blocks
+            .par_chunks_mut(Chunk::SECTION_SIZE as usize)
+            .enumerate()
+            .for_each(|(section, blocks)| {
+                let offset = section * Chunk::SECTION_SIZE as usize;
+
+                for (i, block) in blocks.iter_mut().enumerate() {
+                    let (x, y, z) = Chunk::index_to_xyz(offset + i);
+                    let (n, beach, roof) = columns[(x + z * Chunk::WIDTH) as usize];
+
+                    if y <= n {
+                        let position = (x + world_position.0, y, z + world_position.1);
+
+                        if y < n - roof && self.is_cave(position) {
+                            continue;
+                        }
+
+                        *block = if y < n - Self::ORE_MIN_DEPTH {
+                            self.ore(position).unwrap_or(self.layer(y, n, beach)).id
+                        } else {
+                            self.layer(y, n, beach).id
+                        };
+                    } else if y <= n.max(self.config.sea_level) {
+                        *block = Block::Water.id;
+                    }
+                }
+            });
+
+        blocks
+    }
+
+    fn seed(&self) -> u32 {
+        self.config.seed
+    }
+
+    fn biome(&self, x: i32, z: i32) -> Option<Biome> {
+        Some(self.height_field.biome(x, z))
+    }
+
+}
@@ -0,0 +1,105 @@
+use crate::objects::block::Block;
+use crate::objects::block_material::BlockMaterial;
+use crate::world::chunk::Chunk;
+use crate::world::chunk_section::ChunkSection;
+
+// Which faces of a section can see each other through its non-solid blocks, one bit per pair of
+// faces. Faces are in `Block::POSITIONS` order: front (z - 1), back (z + 1), left (x - 1),
+// right (x + 1), top and bottom.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SectionVisibility(u64);
+
+impl SectionVisibility {
+
+    // What sections nobody has looked into yet are assumed to be, so they never hide anything.
+    pub const ALL: SectionVisibility = SectionVisibility((1 << 36) - 1);
+
+    pub const NONE: SectionVisibility = SectionVisibility(0);
+
+    // Flood fills the section's non-solid blocks; every face touched by one connected pocket of
+    // them can see every other face it touches.
+    pub fn from_section(section: &ChunkSection) -> Self {
+        if section.is_empty() {
+            return Self::ALL;
+        }
+
+        let (w, h, d) = (Chunk::WIDTH, Chunk::SECTION_HEIGHT, Chunk::DEPTH);
+        let mut visited = vec![false; Chunk::SECTION_SIZE as usize];
+        let mut visibility = Self::NONE;
+        let mut stack = vec![];
+
+        for index in 0..Chunk::SECTION_SIZE {
+            let (x, y, z) = (index % w, index / (w * d), index / w % d);
+
+            if visited[index as usize] || Self::is_opaque(section, x, y, z) {
+                continue;
+            }
+
+            visited[index as usize] = true;
+            stack.push((x, y, z));
+
+            let mut faces = 0u8;
+
+            while let Some((x, y, z)) = stack.pop() {
+                faces |= Self::boundary_faces(x, y, z);
+
+                for (dx, dy, dz) in [(0, 0, -1), (0, 0, 1), (-1, 0, 0), (1, 0, 0), (0, 1, 0), (0, -1, 0)] {
+                    let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+
+                    if !(0..w).contains(&nx) || !(0..h).contains(&ny) || !(0..d).contains(&nz) {
+                        continue;
+                    }
+
+                    let neighbor = ChunkSection::xyz_to_index(nx, ny, nz);
+
+                    if !visited[neighbor] && !Self::is_opaque(section, nx, ny, nz) {
+                        visited[neighbor] = true;
+                        stack.push((nx, ny, nz));
+                    }
+                }
+            }
+
+            for a in 0..6 {
+                for b in 0..6 {
+                    if faces >> a & 1 == 1 && faces >> b & 1 == 1 {
+                        visibility.0 |= 1 << (a * 6 + b);
+                    }
+                }
+            }
+
+            if visibility == Self::ALL {
+                break;
+            }
+        }
+
+        visibility
+    }
+
+    pub fn connects(&self, from: usize, to: usize) -> bool {
+        self.0 >> (from * 6 + to) & 1 == 1
+    }
+
+    // Face on the other side of the shared wall, e.g. a section's front is its neighbor's back.
+    pub fn opposite(face: usize) -> usize {
+        face ^ 1
+    }
+
+    // Section offset, as (x, section, z), through each face.
+    pub fn step(face: usize) -> (i32, i32, i32) {
+        [(0, 0, -1), (0, 0, 1), (-1, 0, 0), (1, 0, 0), (0, 1, 0), (0, -1, 0)][face]
+    }
+
+    fn is_opaque(section: &ChunkSection, x: i32, y: i32, z: i32) -> bool {
+        Block::block(section.get(x, y, z)).material == BlockMaterial::Solid
+    }
+
+    fn boundary_faces(x: i32, y: i32, z: i32) -> u8 {
+        (z == 0) as u8
+            | ((z == Chunk::DEPTH - 1) as u8) << 1
+            | ((x == 0) as u8) << 2
+            | ((x == Chunk::WIDTH - 1) as u8) << 3
+            | ((y == Chunk::SECTION_HEIGHT - 1) as u8) << 4
+            | ((y == 0) as u8) << 5
+    }
+
+}
\ No newline at end of file
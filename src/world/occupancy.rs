@@ -0,0 +1,352 @@
+use crate::objects::block::Block;
+use crate::objects::block_material::BlockMaterial;
+use crate::world::chunk::Chunk;
+use crate::world::chunk_section::ChunkSection;
+
+pub const WORDS: usize = (Chunk::HEIGHT / 64) as usize;
+
+pub type Bits = [u64; WORDS];
+
+// One bit per block along a vertical column, y = 0 is the lowest bit of the first word.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ColumnMask {
+    pub filled: Bits,
+    pub solid: Bits,
+    pub water: Bits,
+}
+
+impl ColumnMask {
+
+    // Empty sections are all air, which is what the default mask already holds.
+    pub fn from_sections(sections: &[ChunkSection], x: i32, z: i32) -> Self {
+        let mut column = Self::default();
+
+        for (i, section) in sections.iter().enumerate().filter(|(_, s)| !s.is_empty()) {
+            let bottom = i as i32 * Chunk::SECTION_HEIGHT;
+
+            for y in 0..Chunk::SECTION_HEIGHT {
+                column.set(bottom + y, &Block::block(section.get(x, y, z)));
+            }
+        }
+
+        column
+    }
+
+    pub fn set(&mut self, y: i32, block: &Block) {
+        let (word, bit) = (y as usize / 64, 1u64 << (y % 64));
+
+        self.filled[word] &= !bit;
+        self.solid[word] &= !bit;
+        self.water[word] &= !bit;
+
+        if block.id != Block::Air.id {
+            self.filled[word] |= bit;
+        }
+
+        if block.material == BlockMaterial::Solid {
+            self.solid[word] |= bit;
+        }
+
+        if block.id == Block::Water.id {
+            self.water[word] |= bit;
+        }
+    }
+
+    // A face is hidden by a solid neighbor, or when water touches water.
+    fn visible_against(&self, neighbor: &ColumnMask) -> Bits {
+        std::array::from_fn(|w| self.filled[w] & !neighbor.solid[w] & !(self.water[w] & neighbor.water[w]))
+    }
+
+    fn above(&self) -> ColumnMask {
+        ColumnMask {
+            filled: shift_down(&self.filled),
+            solid: shift_down(&self.solid),
+            water: shift_down(&self.water),
+        }
+    }
+
+    // Nothing can be seen from under the world, so the bottom of the column counts as solid.
+    fn below(&self) -> ColumnMask {
+        let mut solid = shift_up(&self.solid);
+        solid[0] |= 1;
+
+        ColumnMask {
+            filled: shift_up(&self.filled),
+            solid,
+            water: shift_up(&self.water),
+        }
+    }
+
+    // Visible faces in `Block::POSITIONS` order: front, back, left, right, top, bottom.
+    pub fn faces(
+        &self,
+        front: &ColumnMask,
+        back: &ColumnMask,
+        left: &ColumnMask,
+        right: &ColumnMask,
+    ) -> [Bits; 6] {
+        [
+            self.visible_against(front),
+            self.visible_against(back),
+            self.visible_against(left),
+            self.visible_against(right),
+            self.visible_against(&self.above()),
+            self.visible_against(&self.below()),
+        ]
+    }
+
+}
+
+// Bit y of the result holds bit y + 1 of the input; the top bit becomes air.
+fn shift_down(bits: &Bits) -> Bits {
+    let mut shifted = [0; WORDS];
+
+    for w in 0..WORDS {
+        let carry = if w + 1 < WORDS { bits[w + 1] << 63 } else { 0 };
+        shifted[w] = (bits[w] >> 1) | carry;
+    }
+
+    shifted
+}
+
+// Bit y of the result holds bit y - 1 of the input; the bottom bit becomes air.
+fn shift_up(bits: &Bits) -> Bits {
+    let mut shifted = [0; WORDS];
+
+    for w in 0..WORDS {
+        let carry = if w > 0 { bits[w - 1] >> 63 } else { 0 };
+        shifted[w] = (bits[w] << 1) | carry;
+    }
+
+    shifted
+}
+
+#[derive(Clone)]
+pub struct Occupancy {
+    columns: Vec<ColumnMask>,
+}
+
+impl Default for Occupancy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Occupancy {
+
+    pub fn new() -> Self {
+        Self {
+            columns: vec![ColumnMask::default(); (Chunk::WIDTH * Chunk::DEPTH) as usize],
+        }
+    }
+
+    pub fn from_blocks(blocks: &[u8]) -> Self {
+        let mut occupancy = Self::new();
+
+        for (i, b) in blocks.iter().enumerate() {
+            let (x, y, z) = Chunk::index_to_xyz(i);
+            occupancy.set(x, y, z, &Block::block(*b));
+        }
+
+        occupancy
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, z: i32, block: &Block) {
+        self.columns[Self::column_index(x, z)].set(y, block);
+    }
+
+    pub fn is_solid(&self, x: i32, y: i32, z: i32) -> bool {
+        Self::in_bounds(x, y, z) && Self::test(&self.column(x, z).solid, y)
+    }
+
+    pub fn column(&self, x: i32, z: i32) -> &ColumnMask {
+        &self.columns[Self::column_index(x, z)]
+    }
+
+    pub fn memory_size(&self) -> usize {
+        self.columns.capacity() * std::mem::size_of::<ColumnMask>()
+    }
+
+    // Per-column visible face masks for the whole chunk; neighbor chunks are only
+    // sampled along the shared border.
+    pub fn visible_faces(
+        &self,
+        left: &[ChunkSection],
+        right: &[ChunkSection],
+        front: &[ChunkSection],
+        back: &[ChunkSection],
+    ) -> Vec<[Bits; 6]> {
+        let mut faces = vec![[[0; WORDS]; 6]; self.columns.len()];
+
+        for z in 0..Chunk::DEPTH {
+            for x in 0..Chunk::WIDTH {
+                let [f, b, l, r] = self.neighbor_columns(x, z, [left, right, front, back]);
+
+                faces[Self::column_index(x, z)] = self.column(x, z).faces(&f, &b, &l, &r);
+            }
+        }
+
+        faces
+    }
+
+    // Per-column masks of the water blocks with a solid block beside them, in the same order as
+    // the side faces: front, back, left, right.
+    pub fn shores(
+        &self,
+        left: &[ChunkSection],
+        right: &[ChunkSection],
+        front: &[ChunkSection],
+        back: &[ChunkSection],
+    ) -> Vec<[Bits; 4]> {
+        let mut shores = vec![[[0; WORDS]; 4]; self.columns.len()];
+
+        for z in 0..Chunk::DEPTH {
+            for x in 0..Chunk::WIDTH {
+                let column = self.column(x, z);
+
+                if column.water.iter().all(|w| *w == 0) {
+                    continue;
+                }
+
+                let neighbors = self.neighbor_columns(x, z, [left, right, front, back]);
+
+                shores[Self::column_index(x, z)] = neighbors.map(|n| std::array::from_fn(|w| column.water[w] & n.solid[w]));
+            }
+        }
+
+        shores
+    }
+
+    // The columns beside this one, front, back, left and right; from the neighbor chunk on a border.
+    fn neighbor_columns(&self, x: i32, z: i32, [left, right, front, back]: [&[ChunkSection]; 4]) -> [ColumnMask; 4] {
+        let l = if x == 0 {
+            ColumnMask::from_sections(left, Chunk::WIDTH - 1, z)
+        } else {
+            *self.column(x - 1, z)
+        };
+
+        let r = if x + 1 == Chunk::WIDTH {
+            ColumnMask::from_sections(right, 0, z)
+        } else {
+            *self.column(x + 1, z)
+        };
+
+        let f = if z == 0 {
+            ColumnMask::from_sections(front, x, Chunk::DEPTH - 1)
+        } else {
+            *self.column(x, z - 1)
+        };
+
+        let b = if z + 1 == Chunk::DEPTH {
+            ColumnMask::from_sections(back, x, 0)
+        } else {
+            *self.column(x, z + 1)
+        };
+
+        [f, b, l, r]
+    }
+
+    pub fn column_index(x: i32, z: i32) -> usize {
+        (x + z * Chunk::WIDTH) as usize
+    }
+
+    fn test(bits: &Bits, y: i32) -> bool {
+        bits[y as usize / 64] >> (y % 64) & 1 == 1
+    }
+
+    fn in_bounds(x: i32, y: i32, z: i32) -> bool {
+        (0..Chunk::WIDTH).contains(&x) && (0..Chunk::HEIGHT).contains(&y) && (0..Chunk::DEPTH).contains(&z)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::chunk_rng::ChunkRng;
+
+    fn bits(ys: &[i32]) -> Bits {
+        let mut bits = [0; WORDS];
+        ys.iter().for_each(|y| bits[*y as usize / 64] |= 1 << (y % 64));
+        bits
+    }
+
+    fn column(blocks: &[(i32, Block)]) -> ColumnMask {
+        let mut column = ColumnMask::default();
+        blocks.iter().for_each(|(y, block)| column.set(*y, block));
+        column
+    }
+
+    // The old mesher's check: a block shows a face unless the block beside it is solid, or both
+    // are water. Blocks past the chunk's sides are air; under the world nothing is drawn.
+    fn has_face(blocks: &[u8], (x, y, z): (i32, i32, i32), face: usize) -> bool {
+        let block = Block::block(blocks[Chunk::xyz_to_index(x, y, z)]);
+        let (dx, dy, dz) = [(0, 0, -1), (0, 0, 1), (-1, 0, 0), (1, 0, 0), (0, 1, 0), (0, -1, 0)][face];
+        let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+
+        if block.id == Block::Air.id || ny < 0 {
+            return false;
+        }
+
+        let other = if Occupancy::in_bounds(nx, ny, nz) { Block::block(blocks[Chunk::xyz_to_index(nx, ny, nz)]) } else { Block::Air };
+
+        !(other.material == BlockMaterial::Solid || (other.id == Block::Water.id && block.id == Block::Water.id))
+    }
+
+    #[test]
+    fn shifts_carry_across_words() {
+        assert_eq!(shift_up(&bits(&[63])), bits(&[64]));
+        assert_eq!(shift_down(&bits(&[64])), bits(&[63]));
+        assert_eq!(shift_up(&bits(&[0, 63, 64])), bits(&[1, 64, 65]));
+        assert_eq!(shift_down(&bits(&[0, 63, 64])), bits(&[62, 63]));
+    }
+
+    #[test]
+    fn shifts_drop_the_ends_of_the_column() {
+        assert_eq!(shift_up(&bits(&[Chunk::HEIGHT - 1])), [0; WORDS]);
+        assert_eq!(shift_down(&bits(&[0])), [0; WORDS]);
+    }
+
+    #[test]
+    fn top_and_bottom_of_the_column() {
+        let air = ColumnMask::default();
+        let stone = column(&[(0, Block::Stone), (Chunk::HEIGHT - 1, Block::Stone)]);
+        let [.., top, bottom] = stone.faces(&air, &air, &air, &air);
+
+        // The top of the world is open, its bottom is never seen.
+        assert_eq!(top, bits(&[0, Chunk::HEIGHT - 1]));
+        assert_eq!(bottom, bits(&[Chunk::HEIGHT - 1]));
+    }
+
+    #[test]
+    fn water_next_to_water() {
+        let water = column(&[(63, Block::Water), (64, Block::Water), (65, Block::Stone)]);
+        let beside = column(&[(63, Block::Water), (64, Block::Stone), (65, Block::Water)]);
+        let air = ColumnMask::default();
+        let [front, .., top, bottom] = water.faces(&beside, &air, &air, &air);
+
+        // Water hides water and stone hides water; water doesn't hide stone.
+        assert_eq!(front, bits(&[65]));
+        assert_eq!(top, bits(&[65]));
+        assert_eq!(bottom, bits(&[63, 65]));
+    }
+
+    #[test]
+    fn matches_per_block_checks_without_neighbors() {
+        let mut rng = ChunkRng::new(1, (0, 0), 0);
+        let palette = [Block::Air.id, Block::Air.id, Block::Stone.id, Block::Water.id];
+        let blocks = (0..Chunk::SIZE).map(|_| palette[rng.range(0..4) as usize]).collect::<Vec<_>>();
+
+        let occupancy = Occupancy::from_blocks(&blocks);
+        let faces = occupancy.visible_faces(&[], &[], &[], &[]);
+
+        for i in 0..blocks.len() {
+            let (x, y, z) = Chunk::index_to_xyz(i);
+
+            for (face, mask) in faces[Occupancy::column_index(x, z)].iter().enumerate() {
+                assert_eq!(Occupancy::test(mask, y), has_face(&blocks, (x, y, z), face), "face {} of {:?}", face, (x, y, z));
+            }
+        }
+    }
+}
\ No newline at end of file
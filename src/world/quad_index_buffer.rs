@@ -0,0 +1,46 @@
+use wgpu::{Buffer, Device};
+use wgpu::util::DeviceExt;
+use crate::world::chunk::Chunk;
+
+// Chunk meshes are made only of quads, four vertices each, which all share the same triangle
+// pattern. One index buffer covering the most quads a section can hold is bound for every chunk
+// draw, so chunk buffers carry vertices only.
+pub struct QuadIndexBuffer {
+    buffer: Buffer,
+}
+
+impl QuadIndexBuffer {
+
+    pub const QUAD: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+    // Every face of every block in a section.
+    pub const MAX_QUADS: u32 = Chunk::SECTION_SIZE as u32 * 6;
+    pub const BYTES: u64 = Self::MAX_QUADS as u64 * 6 * std::mem::size_of::<u32>() as u64;
+
+    pub fn new(device: &Device) -> Self {
+        let indices = (0..Self::MAX_QUADS)
+            .flat_map(|q| Self::QUAD.map(|i| i + q * 4))
+            .collect::<Vec<_>>();
+
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Quad Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self { buffer }
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    pub fn index_count(vertex_count: u32) -> u32 {
+        vertex_count / 4 * 6
+    }
+
+    pub fn vertex_count(index_count: u32) -> u32 {
+        index_count / 6 * 4
+    }
+
+}
@@ -0,0 +1,94 @@
+use anyhow::*;
+use cgmath::Point3;
+use collision::Aabb3;
+use crate::objects::block::Block;
+use crate::objects::block_material::BlockMaterial;
+use crate::world::block_view::BlockView;
+use crate::world::chunk::Chunk;
+use crate::world::world::World;
+
+// Checks chunk meshes against faces counted straight from the world's blocks, read as one array
+// that runs across chunk borders. The mesher only sees its neighbors through border copies, so a
+// stale or missing copy shows up as a section with more or fewer faces than the blocks call for.
+
+#[derive(Debug, Copy, Clone)]
+pub struct SeamMismatch {
+    pub position: (i32, i32),
+    pub section: i32,
+    pub expected: usize,
+    pub meshed: usize,
+}
+
+// Every chunk within `radius` of `center` has to be loaded and meshed at full resolution, with
+// its neighbors loaded too.
+pub fn check(world: &World, center: (i32, i32), radius: i32) -> Result<Vec<SeamMismatch>> {
+    let min = Point3::new((center.0 - radius - 1) * Chunk::WIDTH, 0, (center.1 - radius - 1) * Chunk::DEPTH);
+    let max = Point3::new((center.0 + radius + 1) * Chunk::WIDTH, Chunk::HEIGHT, (center.1 + radius + 1) * Chunk::DEPTH);
+    let view = world.get_blocks(&Aabb3::new(min, max));
+
+    let mut mismatches = vec![];
+
+    for cx in center.0 - radius..center.0 + radius {
+        for cz in center.1 - radius..center.1 + radius {
+            let chunk = world
+                .get_chunk(cx * Chunk::WIDTH, 0, cz * Chunk::DEPTH)
+                .with_context(|| format!("chunk {:?} isn't loaded", (cx, cz)))?
+                .borrow();
+
+            for section in 0..Chunk::SECTION_COUNT {
+                let mesh = chunk.section_mesh(section);
+
+                if mesh.scale != 1 {
+                    bail!("chunk {:?} isn't meshed at full resolution", (cx, cz));
+                }
+
+                let expected = section_faces(&view, (cx, cz), section);
+                let meshed = (mesh.vertices.len() + mesh.alpha_vertices.len()) / 4;
+
+                if expected != meshed {
+                    mismatches.push(SeamMismatch { position: (cx, cz), section, expected, meshed });
+                }
+            }
+        }
+    }
+
+    Ok(mismatches)
+}
+
+// Faces the full resolution mesher should build for the section; the same rules as
+// `ColumnMask::faces`, one block at a time.
+fn section_faces(view: &BlockView, position: (i32, i32), section: i32) -> usize {
+    let (wx, wz) = Chunk::local_to_world_position(position);
+    let bottom = section * Chunk::SECTION_HEIGHT;
+    let mut faces = 0;
+
+    for y in bottom..bottom + Chunk::SECTION_HEIGHT {
+        for z in wz..wz + Chunk::DEPTH {
+            for x in wx..wx + Chunk::WIDTH {
+                let block = Block::block(view.get(x, y, z).unwrap_or(Block::Air.id));
+
+                if block.id == Block::Air.id {
+                    continue;
+                }
+
+                faces += [(0, 0, -1), (0, 0, 1), (-1, 0, 0), (1, 0, 0), (0, 1, 0), (0, -1, 0)]
+                    .iter()
+                    .filter(|(dx, dy, dz)| is_visible(&block, view, (x + dx, y + dy, z + dz)))
+                    .count();
+            }
+        }
+    }
+
+    faces
+}
+
+// Nothing can be seen from under the world, so below it counts as solid and above it as air.
+fn is_visible(block: &Block, view: &BlockView, (x, y, z): (i32, i32, i32)) -> bool {
+    if y < 0 {
+        return false;
+    }
+
+    let neighbor = Block::block(view.get(x, y, z).unwrap_or(Block::Air.id));
+
+    neighbor.material != BlockMaterial::Solid && !(block.id == Block::Water.id && neighbor.id == Block::Water.id)
+}
\ No newline at end of file
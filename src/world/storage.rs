@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use anyhow::*;
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use crate::world::chunk::Chunk;
+use crate::world::file_format;
+
+// Modified chunks are saved in region files of REGION_SIZE x REGION_SIZE chunks, each chunk's
+// blocks compressed on their own. A region file is read whole the first time one of its chunks is
+// needed and written whole on flush. Only the CACHED_REGIONS most recently used regions stay in
+// memory once they're saved; the rest are read again when needed. Storage without a directory
+// keeps everything in memory, for worlds that mustn't touch the save of their seed.
+//
+// Layout, little endian: magic, version, chunk count, then per chunk its position, the length of
+// its compressed blocks and the blocks.
+pub struct RegionStorage {
+    dir: Option<PathBuf>,
+    regions: Mutex<Regions>,
+}
+
+#[derive(Default)]
+struct Regions {
+    loaded: HashMap<(i32, i32), Region>,
+    // Bumped on every access, for evicting the least recently used regions.
+    clock: u64,
+}
+
+#[derive(Default)]
+struct Region {
+    chunks: HashMap<(i32, i32), Vec<u8>>,
+    dirty: bool,
+    last_used: u64,
+}
+
+impl RegionStorage {
+
+    pub const REGION_SIZE: i32 = 32;
+    pub const MAGIC: &'static [u8; 4] = b"WVRG";
+    pub const VERSION: u32 = 1;
+    // About the regions around the camera; the render distance stays well below a region.
+    pub const CACHED_REGIONS: usize = 4;
+
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: Some(dir.into()),
+            regions: Mutex::new(Regions::default()),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            dir: None,
+            regions: Mutex::new(Regions::default()),
+        }
+    }
+
+    // Blocks of a saved chunk, laid out as `Chunk::xyz_to_index` expects.
+    pub fn load(&self, position: (i32, i32)) -> Result<Option<Vec<u8>>> {
+        let mut regions = self.regions.lock().unwrap();
+        let region = self.region(&mut regions, position)?;
+
+        let compressed = if let Some(compressed) = region.chunks.get(&position) { compressed } else { return Ok(None) };
+
+        let mut blocks = Vec::with_capacity(Chunk::SIZE as usize);
+        ZlibDecoder::new(compressed.as_slice()).read_to_end(&mut blocks)?;
+
+        if blocks.len() != Chunk::SIZE as usize {
+            bail!("chunk {:?} has {} blocks, expected {}", position, blocks.len(), Chunk::SIZE);
+        }
+
+        Ok(Some(blocks))
+    }
+
+    pub fn store(&self, position: (i32, i32), blocks: &[u8]) -> Result<()> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(blocks)?;
+        let compressed = encoder.finish()?;
+
+        let mut regions = self.regions.lock().unwrap();
+        let region = self.region(&mut regions, position)?;
+
+        region.chunks.insert(position, compressed);
+        region.dirty = true;
+
+        Ok(())
+    }
+
+    // Writes every region with chunks stored since the last flush, then drops the least recently
+    // used ones beyond CACHED_REGIONS.
+    pub fn flush(&self) -> Result<()> {
+        if self.dir.is_none() {
+            return Ok(());
+        }
+
+        let mut regions = self.regions.lock().unwrap();
+
+        for (position, region) in regions.loaded.iter_mut().filter(|(_, r)| r.dirty) {
+            let mut data = file_format::header(Self::MAGIC, Self::VERSION);
+            data.extend_from_slice(&(region.chunks.len() as u32).to_le_bytes());
+
+            for ((x, z), compressed) in region.chunks.iter() {
+                data.extend_from_slice(&x.to_le_bytes());
+                data.extend_from_slice(&z.to_le_bytes());
+                data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+                data.extend_from_slice(compressed);
+            }
+
+            if let Some(path) = self.path(*position) {
+                file_format::write(&path, &data)?;
+            }
+
+            region.dirty = false;
+        }
+
+        let excess = regions.loaded.len().saturating_sub(Self::CACHED_REGIONS);
+        let mut oldest = regions.loaded.iter().map(|(p, r)| (r.last_used, *p)).collect::<Vec<_>>();
+        oldest.sort();
+
+        for (_, position) in oldest.into_iter().take(excess) {
+            regions.loaded.remove(&position);
+        }
+
+        Ok(())
+    }
+
+    fn region<'a>(&self, regions: &'a mut Regions, (x, z): (i32, i32)) -> Result<&'a mut Region> {
+        let position = (x.div_euclid(Self::REGION_SIZE), z.div_euclid(Self::REGION_SIZE));
+        regions.clock += 1;
+
+        let region = match regions.loaded.entry(position) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(self.open(position)?),
+        };
+
+        region.last_used = regions.clock;
+
+        Ok(region)
+    }
+
+    // A file that can't be parsed is moved aside and the region starts out empty, so the chunks
+    // saved from now on aren't lost along with it. Failing to read the file at all is returned,
+    // since it may work on the next try.
+    fn open(&self, position: (i32, i32)) -> Result<Region> {
+        let path = match self.path(position) {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Region::default()),
+        };
+
+        let data = std::fs::read(&path).with_context(|| format!("failed to read region {:?}", path))?;
+
+        Self::read(&data).or_else(|e| {
+            let aside = path.with_extension("region.corrupt");
+            eprintln!("{:?}", e.context(format!("region {:?} is corrupt, moving it to {:?}", path, aside)));
+
+            std::fs::rename(&path, &aside)?;
+
+            Ok(Region::default())
+        })
+    }
+
+    fn read(data: &[u8]) -> Result<Region> {
+        let mut reader = data;
+        let mut region = Region::default();
+
+        file_format::read_header(&mut reader, Self::MAGIC, Self::VERSION)?;
+
+        for _ in 0..file_format::read_u32(&mut reader)? {
+            let x = file_format::read_u32(&mut reader)? as i32;
+            let z = file_format::read_u32(&mut reader)? as i32;
+            let len = file_format::read_u32(&mut reader)? as usize;
+
+            if len > reader.len() {
+                bail!("chunk {:?} has {} compressed bytes, only {} left", (x, z), len, reader.len());
+            }
+
+            let mut compressed = vec![0; len];
+            reader.read_exact(&mut compressed)?;
+
+            region.chunks.insert((x, z), compressed);
+        }
+
+        Ok(region)
+    }
+
+    fn path(&self, (x, z): (i32, i32)) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(format!("r.{}.{}.region", x, z)))
+    }
+
+}
\ No newline at end of file
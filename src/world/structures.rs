@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::path::Path;
+use anyhow::*;
+use serde::Deserialize;
+use crate::objects::block::Block;
+use crate::world::biome::Biome;
+use crate::world::chunk::Chunk;
+use crate::world::chunk_rng::ChunkRng;
+use crate::world::decoration::Placement;
+use crate::world::terrain_generator::TerrainGenerator;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpawnRule {
+    // Block names the structure may be built on, e.g. grass or sand.
+    pub surface: Vec<String>,
+    // Biomes the structure may be built in, any when empty. Worlds without biomes only get
+    // structures that don't ask for one.
+    #[serde(default)]
+    pub biomes: Vec<Biome>,
+    #[serde(default)]
+    pub min_height: i32,
+    #[serde(default = "SpawnRule::default_max_height")]
+    pub max_height: i32,
+    // One structure in `rarity` chunks, on average.
+    pub rarity: u32,
+}
+
+impl SpawnRule {
+
+    fn default_max_height() -> i32 {
+        Chunk::HEIGHT
+    }
+
+}
+
+// Templates are authored as horizontal layers from the bottom up. Each layer is a list of rows
+// along z and each character in a row is one block along x, looked up in the palette. Characters
+// missing from the palette, like spaces, leave the world untouched.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Structure {
+    pub name: String,
+    pub palette: HashMap<char, String>,
+    pub layers: Vec<Vec<String>>,
+    // Vertical offset of the bottom layer from the block above the surface; negative values sink
+    // the structure into the ground.
+    #[serde(default)]
+    pub offset: i32,
+    // Whether the structure overwrites terrain or only fills air.
+    #[serde(default)]
+    pub replace: bool,
+    pub spawn: SpawnRule,
+}
+
+impl Structure {
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = std::fs::read_to_string(path.as_ref())
+            .with_context(|| format!("failed to read structure {:?}", path.as_ref()))?;
+
+        let structure: Structure = serde_json::from_str(&data)
+            .with_context(|| format!("failed to parse structure {:?}", path.as_ref()))?;
+
+        for name in structure.palette.values().chain(structure.spawn.surface.iter()) {
+            if Block::by_name(name).is_none() {
+                bail!("unknown block {:?} in structure {:?}", name, structure.name);
+            }
+        }
+
+        Ok(structure)
+    }
+
+    // Blocks relative to the template origin, which sits at its bottom layer's first row and column.
+    pub fn blocks(&self) -> Vec<((i32, i32, i32), Block)> {
+        let mut blocks = vec![];
+
+        for (y, layer) in self.layers.iter().enumerate() {
+            for (z, row) in layer.iter().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    if let Some(block) = self.palette.get(&c).and_then(|name| Block::by_name(name)) {
+                        blocks.push(((x as i32, y as i32, z as i32), block));
+                    }
+                }
+            }
+        }
+
+        blocks
+    }
+
+}
+
+pub struct StructureRegistry {
+    structures: Vec<Structure>,
+}
+
+impl Default for StructureRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StructureRegistry {
+
+    pub const PATH: &'static str = "assets/structures";
+    // Each registered structure rolls with its own salt, counted up from here.
+    pub const SALT: u64 = 0x100;
+
+    pub fn new() -> Self {
+        Self { structures: vec![] }
+    }
+
+    // Loads every .json template in the directory; files are sorted so spawn order is stable. A
+    // template that can't be loaded is skipped rather than losing the rest with it.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut paths = std::fs::read_dir(path.as_ref())
+            .with_context(|| format!("failed to read structures from {:?}", path.as_ref()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect::<Vec<_>>();
+
+        paths.sort();
+
+        let mut registry = Self::new();
+
+        for path in paths {
+            if let Err(e) = Structure::load(path).map(|structure| registry.register(structure)) {
+                eprintln!("{:?}", e);
+            }
+        }
+
+        Ok(registry)
+    }
+
+    pub fn register(&mut self, structure: Structure) {
+        self.structures.push(structure);
+    }
+
+    // At most one structure is rooted in a chunk. Each structure rolls for the chunk in
+    // registration order and the first one whose rule matches the chosen column wins.
+    pub fn place(&self, chunk: &Chunk, generator: &dyn TerrainGenerator) -> Vec<Placement> {
+        let (wx, wz) = chunk.world_position();
+        let seed = generator.seed();
+
+        for (i, structure) in self.structures.iter().enumerate() {
+            let mut rng = ChunkRng::new(seed, chunk.local_position(), Self::SALT + i as u64);
+
+            if !rng.one_in(structure.spawn.rarity) {
+                continue;
+            }
+
+            let (x, z) = (rng.range(0..Chunk::WIDTH), rng.range(0..Chunk::DEPTH));
+
+            let (y, surface) = if let Some((y, id)) = chunk.terrain_surface(x, z) { (y, Block::block(id)) } else { continue };
+
+            let rule = &structure.spawn;
+
+            if y < rule.min_height || y > rule.max_height || !rule.surface.iter().any(|s| s == surface.name) {
+                continue;
+            }
+
+            let biome = generator.biome(x + wx, z + wz);
+
+            if !rule.biomes.is_empty() && !biome.is_some_and(|b| rule.biomes.contains(&b)) {
+                continue;
+            }
+
+            let origin = (x + wx, y + 1 + structure.offset, z + wz);
+
+            return structure
+                .blocks()
+                .into_iter()
+                .map(|((dx, dy, dz), block)| Placement {
+                    position: (origin.0 + dx, origin.1 + dy, origin.2 + dz),
+                    block,
+                    replace: structure.replace,
+                })
+                .collect();
+        }
+
+        vec![]
+    }
+
+}
@@ -0,0 +1,15 @@
+use crate::world::biome::Biome;
+
+// Produces the blocks of a freshly created chunk, laid out as `Chunk::xyz_to_index` expects.
+// Generators are shared across the chunk worker threads.
+pub trait TerrainGenerator: Send + Sync {
+    fn generate(&self, chunk_position: (i32, i32)) -> Vec<u8>;
+
+    // Seeds everything rolled on top of the terrain, such as decoration and structures.
+    fn seed(&self) -> u32;
+
+    // The biome of a world column, for generators that have biomes.
+    fn biome(&self, _x: i32, _z: i32) -> Option<Biome> {
+        None
+    }
+}
@@ -1,148 +1,597 @@
 use std::borrow::{Borrow, BorrowMut};
 use std::cell::RefCell;
-use std::collections::HashMap;
-use cgmath::{EuclideanSpace, frustum, MetricSpace, Point3, vec3, Vector3};
-use collision::{Aabb3, Continuous, Ray, Relation};
-use noise::{Fbm, MultiFractal, Perlin};
-use threadpool::ThreadPool;
-use wgpu::Device;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use cgmath::{EuclideanSpace, MetricSpace, Point3, vec3, Vector3};
+use collision::{Aabb, Aabb3, Continuous, Ray};
+use crossbeam::channel::{Receiver, Sender};
+use rayon::prelude::*;
+use crate::events::game_event::GameEvent;
 use crate::objects::block::Block;
 use crate::objects::block_face::BlockFace;
+use crate::objects::block_tag::BlockTag;
 use crate::objects::target::Target;
 use crate::scene::camera::Camera;
-use crate::scene::frustum::Frustum;
+use crate::world::block_view::BlockView;
 use crate::world::chunk::Chunk;
-use crate::world::chunk_buffer::ChunkBuffer;
+use crate::world::chunk_cache::ChunkCache;
+use crate::world::decoration;
+use crate::world::dirty_set::DirtySet;
+use crate::world::exploration::ExplorationMap;
+use crate::world::job_system::{JobCategory, JobSystem};
+use crate::world::lod::LodDistances;
+use crate::world::mesh_queue::MeshQueue;
+use crate::world::storage::RegionStorage;
+use crate::world::structures::StructureRegistry;
+use crate::world::terrain_generator::TerrainGenerator;
+use crate::world::world_stats::WorldStats;
+use crate::world::world_thread::WorldUpdate;
 
 pub struct World {
     chunks: HashMap<(i32, i32), RefCell<Chunk>>,
-    pool: ThreadPool,
+    jobs: JobSystem,
 
     render_distance: i32,
+    lod: LodDistances,
 
-    noise: Fbm<Perlin>,
+    generator: Arc<dyn TerrainGenerator>,
+    structures: StructureRegistry,
 
-    buffers: HashMap<(i32, i32), ChunkBuffer>,
+    // Chunks are generated on the workers and picked up on the next update. Results tagged with an
+    // older generation were started before the generator changed and are dropped.
+    pending: HashSet<(i32, i32)>,
+    generation: u32,
+    generated_sender: Sender<(u32, Chunk)>,
+    generated_receiver: Receiver<(u32, Chunk)>,
 
-    vertex_count: u32,
+    // Chunks beyond the render distance plus this margin are unloaded. The margin keeps chunks
+    // from flickering in and out while the camera moves back and forth over a border.
+    cache: ChunkCache,
+
+    // Placed beacons, kept apart from the chunks so their beams can be drawn without searching
+    // every loaded block.
+    beacons: HashSet<(i32, i32, i32)>,
+
+    // Edited chunks are saved here when unloaded and loaded back instead of regenerated.
+    storage: Arc<RegionStorage>,
+    // Every chunk ever generated or loaded, saved with the world for the map.
+    exploration: ExplorationMap,
+    // Off for golden scenes and checks, so they neither load nor change the save of their seed.
+    saving: bool,
+
+    mesh_queue: MeshQueue,
+
+    // Behind a RefCell since edits and decoration only borrow the world, like the chunks.
+    dirty: RefCell<DirtySet>,
+    // Set when the render area moves or is resized. The next update requests the chunks missing
+    // from it, visits every loaded one for its new level of detail and unloads what's left it.
+    rescan: bool,
+
+    // What the render thread hasn't been told yet, see `take_updates`. Chunks whose meshes it was
+    // sent are in `shown` until they leave the render distance.
+    updates: Vec<WorldUpdate>,
+    shown: HashSet<(i32, i32)>,
+
+    // Whether the last update loaded, decorated or meshed anything.
+    busy: bool,
+    meshes_built: usize,
+
+    events: Sender<GameEvent>,
+    center: (i32, i32),
 }
 
 impl World {
-    pub fn new(render_distance: i32) -> Self {
-        let noise = Fbm::new(1)
-            .set_octaves(4)
-            .set_frequency(0.0348)
-            .set_persistence(1.6)
-            .set_lacunarity(0.2);
+    pub const EVICTION_MARGIN: i32 = 2;
+    pub const CACHE_MEMORY: usize = 64 * 1024 * 1024;
+    // How far above a block the camera still counts as standing on it; it spawns this high above
+    // the terrain.
+    pub const GROUND_DISTANCE: i32 = 3;
+    // What the render distance can be changed to while playing, in chunks.
+    pub const MIN_RENDER_DISTANCE: i32 = 2;
+    pub const MAX_RENDER_DISTANCE: i32 = 32;
+    pub const SAVE_DIR: &'static str = "saves";
+    // Brightness of unexplored terrain on the map.
+    pub const UNEXPLORED_SHADE: f32 = 0.4;
+
+    pub fn new(render_distance: i32, generator: Box<dyn TerrainGenerator>, events: Sender<GameEvent>) -> Self {
+        let (generated_sender, generated_receiver) = crossbeam::channel::unbounded();
+        let (storage, exploration) = Self::open_saves(generator.seed(), true);
 
         Self {
             chunks: HashMap::new(),
-            pool: ThreadPool::new(16),
+            jobs: JobSystem::new(JobSystem::default_threads()),
             render_distance,
-            noise,
-            buffers: HashMap::new(),
-            vertex_count: 0,
+            lod: LodDistances::DEFAULT,
+            generator: Arc::from(generator),
+            structures: StructureRegistry::load(StructureRegistry::PATH).unwrap_or_else(|e| {
+                eprintln!("{:?}", e);
+                StructureRegistry::new()
+            }),
+            pending: HashSet::new(),
+            generation: 0,
+            generated_sender,
+            generated_receiver,
+            cache: ChunkCache::new(Self::CACHE_MEMORY),
+            beacons: HashSet::new(),
+            storage,
+            exploration,
+            saving: true,
+            mesh_queue: MeshQueue::new(),
+            dirty: RefCell::new(DirtySet::new()),
+            rescan: true,
+            updates: vec![],
+            shown: HashSet::new(),
+            busy: false,
+            meshes_built: 0,
+            events,
+            center: (0, 0),
         }
     }
 
+    // Loads the area around the camera before the first frame, waiting for every chunk.
     pub fn generate(&mut self, camera: &Camera) {
         let (x, z) = Self::to_local_position(camera.position());
         let r = self.render_distance;
+        self.center = (x, z);
 
         for x in (x - (r + 1))..(x + (r + 1)) {
             for z in (z - (r + 1))..(z + (r + 1)) {
-                let chunk = Chunk::new((x, z), &self.noise);
-                self.chunks.insert((x, z), RefCell::new(chunk));
+                self.request_chunk((x, z));
             }
         }
 
-        for ((x, z), chunk) in self.chunks.iter() {
-            let left = if let Some(c) = self.chunks.get(&(*x - 1, *z)) {
-                c.borrow().blocks().clone()
-            } else {
-                vec![]
-            };
+        while !self.pending.is_empty() {
+            let (generation, chunk) = self.generated_receiver.recv().unwrap();
+            self.insert_generated(generation, chunk);
+        }
 
-            let right = if let Some(c) = self.chunks.get(&(*x + 1, *z)) {
-                c.borrow().blocks().clone()
-            } else {
-                vec![]
-            };
+        // Border copies are filled in by the next update, from neighbors that are actually loaded.
+        // Chunks on the edge wait for theirs instead of meshing against air.
+        self.rescan = true;
+    }
 
-            let front = if let Some(c) = self.chunks.get(&(*x, *z - 1)) {
-                c.borrow().blocks().clone()
-            } else {
-                vec![]
-            };
+    // Drops every loaded chunk so the terrain is rebuilt by the new generator as updates request it.
+    pub fn set_generator(&mut self, generator: Box<dyn TerrainGenerator>) {
+        self.save();
 
-            let back = if let Some(c) = self.chunks.get(&(*x, *z + 1)) {
-                c.borrow().blocks().clone()
-            } else {
-                vec![]
-            };
+        (self.storage, self.exploration) = Self::open_saves(generator.seed(), self.saving);
+        self.generator = Arc::from(generator);
+        self.generation = self.generation.wrapping_add(1);
+        self.pending.clear();
+        self.chunks.clear();
+        self.cache.clear();
+        self.beacons.clear();
+        self.shown.clear();
+        self.updates = vec![WorldUpdate::Clear];
+        self.dirty.get_mut().clear();
+        self.rescan = true;
+    }
+
+    // Chunks loaded until then keep their edits, saved to whichever storage they're unloaded into.
+    pub fn set_saving(&mut self, saving: bool) {
+        self.save();
+        self.saving = saving;
+        (self.storage, self.exploration) = Self::open_saves(self.generator.seed(), saving);
+    }
+
+    fn open_saves(seed: u32, saving: bool) -> (Arc<RegionStorage>, ExplorationMap) {
+        if saving {
+            (Arc::new(RegionStorage::new(Self::save_dir(seed))), ExplorationMap::open(Self::save_dir(seed)))
+        } else {
+            (Arc::new(RegionStorage::in_memory()), ExplorationMap::in_memory())
+        }
+    }
+
+    pub fn loaded_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn set_cache_memory(&mut self, bytes: usize) {
+        self.cache.set_max_bytes(bytes);
+    }
+
+    // Jobs already handed to the old workers still finish and are picked up as usual.
+    pub fn set_worker_threads(&mut self, threads: usize) {
+        self.jobs = JobSystem::new(threads);
+    }
+
+    // Takes effect on the next update, which streams in or evicts chunks for the new distance.
+    pub fn set_render_distance(&mut self, render_distance: i32) {
+        self.render_distance = render_distance;
+        self.rescan = true;
+    }
+
+    // Chunks whose scale changes are remeshed by the next updates.
+    pub fn set_lod_distances(&mut self, lod: LodDistances) {
+        self.lod = lod;
+        self.rescan = true;
+    }
+
+    // Number of chunks queued or being generated on the workers.
+    pub fn pending_chunks(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Chunks waiting for a mesh job as of the last update.
+    pub fn queued_meshes(&self) -> usize {
+        self.mesh_queue.len()
+    }
+
+    // Nothing around the camera is left to load or mesh.
+    pub fn is_settled(&self) -> bool {
+        !self.busy && self.pending.is_empty() && self.dirty.borrow().is_empty()
+    }
+
+    // Chunks the next update will visit.
+    pub fn dirty_chunks(&self) -> usize {
+        self.dirty.borrow().len()
+    }
+
+    fn request_chunk(&mut self, position: (i32, i32)) {
+        if self.chunks.contains_key(&position) || self.pending.contains(&position) {
+            return;
+        }
+
+        if let Some(chunk) = self.cache.take(position) {
+            self.chunks.insert(position, RefCell::new(chunk));
+            self.dirty.get_mut().insert_around(position);
+            let _ = self.events.send(GameEvent::ChunkLoaded { position });
+            return;
+        }
+
+        self.pending.insert(position);
+
+        let generator = self.generator.clone();
+        let storage = self.storage.clone();
+        let sender = self.generated_sender.clone();
+        let generation = self.generation;
+
+        self.jobs.spawn(JobCategory::Generation, move || {
+            let _ = sender.send((generation, Self::load_chunk(position, generator.as_ref(), &storage)));
+        });
+    }
+
+    // Saved chunks already carry their decorations, so they skip that pass.
+    fn load_chunk(position: (i32, i32), generator: &dyn TerrainGenerator, storage: &RegionStorage) -> Chunk {
+        match storage.load(position) {
+            Ok(Some(blocks)) => {
+                let mut chunk = Chunk::from_blocks(position, &blocks);
+                chunk.set_decorated(true);
+                chunk.set_modified(true);
+                chunk
+            },
+            Ok(None) => Chunk::new(position, generator),
+            Err(e) => {
+                eprintln!("{:?}", e);
+                Chunk::new(position, generator)
+            },
+        }
+    }
+
+    // Generates, decorates and saves every chunk within `radius` chunks of the spawn chunk, so they
+    // load from disk during play instead of being generated. Decoration needs every neighbor, so a
+    // ring one chunk wider is generated too but not saved. `progress` gets the number of chunks
+    // generated so far and the total.
+    pub fn pregenerate(&mut self, radius: i32, mut progress: impl FnMut(usize, usize)) {
+        let r = radius + 1;
+
+        for x in -r..=r {
+            for z in -r..=r {
+                self.request_chunk((x, z));
+            }
+        }
+
+        let total = self.pending.len() + self.chunks.len();
+
+        while !self.pending.is_empty() {
+            let (generation, chunk) = self.generated_receiver.recv().unwrap();
+            self.insert_generated(generation, chunk);
+            progress(total - self.pending.len(), total);
+        }
+
+        for x in -radius..=radius {
+            for z in -radius..=radius {
+                if !self.chunks[&(x, z)].borrow().is_decorated() {
+                    self.decorate((x, z));
+                }
+            }
+        }
+
+        for x in -radius..=radius {
+            for z in -radius..=radius {
+                self.chunks[&(x, z)].borrow_mut().set_modified(true);
+            }
+        }
+
+        self.save();
+    }
+
+    pub fn save_dir(seed: u32) -> String {
+        format!("{}/{}", Self::SAVE_DIR, seed)
+    }
 
-            chunk.borrow_mut().set_left(&left);
-            chunk.borrow_mut().set_right(&right);
-            chunk.borrow_mut().set_front(&front);
-            chunk.borrow_mut().set_back(&back);
+    // Saves every edited chunk that is still loaded; unloaded ones were saved on eviction.
+    pub fn save(&mut self) {
+        for (position, chunk) in self.chunks.iter() {
+            let chunk = chunk.borrow();
+
+            if chunk.is_modified() {
+                if let Err(e) = self.storage.store(*position, &chunk.blocks()) {
+                    eprintln!("{:?}", e);
+                }
+            }
+        }
+
+        if let Err(e) = self.storage.flush() {
+            eprintln!("{:?}", e);
+        }
+
+        if let Err(e) = self.exploration.save() {
+            eprintln!("{:?}", e);
+        }
+    }
+
+    pub fn exploration(&self) -> &ExplorationMap {
+        &self.exploration
+    }
+
+    fn insert_generated(&mut self, generation: u32, chunk: Chunk) {
+        if generation != self.generation {
+            return;
         }
+
+        let position = chunk.local_position();
+
+        // A freshly generated chunk has lost any edits, including beacons placed in it. Saved
+        // chunks bring theirs back.
+        self.beacons.retain(|(x, _, z)| Self::chunk_position(*x, *z) != position);
+
+        if chunk.is_modified() {
+            self.beacons.extend(chunk.find_blocks(Block::Beacon.id));
+        }
+
+        self.pending.remove(&position);
+        self.exploration.mark(position);
+        self.chunks.insert(position, RefCell::new(chunk));
+        self.dirty.get_mut().insert_around(position);
+        let _ = self.events.send(GameEvent::ChunkLoaded { position });
     }
 
-    pub fn update(&mut self, device: &Device, camera: &Camera) {
+    pub fn update(&mut self, camera: &Camera) {
         let (x, z) = Self::to_local_position(camera.position());
         let r = self.render_distance;
-        let mut next_buffers = HashMap::new();
-        self.vertex_count = 0;
+        let mut meshing = 0;
+        self.busy = !self.pending.is_empty();
+        self.meshes_built = 0;
+        self.mesh_queue.clear();
 
-        for x in (x - (r + 1))..(x + (r + 1)) {
-            for z in (z - (r + 1))..(z + (r + 1)) {
-                let chunk = if let Some(chunk) = self.chunks.get(&(x, z)) {
-                    chunk
-                } else {
-                    self.chunks.insert((x, z), RefCell::new(Chunk::new((x, z), &self.noise)));
-                    self.chunks.get(&(x, z)).unwrap()
-                };
+        if self.center != (x, z) {
+            let _ = self.events.send(GameEvent::PlayerMovedChunk { from: self.center, to: (x, z) });
+            self.center = (x, z);
+            self.rescan = true;
+        }
 
-                if chunk.borrow().left().is_none() {
-                    if let Some(c) = self.chunks.get(&(x - 1, z)) {
-                        chunk.borrow_mut().set_left(c.borrow().blocks());
-                    }
-                } else if chunk.borrow().right().is_none() {
-                    if let Some(c) = self.chunks.get(&(x + 1, z)) {
-                        chunk.borrow_mut().set_right(c.borrow().blocks());
-                    }
-                } else if chunk.borrow().front().is_none() {
-                    if let Some(c) = self.chunks.get(&(x, z - 1)) {
-                        chunk.borrow_mut().set_front(c.borrow().blocks());
-                    }
-                } else if chunk.borrow().back().is_none() {
-                    if let Some(c) = self.chunks.get(&(x, z + 1)) {
-                        chunk.borrow_mut().set_back(c.borrow().blocks());
+        for (generation, chunk) in self.generated_receiver.try_iter().collect::<Vec<_>>() {
+            self.insert_generated(generation, chunk);
+            self.busy = true;
+        }
+
+        let in_range = |(cx, cz): (i32, i32)| (x - (r + 1)..x + (r + 1)).contains(&cx) && (z - (r + 1)..z + (r + 1)).contains(&cz);
+        let rescan = std::mem::take(&mut self.rescan);
+
+        if rescan {
+            for x in (x - (r + 1))..(x + (r + 1)) {
+                for z in (z - (r + 1))..(z + (r + 1)) {
+                    if self.chunks.contains_key(&(x, z)) {
+                        self.dirty.get_mut().insert((x, z));
+                    } else {
+                        self.request_chunk((x, z));
                     }
                 }
+            }
+        }
 
-                chunk.borrow_mut().update(&mut self.pool);
+        for (x, z) in self.dirty.get_mut().take() {
+            if !in_range((x, z)) {
+                continue;
+            }
 
-                if let Some(b) = self.buffers.remove(&(x, z)) {
-                    if chunk.borrow().needs_buffer() {
-                        let buffer = ChunkBuffer::new(device, chunk.borrow().mesh());
-                        self.vertex_count += buffer.vertex_count;
-                        next_buffers.insert((x, z), buffer);
-                        chunk.borrow_mut().set_needs_buffer(false);
-                    } else {
-                        self.vertex_count += b.vertex_count;
-                        next_buffers.insert((x, z), b);
+            let chunk = if let Some(chunk) = self.chunks.get(&(x, z)) { chunk } else { continue };
+
+            if !chunk.borrow().is_decorated() && self.has_all_neighbors((x, z)) {
+                self.decorate((x, z));
+                self.busy = true;
+            }
+
+            if chunk.borrow().left().is_none() {
+                if let Some(c) = self.chunks.get(&(x - 1, z)) {
+                    chunk.borrow_mut().set_left(c.borrow().sections());
+                    self.busy = true;
+                }
+            }
+
+            if chunk.borrow().right().is_none() {
+                if let Some(c) = self.chunks.get(&(x + 1, z)) {
+                    chunk.borrow_mut().set_right(c.borrow().sections());
+                    self.busy = true;
+                }
+            }
+
+            if chunk.borrow().front().is_none() {
+                if let Some(c) = self.chunks.get(&(x, z - 1)) {
+                    chunk.borrow_mut().set_front(c.borrow().sections());
+                    self.busy = true;
+                }
+            }
+
+            if chunk.borrow().back().is_none() {
+                if let Some(c) = self.chunks.get(&(x, z + 1)) {
+                    chunk.borrow_mut().set_back(c.borrow().sections());
+                    self.busy = true;
+                }
+            }
+
+            chunk.borrow_mut().set_lod_scale(self.lod.scale((x - self.center.0).abs().max((z - self.center.1).abs())));
+
+            if chunk.borrow_mut().receive_mesh() {
+                self.meshes_built += 1;
+                self.busy = true;
+            }
+
+            // Chunks with a job running or waiting for one stay dirty until it's picked up.
+            if chunk.borrow().is_generating_mesh() {
+                meshing += 1;
+                self.busy = true;
+                self.dirty.borrow_mut().insert((x, z));
+            } else if chunk.borrow().needs_mesh() {
+                self.mesh_queue.push((x, z), camera);
+                self.busy = true;
+                self.dirty.borrow_mut().insert((x, z));
+            }
+
+            let updated = chunk.borrow_mut().take_updated_sections();
+
+            if updated != 0 {
+                let chunk = chunk.borrow();
+                self.shown.insert((x, z));
+
+                for section in (0..Chunk::SECTION_COUNT).filter(|s| updated >> s & 1 == 1) {
+                    self.updates.push(WorldUpdate::Mesh {
+                        position: (x, z),
+                        section,
+                        height: chunk.filled_height(),
+                        mesh: chunk.section_mesh(section).clone(),
+                    });
+                }
+            }
+        }
+
+        while meshing < self.jobs.threads() {
+            let position = if let Some(position) = self.mesh_queue.pop() { position } else { break };
+            self.chunks[&position].borrow_mut().generate_mesh(&self.jobs);
+            meshing += 1;
+        }
+
+        if rescan {
+            // Chunks that left the render distance have their buffers dropped; the chunk sends its
+            // meshes again if it comes back before being evicted.
+            let left = self.shown.iter().filter(|p| !in_range(**p)).copied().collect::<Vec<_>>();
+
+            for position in left {
+                self.shown.remove(&position);
+                self.updates.push(WorldUpdate::Unload { position });
+
+                if let Some(chunk) = self.chunks.get(&position) {
+                    chunk.borrow_mut().reupload_sections();
+                }
+            }
+
+            self.evict((x, z));
+        }
+    }
+
+    // Everything the render thread needs to catch up with the updates since the last call, in
+    // the order it happened.
+    pub fn take_updates(&mut self) -> Vec<WorldUpdate> {
+        std::mem::take(&mut self.updates)
+    }
+
+    pub fn stats(&self) -> WorldStats {
+        WorldStats {
+            loaded_chunks: self.loaded_chunks(),
+            block_bytes: self.chunks.values().map(|c| c.borrow().block_memory_size()).sum(),
+            dirty_chunks: self.dirty_chunks(),
+            queued_meshes: self.queued_meshes(),
+            meshes_built: self.meshes_built,
+            pending_chunks: self.pending_chunks(),
+            settled: self.is_settled(),
+            cache_chunks: self.cache.len(),
+            cache_bytes: self.cache.bytes(),
+            cache_max_bytes: self.cache.max_bytes(),
+            cache_hits: self.cache.hits(),
+            cache_misses: self.cache.misses(),
+            worker_threads: self.jobs.threads(),
+            queued_jobs: JobCategory::ALL.map(|c| self.jobs.queued(c)),
+            ..WorldStats::default()
+        }
+    }
+
+    fn evict(&mut self, (x, z): (i32, i32)) {
+        let limit = self.render_distance + 1 + Self::EVICTION_MARGIN;
+
+        let far = self.chunks
+            .keys()
+            .filter(|(cx, cz)| (cx - x).abs() > limit || (cz - z).abs() > limit)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut stored = false;
+
+        for position in far {
+            if let Some(chunk) = self.chunks.remove(&position) {
+                let mut chunk = chunk.into_inner();
+                self.dirty.get_mut().remove(position);
+
+                if chunk.is_modified() {
+                    if let Err(e) = self.storage.store(position, &chunk.blocks()) {
+                        eprintln!("{:?}", e);
                     }
-                } else if chunk.borrow().has_mesh() {
-                    let buffer = ChunkBuffer::new(device, chunk.borrow().mesh());
-                    self.vertex_count += buffer.vertex_count;
-                    next_buffers.insert((x, z), buffer);
-                    chunk.borrow_mut().set_needs_buffer(false);
+
+                    stored = true;
                 }
+
+                chunk.unload();
+                self.cache.insert(chunk);
+            }
+        }
+
+        if stored {
+            if let Err(e) = self.storage.flush() {
+                eprintln!("{:?}", e);
+            }
+        }
+    }
+
+    fn has_all_neighbors(&self, (x, z): (i32, i32)) -> bool {
+        (-1..=1).all(|dx| (-1..=1).all(|dz| self.chunks.contains_key(&(x + dx, z + dz))))
+    }
+
+    // Second generation pass; runs once every neighbor has terrain so features can cross borders.
+    fn decorate(&self, position: (i32, i32)) {
+        let placements = {
+            let chunk = self.chunks[&position].borrow();
+            let seed = self.generator.seed();
+            let mut placements = self.structures.place(&chunk, self.generator.as_ref());
+            placements.extend(decoration::decorate(&chunk, seed));
+            placements
+        };
+
+        // Later placements see the earlier ones through the view, as if each was written at once.
+        let mut view = self.get_blocks(&Self::bounds(placements.iter().map(|p| p.position)));
+        let mut blocks = vec![];
+
+        for placement in placements {
+            let (x, y, z) = placement.position;
+            let existing = if let Some(id) = view.get(x, y, z) { Block::block(id) } else { continue };
+
+            let free = existing.has_tag(BlockTag::Replaceable)
+                || (placement.replace && !existing.has_tag(BlockTag::Unbreakable));
+
+            if free {
+                view.set(x, y, z, placement.block.id);
+                blocks.push((placement.position, placement.block));
             }
         }
 
-        self.buffers = next_buffers;
+        let touched = self.write_blocks(blocks);
+
+        self.chunks[&position].borrow_mut().set_decorated(true);
+
+        // Features can land anywhere in the column, so every section of the neighbors is redone.
+        for (x, z) in touched {
+            self.dirty.borrow_mut().insert((x, z));
+            self.notify_neighbors((x, z), [(-1, 0), (1, 0), (0, -1), (0, 1)].map(|(dx, dz)| ((x + dx, z + dz), Chunk::ALL_SECTIONS)));
+        }
     }
 
     pub fn get_target(&self, camera: &Camera) -> Option<Target> {
@@ -154,6 +603,8 @@ impl World {
             camera.position().z.floor() as i32,
         );
 
+        let view = self.get_blocks(&Aabb3::new(Point3::new(cx - 6, cy - 6, cz - 6), Point3::new(cx + 6, cy + 6, cz + 6)));
+
         let mut nearest_block: Option<Target> = None;
         let mut nearest = 1000f32;
         let mut nearest_block_d = Block::Air;
@@ -168,10 +619,9 @@ impl World {
                         continue;
                     }
 
-                    let block_id = self.get_block(x, y, z)
-                        .unwrap_or(0);
+                    if !view.is_solid(x, y, z) { continue; }
 
-                    if block_id == 0 || block_id == Block::Water.id { continue; }
+                    let block_id = view.get(x, y, z).unwrap_or(0);
 
                     let bb = Aabb3::new(
                         Point3::new(x as f32, y as f32, z as f32),
@@ -216,45 +666,24 @@ impl World {
             let p = target.position;
             let p = vec3(p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
 
-            let chunk = self.get_chunk(p.x, p.y, p.z).unwrap();
+            let broken = if let Some(id) = self.get_block(p.x, p.y, p.z) { Block::block(id) } else { return };
 
-            let (lx, lz) = {
-                let pp = chunk.borrow().world_position();
-                ((p.x - pp.0).abs(), (p.z - pp.1).abs())
-            };
+            if broken.has_tag(BlockTag::Unbreakable) || broken.has_tag(BlockTag::Replaceable) {
+                return;
+            }
 
-            let (cx, cz) = chunk.borrow().local_position();
+            let chunk = self.get_chunk(p.x, p.y, p.z).unwrap();
 
             chunk.borrow_mut().place_block_at_world_position(&Block::Air, (p.x, p.y, p.z));
-            let c = chunk;
+            let _ = self.events.send(GameEvent::BlockBroken { position: (p.x, p.y, p.z), block: broken });
 
-            if lx == 0 {
-                if let Some(chunk) = self.chunks.get(&(cx - 1, cz)) {
-                    chunk.borrow_mut().set_right(c.borrow().blocks());
-                    chunk.borrow_mut().set_mesh_generated(false);
-                }
-            } else if lx == Chunk::WIDTH - 1 {
-                if let Some(chunk) = self.chunks.get(&(cx + 1, cz)) {
-                    chunk.borrow_mut().set_left(c.borrow().blocks());
-                    chunk.borrow_mut().set_mesh_generated(false);
-                }
-            }
+            self.track_beacon((p.x, p.y, p.z), &Block::Air);
 
-            if lz == 0 {
-                if let Some(chunk) = self.chunks.get(&(cx, cz - 1)) {
-                    chunk.borrow_mut().set_back(c.borrow().blocks());
-                    chunk.borrow_mut().set_mesh_generated(false);
-                }
-            } else if lz == Chunk::DEPTH - 1 {
-                if let Some(chunk) = self.chunks.get(&(cx, cz + 1)) {
-                    chunk.borrow_mut().set_front(c.borrow().blocks());
-                    chunk.borrow_mut().set_mesh_generated(false);
-                }
-            }
+            self.mark_dirty(&[(p.x, p.y, p.z)]);
         }
     }
 
-    pub fn place_block(&mut self, target: Option<&Target>) {
+    pub fn place_block(&mut self, target: Option<&Target>, block: &Block) {
         if let Some(target) = target {
             let p = target.position;
             let face = target.face;
@@ -271,60 +700,222 @@ impl World {
 
             let s = vec3(spot.x.floor() as i32, spot.y.floor() as i32, spot.z.floor() as i32);
 
+            let existing = if let Some(id) = self.get_block(s.x, s.y, s.z) { Block::block(id) } else { return };
+
+            if !existing.has_tag(BlockTag::Replaceable) {
+                return;
+            }
+
             let chunk = self.get_chunk(s.x, s.y, s.z).unwrap();
 
-            let (lx, lz) = {
-                let p = chunk.borrow().world_position();
-                ((s.x - p.0).abs(), (s.z - p.1).abs())
-            };
+            chunk.borrow_mut().place_block_at_world_position(block, (s.x, s.y, s.z));
+            let _ = self.events.send(GameEvent::BlockPlaced { position: (s.x, s.y, s.z), block: *block });
 
-            let (cx, cz) = chunk.borrow().local_position();
+            self.track_beacon((s.x, s.y, s.z), block);
 
-            chunk.borrow_mut().place_block_at_world_position(&Block::Stone, (s.x, s.y, s.z));
+            self.mark_dirty(&[(s.x, s.y, s.z)]);
+        }
+    }
 
-            let c = chunk;
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: &Block) {
+        self.set_blocks([((x, y, z), *block)]);
+    }
 
-            if lx == 0 {
-                if let Some(chunk) = self.chunks.get(&(cx - 1, cz)) {
-                    chunk.borrow_mut().set_right(c.borrow().blocks());
-                    chunk.borrow_mut().set_mesh_generated(false);
-                }
-            } else if lx == Chunk::WIDTH - 1 {
-                if let Some(chunk) = self.chunks.get(&(cx + 1, cz)) {
-                    chunk.borrow_mut().set_left(c.borrow().blocks());
-                    chunk.borrow_mut().set_mesh_generated(false);
+    fn track_beacon(&mut self, position: (i32, i32, i32), block: &Block) {
+        if block.id == Block::Beacon.id {
+            self.beacons.insert(position);
+        } else {
+            self.beacons.remove(&position);
+        }
+    }
+
+    // Beam bases for every beacon whose chunk is still loaded or cached, so landmarks stay visible
+    // well beyond the render distance.
+    pub fn beacons(&self) -> Vec<[f32; 3]> {
+        self.beacons
+            .iter()
+            .filter(|(x, _, z)| {
+                let position = Self::chunk_position(*x, *z);
+                self.chunks.contains_key(&position) || self.cache.contains(position)
+            })
+            .map(|(x, y, z)| [*x as f32 + 0.5, *y as f32 + 1.0, *z as f32 + 0.5])
+            .collect()
+    }
+
+    // Every edit goes through here once its blocks are written. Sections whose meshes may have
+    // changed are remeshed, and chunks bordering an edited block are notified with the sections
+    // the edit reaches, once per pair of chunks however many blocks changed.
+    pub fn mark_dirty(&self, positions: &[(i32, i32, i32)]) {
+        let mut origins = HashMap::<_, u64>::new();
+        let mut neighbors = HashMap::<_, HashMap<_, u64>>::new();
+
+        for position in positions {
+            let origin = Self::chunk_position(position.0, position.2);
+
+            for (chunk, section) in Self::dirty_sections(*position) {
+                if chunk == origin {
+                    *origins.entry(origin).or_default() |= 1 << section;
+                } else {
+                    *neighbors.entry(origin).or_default().entry(chunk).or_default() |= 1 << section;
                 }
             }
+        }
 
-            if lz == 0 {
-                if let Some(chunk) = self.chunks.get(&(cx, cz - 1)) {
-                    chunk.borrow_mut().set_back(c.borrow().blocks());
-                    chunk.borrow_mut().set_mesh_generated(false);
-                }
-            } else if lz == Chunk::DEPTH - 1 {
-                if let Some(chunk) = self.chunks.get(&(cx, cz + 1)) {
-                    chunk.borrow_mut().set_front(c.borrow().blocks());
-                    chunk.borrow_mut().set_mesh_generated(false);
-                }
+        for (origin, sections) in origins {
+            if let Some(chunk) = self.chunks.get(&origin) {
+                let mut chunk = chunk.borrow_mut();
+                chunk.set_modified(true);
+                chunk.invalidate(sections);
+                self.dirty.borrow_mut().insert(origin);
             }
         }
+
+        for (origin, neighbors) in neighbors {
+            self.notify_neighbors(origin, neighbors);
+        }
     }
 
-    pub fn buffers(&self, frustum: &Frustum) -> Vec<&ChunkBuffer> {
-        let f = frustum.get();
+    // Tells loaded chunks next to `position` that its blocks changed. Each gets a fresh copy of
+    // them to mesh its border against and remeshes the given sections on the next update, so both
+    // sides of an edit on a border are rebuilt together.
+    fn notify_neighbors(&self, position: (i32, i32), neighbors: impl IntoIterator<Item = ((i32, i32), u64)>) {
+        let chunk = if let Some(chunk) = self.chunks.get(&position) { chunk.borrow() } else { return };
 
-        self.buffers
-            .iter()
-            .filter(|((x, z), _)| {
-                let bb = Aabb3::new(
-                    Point3::new(*x as f32 * 16.0, 0.0, *z as f32 * 16.0),
-                    Point3::new(*x as f32 * 16.0 + 16.0, 256.0, *z as f32 * 16.0 + 16.0),
-                );
+        for (neighbor, sections) in neighbors {
+            if let Some(c) = self.chunks.get(&neighbor) {
+                let offset = (position.0 - neighbor.0, position.1 - neighbor.1);
+                c.borrow_mut().neighbor_changed(offset, chunk.sections(), sections);
+                self.dirty.borrow_mut().insert(neighbor);
+            }
+        }
+    }
 
-                f.contains(&bb) != Relation::Out
-            })
-            .map(|(a, b)| b)
-            .collect()
+    // The section holding the block, the ones above or below it when the block sits on a
+    // section boundary, and the same sections of any chunk the block borders except the one above:
+    // a neighbor's faces only change next to the block, but the dirt under it can gain or lose its
+    // grass overlay.
+    pub fn dirty_sections((x, y, z): (i32, i32, i32)) -> Vec<((i32, i32), i32)> {
+        if !(0..Chunk::HEIGHT).contains(&y) {
+            return vec![];
+        }
+
+        let (cx, cz) = Self::chunk_position(x, z);
+        let (lx, lz) = (x.rem_euclid(Chunk::WIDTH), z.rem_euclid(Chunk::DEPTH));
+        let section = y / Chunk::SECTION_HEIGHT;
+
+        let mut sections = vec![section];
+
+        if y % Chunk::SECTION_HEIGHT == 0 && section > 0 {
+            sections.push(section - 1);
+        }
+
+        let mut chunks = vec![];
+
+        if lx == 0 {
+            chunks.push((cx - 1, cz));
+        } else if lx == Chunk::WIDTH - 1 {
+            chunks.push((cx + 1, cz));
+        }
+
+        if lz == 0 {
+            chunks.push((cx, cz - 1));
+        } else if lz == Chunk::DEPTH - 1 {
+            chunks.push((cx, cz + 1));
+        }
+
+        let mut dirty = sections.iter().map(|s| ((cx, cz), *s)).collect::<Vec<_>>();
+
+        if y % Chunk::SECTION_HEIGHT == Chunk::SECTION_HEIGHT - 1 && section < Chunk::SECTION_COUNT - 1 {
+            dirty.push(((cx, cz), section + 1));
+        }
+
+        dirty.extend(chunks.iter().flat_map(|c| sections.iter().map(move |s| (*c, *s))));
+        dirty
+    }
+
+    // Top-down preview of the terrain within `radius` chunks of the origin, one pixel per column.
+    // Chunks are generated straight from the generator, so this works before anything is loaded
+    // and leaves the world untouched. Slopes facing north-west are lit and the rest shaded, and
+    // water darkens with depth. Chunks that were never explored are drawn dim and grey.
+    pub fn render_map(&self, radius: i32) -> image::RgbaImage {
+        let size = (radius * 2 * Chunk::WIDTH) as u32;
+        let origin = (-radius * Chunk::WIDTH, -radius * Chunk::DEPTH);
+
+        let chunks = (-radius..radius)
+            .flat_map(|z| (-radius..radius).map(move |x| (x, z)))
+            .collect::<Vec<_>>();
+
+        let generator = self.generator.as_ref();
+
+        let columns: Vec<Vec<(i32, Block, i32)>> = self.jobs.install(|| {
+            chunks
+                .par_iter()
+                .map(|position| {
+                    let blocks = generator.generate(*position);
+
+                    (0..Chunk::WIDTH * Chunk::DEPTH)
+                        .map(|i| Self::map_column(&blocks, i % Chunk::WIDTH, i / Chunk::WIDTH))
+                        .collect()
+                })
+                .collect()
+        });
+
+        let mut heights = HashMap::new();
+
+        for (position, columns) in chunks.iter().zip(columns.iter()) {
+            let (wx, wz) = Chunk::local_to_world_position(*position);
+
+            for (i, column) in columns.iter().enumerate() {
+                heights.insert((wx + i as i32 % Chunk::WIDTH, wz + i as i32 / Chunk::WIDTH), *column);
+            }
+        }
+
+        image::RgbaImage::from_fn(size, size, |px, pz| {
+            let (x, z) = (origin.0 + px as i32, origin.1 + pz as i32);
+            let (y, block, depth) = heights[&(x, z)];
+
+            let shade = if block.id == Block::Water.id {
+                1.0 - (depth as f32 * 0.06).min(0.6)
+            } else {
+                let (ny, _, _) = heights.get(&(x - 1, z - 1)).copied().unwrap_or((y, block, 0));
+                (1.0 + (y - ny) as f32 * 0.12).clamp(0.6, 1.3)
+            };
+
+            let mut color = block.map_color().map(|c| (c as f32 * shade).min(255.0));
+
+            if !self.exploration.is_explored(Self::chunk_position(x, z)) {
+                let grey = color.iter().sum::<f32>() / 3.0;
+                color = color.map(|c| (c + grey) * 0.5 * Self::UNEXPLORED_SHADE);
+            }
+
+            let [r, g, b] = color.map(|c| c as u8);
+            image::Rgba([r, g, b, if block.id == Block::Air.id { 0 } else { 255 }])
+        })
+    }
+
+    // Height, block and water depth of the highest non-air block in a generated column.
+    fn map_column(blocks: &[u8], x: i32, z: i32) -> (i32, Block, i32) {
+        let top = (0..Chunk::HEIGHT)
+            .rev()
+            .find(|y| blocks[Chunk::xyz_to_index(x, *y, z)] != Block::Air.id);
+
+        let y = if let Some(y) = top { y } else { return (0, Block::Air, 0) };
+        let block = Block::block(blocks[Chunk::xyz_to_index(x, y, z)]);
+
+        let depth = (0..y)
+            .rev()
+            .take_while(|y| blocks[Chunk::xyz_to_index(x, *y, z)] == Block::Water.id)
+            .count() as i32;
+
+        (y, block, depth)
+    }
+
+    // One above the highest non-air block of the column at world (x, z), if its chunk is loaded.
+    pub fn surface_height(&self, x: i32, z: i32) -> Option<i32> {
+        let chunk = self.chunks.get(&Self::chunk_position(x, z))?.borrow();
+        let (wx, wz) = chunk.world_position();
+
+        Some(chunk.surface_height(x - wx, z - wz))
     }
 
     pub fn to_local_position(position: &Vector3<f32>) -> (i32, i32) {
@@ -333,6 +924,10 @@ impl World {
         (x / Chunk::WIDTH, z / Chunk::DEPTH)
     }
 
+    pub fn chunk_position(x: i32, z: i32) -> (i32, i32) {
+        (x.div_euclid(Chunk::WIDTH), z.div_euclid(Chunk::DEPTH))
+    }
+
     pub fn get_chunk(&self, x: i32, y: i32, z: i32) -> Option<&RefCell<Chunk>> {
         let (cx, cz) = (
             (x as f32 / Chunk::WIDTH as f32).floor() as i32,
@@ -342,6 +937,96 @@ impl World {
         self.chunks.get(&(cx, cz))
     }
 
+    pub fn is_solid(&self, x: i32, y: i32, z: i32) -> bool {
+        self.get_chunk(x, y, z)
+            .map(|chunk| chunk.borrow().is_solid_at_world_position((x, y, z)))
+            .unwrap_or(false)
+    }
+
+    pub fn is_grounded(&self, position: &Vector3<f32>) -> bool {
+        let (x, z) = (position.x.floor() as i32, position.z.floor() as i32);
+
+        (1..=Self::GROUND_DISTANCE).any(|d| self.is_solid(x, (position.y - d as f32).floor() as i32, z))
+    }
+
+    // Copies the blocks in `aabb`, from its min up to but not including its max, borrowing each
+    // chunk it overlaps once.
+    pub fn get_blocks(&self, aabb: &Aabb3<i32>) -> BlockView {
+        let mut view = BlockView::new(aabb);
+        let (min, max) = (aabb.min, aabb.max);
+        let (y0, y1) = (min.y.max(0), max.y.min(Chunk::HEIGHT));
+
+        let (c0, c1) = (Self::chunk_position(min.x, min.z), Self::chunk_position(max.x - 1, max.z - 1));
+
+        for cx in c0.0..=c1.0 {
+            for cz in c0.1..=c1.1 {
+                let chunk = if let Some(chunk) = self.chunks.get(&(cx, cz)) { chunk.borrow() } else { continue };
+                let (wx, wz) = chunk.world_position();
+
+                for x in min.x.max(wx)..max.x.min(wx + Chunk::WIDTH) {
+                    for z in min.z.max(wz)..max.z.min(wz + Chunk::DEPTH) {
+                        for y in y0..y1 {
+                            if let Some(id) = chunk.block_at_local_position((x - wx, y, z - wz)) {
+                                view.set(x, y, z, id);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        view
+    }
+
+    // Edits many blocks at once, borrowing each chunk once and refreshing each neighbor's border
+    // copy once rather than per block. Blocks in chunks that aren't loaded are skipped.
+    pub fn set_blocks(&mut self, blocks: impl IntoIterator<Item = ((i32, i32, i32), Block)>) {
+        let blocks = blocks
+            .into_iter()
+            .filter(|((x, y, z), _)| (0..Chunk::HEIGHT).contains(y) && self.chunks.contains_key(&Self::chunk_position(*x, *z)))
+            .collect::<Vec<_>>();
+
+        for (position, block) in blocks.iter() {
+            self.track_beacon(*position, block);
+        }
+
+        let positions = blocks.iter().map(|(p, _)| *p).collect::<Vec<_>>();
+        self.write_blocks(blocks);
+        self.mark_dirty(&positions);
+    }
+
+    // Writes blocks grouped by chunk, returning the chunks that were written to. Those are marked
+    // modified so a feature spilling into an already saved neighbor is saved with it.
+    fn write_blocks(&self, blocks: Vec<((i32, i32, i32), Block)>) -> HashSet<(i32, i32)> {
+        let mut by_chunk = HashMap::<_, Vec<_>>::new();
+
+        for ((x, y, z), block) in blocks {
+            by_chunk.entry(Self::chunk_position(x, z)).or_default().push(((x, y, z), block));
+        }
+
+        for (position, blocks) in by_chunk.iter() {
+            let mut chunk = if let Some(chunk) = self.chunks.get(position) { chunk.borrow_mut() } else { continue };
+
+            for (p, block) in blocks {
+                chunk.place_block_at_world_position(block, *p);
+            }
+
+            chunk.set_modified(true);
+        }
+
+        by_chunk.into_keys().collect()
+    }
+
+    // The smallest box holding every position, with its max one past the last block.
+    fn bounds(positions: impl Iterator<Item = (i32, i32, i32)>) -> Aabb3<i32> {
+        let mut positions = positions.map(|(x, y, z)| Point3::new(x, y, z));
+
+        let first = if let Some(first) = positions.next() { first } else { return Aabb3::new(Point3::new(0, 0, 0), Point3::new(0, 0, 0)) };
+        let aabb = positions.fold(Aabb3::new(first, first), |aabb, p| aabb.grow(p));
+
+        Aabb3::new(aabb.min, aabb.max + vec3(1, 1, 1))
+    }
+
     pub fn get_block(&self, x: i32, y: i32, z: i32) -> Option<u8> {
         let (cx, cz) = (
             (x as f32 / Chunk::WIDTH as f32).floor() as i32,
@@ -350,7 +1035,6 @@ impl World {
 
         if let Some(chunk) = self.chunks.get(&(cx, cz)) {
             chunk.borrow().block_at_world_position((x, y, z))
-                .cloned()
         } else {
             None
         }
@@ -0,0 +1,30 @@
+use crate::world::job_system::JobCategory;
+
+// What the world holds and did in its last update, for the debug text.
+#[derive(Debug, Clone, Default)]
+pub struct WorldStats {
+    pub loaded_chunks: usize,
+    // Blocks of the loaded chunks, without their neighbor copies or meshes.
+    pub block_bytes: usize,
+    pub dirty_chunks: usize,
+    pub queued_meshes: usize,
+    // Mesh jobs finished in the last update.
+    pub meshes_built: usize,
+    pub pending_chunks: usize,
+    pub settled: bool,
+
+    pub cache_chunks: usize,
+    pub cache_bytes: usize,
+    pub cache_max_bytes: usize,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+
+    pub worker_threads: usize,
+    // Jobs waiting for a worker, by `JobCategory`.
+    pub queued_jobs: [usize; JobCategory::ALL.len()],
+
+    // Filled in on the render thread, which owns the buffers; see `WorldThread::stats`. Indices
+    // are the one `QuadIndexBuffer` every section shares.
+    pub vertex_bytes: u64,
+    pub index_bytes: u64,
+}
\ No newline at end of file
@@ -0,0 +1,461 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+use cgmath::Vector3;
+use crossbeam::channel::{Receiver, RecvTimeoutError, Sender};
+use wgpu::{Device, Queue};
+use crate::mesh::vertex::Vertex;
+use crate::objects::block::Block;
+use crate::objects::target::Target;
+use crate::scene::camera::Camera;
+use crate::scene::frustum::Frustum;
+use crate::world::chunk::Chunk;
+use crate::world::chunk_buffer::ChunkBuffer;
+use crate::world::chunk_buffer_pool::ChunkBufferPool;
+use crate::world::chunk_mesh::ChunkMesh;
+use crate::world::lod::LodDistances;
+use crate::world::occlusion::SectionVisibility;
+use crate::world::quad_index_buffer::QuadIndexBuffer;
+use crate::world::terrain_generator::TerrainGenerator;
+use crate::world::world::World;
+use crate::world::world_stats::WorldStats;
+
+// Sent to the simulation thread, which applies everything received before its next update.
+pub enum WorldCommand {
+    // The player's camera as of this frame, which chunks stream in around and blocks are targeted
+    // from.
+    Camera(Camera),
+    Generate(Camera),
+    PlaceBlock(Target, Block),
+    RemoveBlock(Target),
+    SetBlock((i32, i32, i32), Block),
+    SetGenerator(Box<dyn TerrainGenerator>),
+    SetSaving(bool),
+    SetRenderDistance(i32),
+    SetLodDistances(LodDistances),
+    SetCacheMemory(usize),
+    SetWorkerThreads(usize),
+    // Answered once every edited chunk is on disk.
+    Save(Sender<()>),
+}
+
+// Sent back after each update, in the order things happened on the simulation thread.
+pub enum WorldUpdate {
+    // A section's latest mesh, empty once it has no faces, and its chunk's filled height.
+    Mesh { position: (i32, i32), section: i32, height: i32, mesh: ChunkMesh },
+    // The chunk left the render distance; it sends its meshes again if it comes back.
+    Unload { position: (i32, i32) },
+    // Every chunk was dropped for a new generator.
+    Clear,
+    Report(WorldReport),
+}
+
+// The simulation thread's state as of its last update.
+#[derive(Debug, Clone, Default)]
+pub struct WorldReport {
+    // Commands applied so far that count towards `WorldThread::is_settled`.
+    pub commands: u64,
+    pub stats: WorldStats,
+    pub target: Option<Target>,
+    pub beacons: Vec<[f32; 3]>,
+    // Whether the camera is standing on a block, see `World::GROUND_DISTANCE`.
+    pub grounded: bool,
+}
+
+// The render thread's side of a `World` that runs on its own thread. Loading, decoration,
+// neighbor stitching, eviction and meshing all happen there, so none of them can hold up a frame;
+// this side only turns the meshes it's sent into buffers and forwards edits and settings. Queries
+// answered from the last report, like the targeted block, are a frame or so behind.
+pub struct WorldThread {
+    commands: Sender<WorldCommand>,
+    updates: Receiver<WorldUpdate>,
+    sent: u64,
+    center: Option<(i32, i32)>,
+    report: WorldReport,
+
+    render_distance: i32,
+
+    // Keyed by chunk and section; sections without any faces have no buffer. Their vertices are in
+    // the pools, opaque and alpha, and freed from them whenever a buffer is dropped.
+    buffers: HashMap<((i32, i32), i32), ChunkBuffer>,
+    vertices: ChunkBufferPool,
+    alpha_vertices: ChunkBufferPool,
+    // Meshes waiting for their buffers. Only UPLOAD_BUDGET bytes are uploaded per update, so
+    // crossing into a new row of chunks doesn't create every buffer in one frame.
+    uploads: VecDeque<((i32, i32), i32, ChunkMesh)>,
+
+    // Of every section and chunk meshes were received for, for culling before they're uploaded.
+    visibility: HashMap<((i32, i32), i32), SectionVisibility>,
+    heights: HashMap<(i32, i32), i32>,
+}
+
+impl WorldThread {
+
+    pub const UPLOAD_BUDGET: usize = 1024 * 1024;
+
+    // How often the simulation thread updates while it has work left, when no command wakes it.
+    pub const TICK: Duration = Duration::from_millis(5);
+
+    pub fn spawn(world: World, render_distance: i32) -> Self {
+        let (commands, command_receiver) = crossbeam::channel::unbounded();
+        let (update_sender, updates) = crossbeam::channel::unbounded();
+
+        std::thread::Builder::new()
+            .name(String::from("world"))
+            .spawn(move || Self::run(world, command_receiver, update_sender))
+            .unwrap();
+
+        Self {
+            commands,
+            updates,
+            sent: 0,
+            center: None,
+            report: WorldReport::default(),
+            render_distance,
+            buffers: HashMap::new(),
+            vertices: ChunkBufferPool::new("Chunk Vertex Pool"),
+            alpha_vertices: ChunkBufferPool::new("Chunk Alpha Vertex Pool"),
+            uploads: VecDeque::new(),
+            visibility: HashMap::new(),
+            heights: HashMap::new(),
+        }
+    }
+
+    // The simulation thread. It sleeps while the world is settled and nothing was sent, and stops
+    // once the render side is dropped.
+    fn run(mut world: World, commands: Receiver<WorldCommand>, updates: Sender<WorldUpdate>) {
+        let mut camera = None;
+        let mut center = None;
+        let mut applied = 0;
+
+        loop {
+            let first = if world.is_settled() {
+                commands.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                commands.recv_timeout(Self::TICK)
+            };
+
+            let first = match first {
+                Ok(command) => Some(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+
+            for command in first.into_iter().chain(commands.try_iter()) {
+                if Self::counts(&mut center, &command) {
+                    applied += 1;
+                }
+
+                match command {
+                    WorldCommand::Camera(c) => camera = Some(c),
+                    WorldCommand::Generate(c) => {
+                        world.generate(&c);
+                        camera = Some(c);
+                    },
+                    WorldCommand::PlaceBlock(target, block) => world.place_block(Some(&target), &block),
+                    WorldCommand::RemoveBlock(target) => world.remove_block(Some(&target)),
+                    WorldCommand::SetBlock((x, y, z), block) => world.set_block(x, y, z, &block),
+                    WorldCommand::SetGenerator(generator) => world.set_generator(generator),
+                    WorldCommand::SetSaving(saving) => world.set_saving(saving),
+                    WorldCommand::SetRenderDistance(r) => world.set_render_distance(r),
+                    WorldCommand::SetLodDistances(lod) => world.set_lod_distances(lod),
+                    WorldCommand::SetCacheMemory(bytes) => world.set_cache_memory(bytes),
+                    WorldCommand::SetWorkerThreads(threads) => world.set_worker_threads(threads),
+                    WorldCommand::Save(done) => {
+                        world.save();
+                        let _ = done.send(());
+                    },
+                }
+            }
+
+            let camera = if let Some(camera) = &camera { camera } else { continue };
+
+            world.update(camera);
+
+            for update in world.take_updates() {
+                let _ = updates.send(update);
+            }
+
+            let _ = updates.send(WorldUpdate::Report(WorldReport {
+                commands: applied,
+                stats: world.stats(),
+                target: world.get_target(camera),
+                beacons: world.beacons(),
+                grounded: world.is_grounded(camera.position()),
+            }));
+        }
+    }
+
+    fn send(&mut self, command: WorldCommand) {
+        if Self::counts(&mut self.center, &command) {
+            self.sent += 1;
+        }
+
+        let _ = self.commands.send(command);
+    }
+
+    // Whether a command may give the simulation thread work, so the world isn't settled until it's
+    // been applied. The camera is sent every frame and only counts when it enters another chunk;
+    // both threads track which one it was last in.
+    fn counts(center: &mut Option<(i32, i32)>, command: &WorldCommand) -> bool {
+        match command {
+            WorldCommand::Camera(camera) | WorldCommand::Generate(camera) => {
+                let chunk = World::to_local_position(camera.position());
+                let moved = *center != Some(chunk);
+                *center = Some(chunk);
+                moved || matches!(command, WorldCommand::Generate(_))
+            },
+            _ => true,
+        }
+    }
+
+    // Hands the simulation thread this frame's camera, then uploads what it has sent since the
+    // last update.
+    pub fn update(&mut self, device: &Device, queue: &Queue, camera: &Camera) {
+        self.send(WorldCommand::Camera(camera.clone()));
+
+        for update in self.updates.try_iter().collect::<Vec<_>>() {
+            match update {
+                WorldUpdate::Mesh { position, section, height, mesh } => {
+                    self.visibility.insert((position, section), mesh.visibility);
+                    self.heights.insert(position, height);
+
+                    if let Some((_, _, queued)) = self.uploads.iter_mut().find(|(p, s, _)| (*p, *s) == (position, section)) {
+                        *queued = mesh;
+                    } else {
+                        self.uploads.push_back((position, section, mesh));
+                    }
+                },
+                WorldUpdate::Unload { position } => {
+                    self.uploads.retain(|(p, _, _)| *p != position);
+                    self.visibility.retain(|(p, _), _| *p != position);
+                    self.heights.remove(&position);
+
+                    for section in 0..Chunk::SECTION_COUNT {
+                        self.drop_buffer((position, section));
+                    }
+                },
+                WorldUpdate::Clear => {
+                    self.buffers.clear();
+                    self.vertices.clear();
+                    self.alpha_vertices.clear();
+                    self.uploads.clear();
+                    self.visibility.clear();
+                    self.heights.clear();
+                },
+                WorldUpdate::Report(report) => self.report = report,
+            }
+        }
+
+        self.upload(device, queue);
+    }
+
+    // Builds queued section buffers, at least one per update however large unless the pools are
+    // full.
+    fn upload(&mut self, device: &Device, queue: &Queue) {
+        let mut uploaded = 0;
+
+        while uploaded < Self::UPLOAD_BUDGET {
+            let ((x, z), section, mesh) = if let Some(upload) = self.uploads.pop_front() { upload } else { break };
+
+            self.drop_buffer(((x, z), section));
+
+            if mesh.is_empty() {
+                continue;
+            }
+
+            let vertex_count = mesh.vertices.len() as u32;
+            let alpha_vertex_count = mesh.alpha_vertices.len() as u32;
+
+            // With the pools at the device limit the section waits until unloading frees room.
+            let first_vertex = if let Some(first) = self.vertices.allocate(device, queue, vertex_count) {
+                first
+            } else {
+                self.uploads.push_front(((x, z), section, mesh));
+                break;
+            };
+
+            let first_alpha_vertex = if let Some(first) = self.alpha_vertices.allocate(device, queue, alpha_vertex_count) {
+                first
+            } else {
+                self.vertices.free(first_vertex, vertex_count);
+                self.uploads.push_front(((x, z), section, mesh));
+                break;
+            };
+
+            self.vertices.write(queue, first_vertex, &mesh.vertices);
+            self.alpha_vertices.write(queue, first_alpha_vertex, &mesh.alpha_vertices);
+
+            let buffer = ChunkBuffer::new(&mesh, (x, z), section, first_vertex, first_alpha_vertex);
+            uploaded += (buffer.vertex_count + buffer.alpha_vertex_count) as usize * std::mem::size_of::<Vertex>();
+            self.buffers.insert(((x, z), section), buffer);
+        }
+    }
+
+    fn drop_buffer(&mut self, key: ((i32, i32), i32)) {
+        if let Some(old) = self.buffers.remove(&key) {
+            self.vertices.free(old.first_vertex, old.vertex_count);
+            self.alpha_vertices.free(old.first_alpha_vertex, old.alpha_vertex_count);
+        }
+    }
+
+    // Loads the area around the camera on the simulation thread, which waits for every chunk
+    // before it updates again.
+    pub fn generate(&mut self, camera: &Camera) {
+        self.send(WorldCommand::Generate(camera.clone()));
+    }
+
+    pub fn place_block(&mut self, target: Option<&Target>, block: &Block) {
+        if let Some(target) = target {
+            self.send(WorldCommand::PlaceBlock(target.clone(), *block));
+        }
+    }
+
+    pub fn remove_block(&mut self, target: Option<&Target>) {
+        if let Some(target) = target {
+            self.send(WorldCommand::RemoveBlock(target.clone()));
+        }
+    }
+
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: &Block) {
+        self.send(WorldCommand::SetBlock((x, y, z), *block));
+    }
+
+    pub fn set_generator(&mut self, generator: Box<dyn TerrainGenerator>) {
+        self.send(WorldCommand::SetGenerator(generator));
+    }
+
+    pub fn set_saving(&mut self, saving: bool) {
+        self.send(WorldCommand::SetSaving(saving));
+    }
+
+    pub fn render_distance(&self) -> i32 {
+        self.render_distance
+    }
+
+    pub fn set_render_distance(&mut self, render_distance: i32) {
+        self.render_distance = render_distance;
+        self.send(WorldCommand::SetRenderDistance(render_distance));
+    }
+
+    pub fn set_lod_distances(&mut self, lod: LodDistances) {
+        self.send(WorldCommand::SetLodDistances(lod));
+    }
+
+    pub fn set_cache_memory(&mut self, bytes: usize) {
+        self.send(WorldCommand::SetCacheMemory(bytes));
+    }
+
+    pub fn set_worker_threads(&mut self, threads: usize) {
+        self.send(WorldCommand::SetWorkerThreads(threads));
+    }
+
+    // Blocks until the simulation thread has saved, since this is usually right before exiting.
+    pub fn save(&mut self) {
+        let (done, saved) = crossbeam::channel::bounded(1);
+        self.send(WorldCommand::Save(done));
+        let _ = saved.recv();
+    }
+
+    pub fn stats(&self) -> WorldStats {
+        WorldStats {
+            vertex_bytes: self.vertices.used_bytes() + self.alpha_vertices.used_bytes(),
+            index_bytes: QuadIndexBuffer::BYTES,
+            ..self.report.stats.clone()
+        }
+    }
+
+    pub fn is_grounded(&self) -> bool {
+        self.report.grounded
+    }
+
+    // The simulation thread has applied every command and has nothing left to load or mesh, and
+    // every mesh it sent is uploaded, so frames are complete.
+    pub fn is_settled(&self) -> bool {
+        self.report.stats.settled && self.report.commands == self.sent && self.uploads.is_empty()
+    }
+
+    pub fn target(&self) -> Option<Target> {
+        self.report.target.clone()
+    }
+
+    pub fn beacons(&self) -> &[[f32; 3]] {
+        &self.report.beacons
+    }
+
+    pub fn vertices(&self, alpha: bool) -> &ChunkBufferPool {
+        if alpha { &self.alpha_vertices } else { &self.vertices }
+    }
+
+    // Sections are culled one at a time, so a tall column only draws the part the frustum
+    // reaches, and sections above the column's highest block are skipped outright.
+    pub fn buffers(&self, frustum: &Frustum, eye: &Vector3<f32>) -> Vec<&ChunkBuffer> {
+        let f = frustum.get();
+        let visible = self.visible_sections(&f, eye);
+
+        self.buffers
+            .iter()
+            .filter(|((position, section), _)| {
+                let height = self.heights.get(position).copied().unwrap_or(Chunk::HEIGHT);
+                Frustum::is_section_visible(&f, *position, *section, height)
+            })
+            .filter(|(key, _)| visible.as_ref().is_none_or(|v| v.contains(key)))
+            .map(|(_, b)| b)
+            .collect()
+    }
+
+    // Sections the eye could see through open space, walking outwards from its own section. A
+    // section is only left through faces its visibility connects to the face it was entered by,
+    // and never back towards the eye, so terrain that walls off caves or the far side of a
+    // mountain stops the walk. None when the eye is above or below the world, where nothing is
+    // culled this way.
+    fn visible_sections(&self, frustum: &collision::Frustum<f32>, eye: &Vector3<f32>) -> Option<HashSet<((i32, i32), i32)>> {
+        let section = (eye.y / Chunk::SECTION_HEIGHT as f32).floor() as i32;
+
+        if !(0..Chunk::SECTION_COUNT).contains(&section) {
+            return None;
+        }
+
+        let start = (World::chunk_position(eye.x.floor() as i32, eye.z.floor() as i32), section);
+        let limit = self.render_distance + 1;
+
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, None, 0u8)]);
+
+        while let Some((((x, z), section), entered, directions)) = queue.pop_front() {
+            let visibility = self.visibility
+                .get(&((x, z), section))
+                .copied()
+                .unwrap_or(SectionVisibility::ALL);
+
+            for face in 0..6 {
+                if directions >> SectionVisibility::opposite(face) & 1 == 1 {
+                    continue;
+                }
+
+                if let Some(entered) = entered {
+                    if !visibility.connects(entered, face) {
+                        continue;
+                    }
+                }
+
+                let (dx, ds, dz) = SectionVisibility::step(face);
+                let next = ((x + dx, z + dz), section + ds);
+                let ((nx, nz), ns) = next;
+
+                if !(0..Chunk::SECTION_COUNT).contains(&ns)
+                    || (nx - start.0.0).abs() > limit
+                    || (nz - start.0.1).abs() > limit
+                    || visited.contains(&next)
+                    || !Frustum::is_chunk_visible(frustum, (nx, nz), Chunk::HEIGHT) {
+                    continue;
+                }
+
+                visited.insert(next);
+                queue.push_back((next, Some(SectionVisibility::opposite(face)), directions | 1 << face));
+            }
+        }
+
+        Some(visited)
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+use crate::world::floating_islands_generator::FloatingIslandsGenerator;
+use crate::world::noise_terrain_generator::NoiseTerrainGenerator;
+use crate::world::terrain_generator::TerrainGenerator;
+use crate::world::worldgen_config::WorldgenConfig;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorldType {
+    #[default]
+    Default,
+    FloatingIslands,
+}
+
+impl WorldType {
+
+    pub fn generator(&self, config: &WorldgenConfig) -> Box<dyn TerrainGenerator> {
+        match self {
+            WorldType::Default => Box::new(NoiseTerrainGenerator::new(config)),
+            WorldType::FloatingIslands => Box::new(FloatingIslandsGenerator::new(config)),
+        }
+    }
+
+}
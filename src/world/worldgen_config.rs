@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use anyhow::*;
+use serde::Deserialize;
+use crate::world::world_type::WorldType;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WorldgenConfig {
+    pub world_type: WorldType,
+    pub seed: u32,
+    pub octaves: usize,
+    pub frequency: f64,
+    pub persistence: f64,
+    pub lacunarity: f64,
+    pub amplitude: f64,
+    pub base_height: f64,
+    pub sea_level: i32,
+    pub snow_height: i32,
+}
+
+impl Default for WorldgenConfig {
+    fn default() -> Self {
+        Self {
+            world_type: WorldType::Default,
+            seed: 1,
+            octaves: 4,
+            frequency: 0.0348,
+            persistence: 1.6,
+            lacunarity: 0.2,
+            amplitude: 32.0,
+            base_height: 64.0,
+            sea_level: 60,
+            snow_height: 84,
+        }
+    }
+}
+
+impl WorldgenConfig {
+
+    pub const PATH: &'static str = "worldgen.toml";
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = std::fs::read_to_string(path.as_ref())
+            .with_context(|| format!("failed to read worldgen config {:?}", path.as_ref()))?;
+
+        Ok(toml::from_str(&data)?)
+    }
+
+}
+
+// Polls the config file's modification time so terrain can be tweaked while the game runs.
+pub struct WorldgenWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: instant::Instant,
+}
+
+impl WorldgenWatcher {
+
+    pub const INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let modified = Self::modified(&path);
+
+        Self {
+            path,
+            modified,
+            last_check: instant::Instant::now(),
+        }
+    }
+
+    // Falls back to the defaults when the file does not exist.
+    pub fn config(&self) -> WorldgenConfig {
+        if !self.path.exists() {
+            return WorldgenConfig::default();
+        }
+
+        WorldgenConfig::load(&self.path).unwrap_or_else(|e| {
+            eprintln!("{:?}", e);
+            WorldgenConfig::default()
+        })
+    }
+
+    // Returns the new config once after the file changes; parse errors are reported and skipped
+    // so a half-saved file does not throw away the current world.
+    pub fn poll(&mut self) -> Option<WorldgenConfig> {
+        if self.last_check.elapsed() < Self::INTERVAL {
+            return None;
+        }
+
+        self.last_check = instant::Instant::now();
+
+        let modified = Self::modified(&self.path);
+
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+
+        self.modified = modified;
+
+        WorldgenConfig::load(&self.path)
+            .map_err(|e| eprintln!("{:?}", e))
+            .ok()
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+}